@@ -0,0 +1,309 @@
+use std::ops;
+
+use num::bigint::BigInt;
+use num::complex::Complex;
+use num::rational::Ratio;
+use num::{Integer, Num, One, Signed, Zero};
+
+use crate::CRing;
+
+// The algebraic structures below CRing, each adding what an algorithm may
+// rely on:
+//
+//   CRing > IntegralDomain > GCDDomain > EuclideanDomain > Field
+//
+// OrderedRing is separate, since most fields used here (Complex, ModInt)
+// have no compatible order. Floats are treated as an (approximate) field.
+
+// A commutative ring without zero divisors, so a * b == 0 only if a == 0 or
+// b == 0 and nonzero factors can be cancelled.
+pub trait IntegralDomain: CRing {}
+
+// An integral domain in which any two elements have a greatest common
+// divisor. The gcd is only unique up to a unit; implementations pick a
+// normalized representative where one exists (non-negative integers, monic
+// polynomials).
+pub trait GCDDomain: IntegralDomain {
+    fn gcd(&self, other: &Self) -> Self;
+}
+
+// An integral domain with division with remainder, a = q * b + r where r is
+// zero or smaller than b. Panics when dividing by zero, like the primitive
+// integer types.
+pub trait EuclideanDomain: GCDDomain {
+    fn quo_rem(&self, other: &Self) -> (Self, Self)
+    where
+        Self: Sized;
+}
+
+// Every nonzero element has a multiplicative inverse.
+pub trait Field: EuclideanDomain + ops::Div<Output = Self> {
+    fn inverse(&self) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+// A ring with a total order compatible with addition and multiplication by
+// positive elements.
+pub trait OrderedRing: CRing + PartialOrd {
+    fn is_positive(&self) -> bool
+    where
+        Self: Sized,
+    {
+        *self > Self::zero()
+    }
+
+    fn is_negative(&self) -> bool
+    where
+        Self: Sized,
+    {
+        *self < Self::zero()
+    }
+}
+
+// Euclid's algorithm for any type with a division with remainder.
+pub fn euclidean_gcd<T>(a: &T, b: &T) -> T
+where
+    T: EuclideanDomain + Clone,
+{
+    let mut a = a.clone();
+    let mut b = b.clone();
+    while !b.is_zero() {
+        let (_, rem) = a.quo_rem(&b);
+        a = b;
+        b = rem;
+    }
+    a
+}
+
+// Signed integers. The unsigned types are left out since they are not closed
+// under subtraction.
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl IntegralDomain for $t {}
+
+        impl GCDDomain for $t {
+            fn gcd(&self, other: &Self) -> Self {
+                Integer::gcd(self, other)
+            }
+        }
+
+        // The remainder is always non-negative.
+        impl EuclideanDomain for $t {
+            fn quo_rem(&self, other: &Self) -> (Self, Self) {
+                (self.div_euclid(*other), self.rem_euclid(*other))
+            }
+        }
+
+        impl OrderedRing for $t {}
+    )*};
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize);
+
+// In a field every nonzero element divides every other, so the remainder is
+// always zero and the gcd is either zero or one.
+macro_rules! impl_float {
+    ($($t:ty),*) => {$(
+        impl IntegralDomain for $t {}
+
+        impl GCDDomain for $t {
+            fn gcd(&self, other: &Self) -> Self {
+                if *self == 0.0 && *other == 0.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+
+        impl EuclideanDomain for $t {
+            fn quo_rem(&self, other: &Self) -> (Self, Self) {
+                if *other == 0.0 {
+                    panic!("division by zero");
+                }
+                (self / other, 0.0)
+            }
+        }
+
+        impl Field for $t {
+            fn inverse(&self) -> Option<Self> {
+                if *self == 0.0 {
+                    None
+                } else {
+                    Some(1.0 / self)
+                }
+            }
+        }
+
+        impl OrderedRing for $t {}
+    )*};
+}
+
+impl_float!(f32, f64);
+
+impl IntegralDomain for BigInt {}
+
+impl GCDDomain for BigInt {
+    fn gcd(&self, other: &Self) -> Self {
+        Integer::gcd(self, other)
+    }
+}
+
+impl EuclideanDomain for BigInt {
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        let (quo, rem) = self.div_mod_floor(other);
+        if Signed::is_negative(&rem) {
+            // only reachable for a negative divisor
+            (quo + 1, rem - other)
+        } else {
+            (quo, rem)
+        }
+    }
+}
+
+impl OrderedRing for BigInt {}
+
+impl<T> IntegralDomain for Ratio<T> where T: Clone + Integer {}
+
+impl<T> GCDDomain for Ratio<T>
+where
+    T: Clone + Integer,
+{
+    fn gcd(&self, other: &Self) -> Self {
+        if self.is_zero() && other.is_zero() {
+            Self::zero()
+        } else {
+            Self::one()
+        }
+    }
+}
+
+impl<T> EuclideanDomain for Ratio<T>
+where
+    T: Clone + Integer,
+{
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        (self.clone() / other.clone(), Self::zero())
+    }
+}
+
+impl<T> Field for Ratio<T>
+where
+    T: Clone + Integer,
+{
+    fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.recip())
+        }
+    }
+}
+
+impl<T> OrderedRing for Ratio<T> where T: Clone + Integer {}
+
+// Complex numbers over a field, e.g. Complex<f64> or Complex<Ratio<i64>>.
+impl<T> IntegralDomain for Complex<T> where T: Field + Clone + Num + ops::Neg<Output = T> {}
+
+impl<T> GCDDomain for Complex<T>
+where
+    T: Field + Clone + Num + ops::Neg<Output = T>,
+{
+    fn gcd(&self, other: &Self) -> Self {
+        if self.is_zero() && other.is_zero() {
+            Self::zero()
+        } else {
+            Self::one()
+        }
+    }
+}
+
+impl<T> EuclideanDomain for Complex<T>
+where
+    T: Field + Clone + Num + ops::Neg<Output = T>,
+{
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        if other.is_zero() {
+            panic!("division by zero");
+        }
+        (self.clone() / other.clone(), Self::zero())
+    }
+}
+
+impl<T> Field for Complex<T>
+where
+    T: Field + Clone + Num + ops::Neg<Output = T>,
+{
+    fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            None
+        } else {
+            Some(self.inv())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(7, 3, 2, 1)]
+    #[case(-7, 3, -3, 2)]
+    #[case(7, -3, -2, 1)]
+    #[case(-7, -3, 3, 2)]
+    fn test_integer_quo_rem(#[case] a: i64, #[case] b: i64, #[case] quo: i64, #[case] rem: i64) {
+        assert_eq!(a.quo_rem(&b), (quo, rem));
+        let (big_quo, big_rem) = BigInt::from(a).quo_rem(&BigInt::from(b));
+        assert_eq!(big_quo, BigInt::from(quo));
+        assert_eq!(big_rem, BigInt::from(rem));
+    }
+
+    #[rstest]
+    fn test_gcd() {
+        assert_eq!(GCDDomain::gcd(&12i32, &-18), 6);
+        assert_eq!(euclidean_gcd(&12i32, &18), 6);
+        assert_eq!(
+            GCDDomain::gcd(&BigInt::from(84), &BigInt::from(36)),
+            BigInt::from(12)
+        );
+        assert_eq!(
+            GCDDomain::gcd(&Ratio::new(1, 2), &Ratio::new(3, 4)),
+            Ratio::one()
+        );
+    }
+
+    #[rstest]
+    fn test_field_inverse() {
+        assert_eq!(Ratio::new(3, 4).inverse(), Some(Ratio::new(4, 3)));
+        assert_eq!(Ratio::<i64>::zero().inverse(), None);
+        assert_eq!(2.0f64.inverse(), Some(0.5));
+        assert_eq!(
+            Complex::new(0.0, 2.0).inverse(),
+            Some(Complex::new(0.0, -0.5))
+        );
+        assert_eq!(Complex::<f64>::zero().inverse(), None);
+    }
+
+    #[rstest]
+    fn test_ordered() {
+        assert!(OrderedRing::is_negative(&-3i32));
+        assert!(OrderedRing::is_positive(&Ratio::new(1, 3)));
+        assert!(!OrderedRing::is_positive(&0.0f64));
+    }
+
+    #[rstest]
+    fn test_generic_bounds() {
+        fn solve_linear<F: Field + Clone>(a: F, b: F) -> Option<F> {
+            // a * x + b = 0
+            a.inverse().map(|inverse| (F::zero() - b) * inverse)
+        }
+        assert_eq!(
+            solve_linear(Ratio::new(2, 1), Ratio::new(1, 1)),
+            Some(Ratio::new(-1, 2))
+        );
+        assert_eq!(solve_linear(0.0, 1.0), None);
+    }
+}
@@ -0,0 +1,683 @@
+use crate::diagnostic::{Diagnostic, Span};
+use crate::exponents::Exponents;
+use crate::monomial::{variable_symbol, Monomial};
+use crate::parser::{FromLiteral, ParserErr};
+use crate::polynomial::{Limits, Polynomial, PolynomialErr};
+use crate::CRing;
+use log::error;
+use std::cmp;
+use std::fmt;
+use std::mem;
+
+// An unevaluated expression as parsed, each node with the span of input it
+// was read from. Evaluating folds it into a polynomial over any coefficient
+// type, so the same expression can be inspected, rewritten or evaluated
+// more than once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
+    // the literal as written, read only once the coefficient type is known
+    Number(String),
+    // index of the variable, as in Exponents
+    Var(usize),
+    // the imaginary unit i
+    Imaginary,
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    // a product written with '*'
+    Mul(Box<Expr>, Box<Expr>),
+    // a product written by juxtaposition, as in 3xy
+    ImplicitMul(Box<Expr>, Box<Expr>),
+    // division by a constant
+    Div(Box<Expr>, Box<Expr>),
+    // the exponent is a constant integer expression
+    Pow(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Expr {
+        Expr { kind, span }
+    }
+
+    // Binding strength when printed, higher binds tighter.
+    fn precedence(&self) -> u8 {
+        match self.kind {
+            ExprKind::Add(..) | ExprKind::Sub(..) => 1,
+            ExprKind::Neg(_) => 2,
+            ExprKind::Mul(..) | ExprKind::Div(..) => 3,
+            ExprKind::ImplicitMul(..) => 4,
+            ExprKind::Pow(..) => 5,
+            ExprKind::Number(_) | ExprKind::Var(_) | ExprKind::Imaginary => 6,
+        }
+    }
+
+    // Replaces every occurrence of the variable, keeping the spans of the
+    // replaced nodes.
+    pub fn substitute(&self, var: usize, replacement: &Expr) -> Expr {
+        let sub = |e: &Expr| Box::new(e.substitute(var, replacement));
+        let kind = match &self.kind {
+            ExprKind::Var(ind) if *ind == var => {
+                return Expr::new(replacement.kind.clone(), self.span);
+            }
+            ExprKind::Number(_) | ExprKind::Var(_) | ExprKind::Imaginary => self.kind.clone(),
+            ExprKind::Neg(e) => ExprKind::Neg(sub(e)),
+            ExprKind::Add(l, r) => ExprKind::Add(sub(l), sub(r)),
+            ExprKind::Sub(l, r) => ExprKind::Sub(sub(l), sub(r)),
+            ExprKind::Mul(l, r) => ExprKind::Mul(sub(l), sub(r)),
+            ExprKind::ImplicitMul(l, r) => ExprKind::ImplicitMul(sub(l), sub(r)),
+            ExprKind::Div(l, r) => ExprKind::Div(sub(l), sub(r)),
+            ExprKind::Pow(e, exponent) => ExprKind::Pow(sub(e), sub(exponent)),
+        };
+        Expr::new(kind, self.span)
+    }
+
+    pub fn evaluate<T>(&self, limits: &Limits) -> Result<Polynomial<T>, ParserErr>
+    where
+        T: CRing + Clone + PartialEq + FromLiteral,
+    {
+        self.fold(
+            |node| node.operands(false),
+            |node, first: Option<Polynomial<T>>, second| {
+                Ok(match (&node.kind, first, second) {
+                    (ExprKind::Number(_) | ExprKind::Var(_) | ExprKind::Imaginary, ..) => {
+                        let mut polynomial = Polynomial::new();
+                        polynomial += node.leaf()?;
+                        polynomial
+                    }
+                    (ExprKind::Neg(_), Some(value), _) => -value,
+                    (ExprKind::Add(..), Some(l), Some(r)) => l + r,
+                    (ExprKind::Sub(..), Some(l), Some(r)) => l - r,
+                    (ExprKind::Mul(..) | ExprKind::ImplicitMul(..), Some(l), Some(r)) => l
+                        .checked_mul(&r, limits)
+                        .map_err(|e| node.evaluation_error(e, limits))?,
+                    (ExprKind::Div(_, r), Some(dividend), Some(divisor)) => {
+                        let divisor = r.constant(divisor)?;
+                        let mut polynomial = Polynomial::new();
+                        for monomial in dividend.monomials {
+                            polynomial += Monomial {
+                                coefficient: node.divide(&monomial.coefficient, &divisor, r)?,
+                                power_list: monomial.power_list,
+                            };
+                        }
+                        polynomial
+                    }
+                    (ExprKind::Pow(_, exponent), Some(base), _) => base
+                        .pow_with_limits(exponent.constant_exponent()?, limits)
+                        .map_err(|e| node.evaluation_error(e, limits))?,
+                    (kind, ..) => unreachable!("operands missing for {:?}", kind),
+                })
+            },
+        )
+    }
+
+    // The operands below the node, left to right. A power's exponent is
+    // included only with exponents set, as evaluation reads it on its own
+    // as an integer.
+    fn operands(&self, exponents: bool) -> (Option<&Expr>, Option<&Expr>) {
+        match &self.kind {
+            ExprKind::Number(_) | ExprKind::Var(_) | ExprKind::Imaginary => (None, None),
+            ExprKind::Neg(e) => (Some(e), None),
+            ExprKind::Pow(e, _) if !exponents => (Some(e), None),
+            ExprKind::Add(l, r)
+            | ExprKind::Sub(l, r)
+            | ExprKind::Mul(l, r)
+            | ExprKind::ImplicitMul(l, r)
+            | ExprKind::Div(l, r)
+            | ExprKind::Pow(l, r) => (Some(l), Some(r)),
+        }
+    }
+
+    // Folds the tree bottom up, combining each node with the values of the
+    // operands that operands gives it, left to right. A chain of sums is as
+    // deep as it is long, so the nodes wait on a stack of their own rather
+    // than the call stack.
+    fn fold<'a, V>(
+        &'a self,
+        operands: impl Fn(&'a Expr) -> (Option<&'a Expr>, Option<&'a Expr>),
+        mut combine: impl FnMut(&'a Expr, Option<V>, Option<V>) -> Result<V, ParserErr>,
+    ) -> Result<V, ParserErr> {
+        // each node is visited twice, first to schedule its operands and
+        // then, with their values on top of values, to combine them
+        let mut stack = vec![(self, false)];
+        let mut values = Vec::new();
+        while let Some((node, combining)) = stack.pop() {
+            let (first, second) = operands(node);
+            if combining {
+                let second = second.and_then(|_| values.pop());
+                let first = first.and_then(|_| values.pop());
+                values.push(combine(node, first, second)?);
+            } else {
+                stack.push((node, true));
+                stack.extend(second.map(|e| (e, false)));
+                stack.extend(first.map(|e| (e, false)));
+            }
+        }
+        Ok(values.pop().expect("the root is combined last"))
+    }
+
+    // The value of an expression that must not contain variables, such as a
+    // divisor, from the polynomial it evaluated to.
+    fn constant<T>(&self, polynomial: Polynomial<T>) -> Result<T, ParserErr>
+    where
+        T: CRing + Clone,
+    {
+        match polynomial.monomials.as_slice() {
+            [] => Ok(T::zero()),
+            [monomial] if monomial.degree() == 0 => Ok(monomial.coefficient.clone()),
+            _ => {
+                let msg = String::from("Expected a constant");
+                error!("{}", msg);
+                Err(ParserErr::EvaluationErr(
+                    Diagnostic::new(msg, self.span)
+                        .with_note(String::from("only division by constants is supported")),
+                ))
+            }
+        }
+    }
+
+    // The exact quotient of coefficients, divisor being the expression the
+    // divisor was read from.
+    fn divide<T>(&self, dividend: &T, divisor: &T, expr: &Expr) -> Result<T, ParserErr>
+    where
+        T: CRing + FromLiteral,
+    {
+        if divisor.is_zero() {
+            let msg = String::from("Division by zero");
+            error!("{}", msg);
+            return Err(ParserErr::EvaluationErr(Diagnostic::new(msg, expr.span)));
+        }
+        match dividend.divide(divisor) {
+            Some(v) => Ok(v),
+            None => {
+                let msg = String::from("Inexact division");
+                error!("{}", msg);
+                Err(ParserErr::EvaluationErr(
+                    Diagnostic::new(msg, self.span).with_note(String::from(
+                        "the quotient is not a value of the coefficient type",
+                    )),
+                ))
+            }
+        }
+    }
+
+    // The value of a constant integer expression, as used for exponents.
+    pub fn constant_exponent(&self) -> Result<i32, ParserErr> {
+        self.fold(
+            |node| node.operands(true),
+            |node, first, second| {
+                let evaluation_error = |msg: String, note: String| {
+                    error!("{}", msg);
+                    ParserErr::EvaluationErr(Diagnostic::new(msg, node.span).with_note(note))
+                };
+                let overflow = || {
+                    evaluation_error(
+                        String::from("Exponent overflow"),
+                        format!("exponents may not exceed {}", i32::MAX),
+                    )
+                };
+                match (&node.kind, first, second) {
+                    (ExprKind::Number(literal), ..) => {
+                        literal.parse::<i32>().map_err(|e| {
+                            let msg = format!("Invalid exponent {}: {}", literal, e);
+                            error!("{}", msg);
+                            ParserErr::InvalidLiteral(Diagnostic::new(msg, node.span).with_note(
+                                format!("exponents are integers from 0 to {}", i32::MAX),
+                            ))
+                        })
+                    }
+                    (ExprKind::Var(_), ..) => Err(evaluation_error(
+                        String::from("Exponents must be constant"),
+                        String::from("exponents are integer expressions without variables"),
+                    )),
+                    (ExprKind::Imaginary, ..) => Err(evaluation_error(
+                        String::from("Exponents must be real"),
+                        String::from("exponents are integers"),
+                    )),
+                    (ExprKind::Neg(_), Some(value), _) => value.checked_neg().ok_or_else(overflow),
+                    (ExprKind::Add(..), Some(l), Some(r)) => l.checked_add(r).ok_or_else(overflow),
+                    (ExprKind::Sub(..), Some(l), Some(r)) => l.checked_sub(r).ok_or_else(overflow),
+                    (ExprKind::Mul(..) | ExprKind::ImplicitMul(..), Some(l), Some(r)) => {
+                        l.checked_mul(r).ok_or_else(overflow)
+                    }
+                    (ExprKind::Div(..), Some(dividend), Some(divisor)) => {
+                        if divisor == 0 {
+                            return Err(evaluation_error(
+                                String::from("Division by zero"),
+                                String::from("exponents are integers"),
+                            ));
+                        }
+                        match dividend.checked_rem(divisor) {
+                            Some(0) => dividend.checked_div(divisor).ok_or_else(overflow),
+                            Some(_) => Err(evaluation_error(
+                                String::from("Exponent is not an integer"),
+                                String::from("exponents are integers"),
+                            )),
+                            None => Err(overflow()),
+                        }
+                    }
+                    (ExprKind::Pow(..), Some(base), Some(exponent)) => {
+                        match u32::try_from(exponent) {
+                            Ok(v) => base.checked_pow(v).ok_or_else(overflow),
+                            Err(_) => Err(evaluation_error(
+                                format!("Cannot raise to negative power {}", exponent),
+                                String::from("exponents are integers"),
+                            )),
+                        }
+                    }
+                    (kind, ..) => unreachable!("operands missing for {:?}", kind),
+                }
+            },
+        )
+    }
+
+    // Evaluates an expression that is a single term, such as -3x^2y. Unlike
+    // evaluate, a zero coefficient keeps the powers.
+    pub fn evaluate_monomial<T>(&self) -> Result<Monomial<T>, ParserErr>
+    where
+        T: CRing + Clone + PartialEq + FromLiteral,
+    {
+        self.fold(
+            // a sum is rejected before its terms are read
+            |node| match node.kind {
+                ExprKind::Add(..) | ExprKind::Sub(..) => (None, None),
+                _ => node.operands(false),
+            },
+            |node, first: Option<Monomial<T>>, second| {
+                let overflow = || {
+                    let msg = String::from("Exponent overflow");
+                    error!("{}", msg);
+                    ParserErr::EvaluationErr(
+                        Diagnostic::new(msg, node.span)
+                            .with_note(format!("exponents may not exceed {}", i32::MAX)),
+                    )
+                };
+                match (&node.kind, first, second) {
+                    (ExprKind::Number(_) | ExprKind::Var(_) | ExprKind::Imaginary, ..) => {
+                        node.leaf()
+                    }
+                    (ExprKind::Neg(_), Some(monomial), _) => Ok(-monomial),
+                    (ExprKind::Mul(..) | ExprKind::ImplicitMul(..), Some(l), Some(r)) => {
+                        l.checked_mul(&r).ok_or_else(overflow)
+                    }
+                    (ExprKind::Div(_, r), Some(mut monomial), Some(divisor)) => {
+                        if divisor.degree() != 0 {
+                            let msg = String::from("Expected a constant");
+                            error!("{}", msg);
+                            return Err(ParserErr::EvaluationErr(
+                                Diagnostic::new(msg, r.span).with_note(String::from(
+                                    "only division by constants is supported",
+                                )),
+                            ));
+                        }
+                        monomial.coefficient =
+                            node.divide(&monomial.coefficient, &divisor.coefficient, r)?;
+                        Ok(monomial)
+                    }
+                    (ExprKind::Pow(_, exponent), Some(base), _) => {
+                        let exponent = exponent.constant_exponent()?;
+                        let exponent = match u32::try_from(exponent) {
+                            Ok(v) => v,
+                            Err(_) => {
+                                let msg =
+                                    format!("Cannot raise a term to negative power {}", exponent);
+                                error!("{}", msg);
+                                return Err(ParserErr::EvaluationErr(Diagnostic::new(
+                                    msg, node.span,
+                                )));
+                            }
+                        };
+                        base.checked_pow(exponent).ok_or_else(overflow)
+                    }
+                    (ExprKind::Add(..) | ExprKind::Sub(..), ..) => {
+                        let msg = String::from("Expected a single term");
+                        error!("{}", msg);
+                        Err(ParserErr::InvalidSyntax(Diagnostic::new(msg, node.span)))
+                    }
+                    (kind, ..) => unreachable!("operands missing for {:?}", kind),
+                }
+            },
+        )
+    }
+
+    // The term of a number, a variable or the imaginary unit.
+    fn leaf<T>(&self) -> Result<Monomial<T>, ParserErr>
+    where
+        T: CRing + Clone + PartialEq + FromLiteral,
+    {
+        match &self.kind {
+            ExprKind::Number(literal) => match T::from_literal(literal) {
+                Some(coefficient) => Ok(Monomial {
+                    coefficient,
+                    power_list: Exponents::new(),
+                }),
+                None => {
+                    let msg = format!("Invalid literal {} for the coefficient type", literal);
+                    error!("{}", msg);
+                    Err(ParserErr::InvalidLiteral(
+                        Diagnostic::new(msg, self.span).with_note(String::from(
+                            "the literal is not a value of the coefficient type",
+                        )),
+                    ))
+                }
+            },
+            ExprKind::Imaginary => match T::imaginary_unit() {
+                Some(coefficient) => Ok(Monomial {
+                    coefficient,
+                    power_list: Exponents::new(),
+                }),
+                None => {
+                    let msg =
+                        String::from("The imaginary unit is not a value of the coefficient type");
+                    error!("{}", msg);
+                    Err(ParserErr::InvalidLiteral(
+                        Diagnostic::new(msg, self.span)
+                            .with_note(String::from("use complex coefficients, such as ParserC")),
+                    ))
+                }
+            },
+            ExprKind::Var(ind) => {
+                let mut monomial = Monomial::new();
+                monomial.power_list.set(*ind, 1);
+                Ok(monomial)
+            }
+            kind => unreachable!("{:?} is not a leaf", kind),
+        }
+    }
+
+    fn evaluation_error(&self, e: PolynomialErr, limits: &Limits) -> ParserErr {
+        let diagnostic = match e {
+            PolynomialErr::NegativeExponent(msg) => Diagnostic::new(msg, self.span),
+            PolynomialErr::ExponentOverflow(msg) => Diagnostic::new(msg, self.span)
+                .with_note(format!("exponents may not exceed {}", i32::MAX)),
+            PolynomialErr::DegreeLimit(msg) => Diagnostic::new(msg, self.span)
+                .with_note(format!("degrees are limited to {}", limits.max_degree)),
+            PolynomialErr::TermLimit(msg) => Diagnostic::new(msg, self.span).with_note(format!(
+                "polynomials are limited to {} terms",
+                limits.max_terms
+            )),
+        };
+        error!("Unable to evaluate expression: {}", diagnostic.message);
+        ParserErr::EvaluationErr(diagnostic)
+    }
+}
+
+// Dropping the tree recursively would overflow the stack on a long chain,
+// so the children are moved onto a list of their own and dropped from there
+// once they have none.
+impl Drop for Expr {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        let mut kind = mem::replace(&mut self.kind, ExprKind::Imaginary);
+        loop {
+            match kind {
+                ExprKind::Number(_) | ExprKind::Var(_) | ExprKind::Imaginary => {}
+                ExprKind::Neg(e) => stack.push(e),
+                ExprKind::Add(l, r)
+                | ExprKind::Sub(l, r)
+                | ExprKind::Mul(l, r)
+                | ExprKind::ImplicitMul(l, r)
+                | ExprKind::Div(l, r)
+                | ExprKind::Pow(l, r) => {
+                    stack.push(l);
+                    stack.push(r);
+                }
+            }
+            match stack.pop() {
+                Some(mut e) => kind = mem::replace(&mut e.kind, ExprKind::Imaginary),
+                None => break,
+            }
+        }
+    }
+}
+
+// What is left to print of an expression, kept on a stack rather than
+// printed by recursion, which a long chain would overflow.
+enum Piece<'a> {
+    Text(&'static str),
+    // an expression printed as it is
+    Node(&'a Expr),
+    // an operand needing at least the given precedence
+    Operand(&'a Expr, u8),
+    // an operand after an operator, where a negation takes only what binds
+    // at least as tightly as that operator and needs no parentheses of its
+    // own
+    RightOperand(&'a Expr, u8),
+}
+
+// Prints the expression as it would be written, with only the parentheses
+// the precedence of the operators requires.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pieces = vec![Piece::Node(self)];
+        while let Some(piece) = pieces.pop() {
+            let node = match piece {
+                Piece::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
+                }
+                Piece::Node(node) => node,
+                Piece::Operand(e, precedence) => {
+                    if e.precedence() < precedence {
+                        pieces.extend([Piece::Text(")"), Piece::Node(e), Piece::Text("(")]);
+                    } else {
+                        pieces.push(Piece::Node(e));
+                    }
+                    continue;
+                }
+                Piece::RightOperand(e, precedence) => {
+                    match &e.kind {
+                        ExprKind::Neg(inner) => pieces.extend([
+                            Piece::RightOperand(inner, cmp::max(precedence, 3)),
+                            Piece::Text("-"),
+                        ]),
+                        _ => pieces.push(Piece::Operand(e, precedence)),
+                    }
+                    continue;
+                }
+            };
+            // the pieces of the node, pushed last first
+            let (l, operator, r) = match &node.kind {
+                ExprKind::Number(literal) => {
+                    f.write_str(literal)?;
+                    continue;
+                }
+                ExprKind::Var(ind) => {
+                    write!(f, "{}", variable_symbol(*ind))?;
+                    continue;
+                }
+                ExprKind::Imaginary => {
+                    f.write_str("i")?;
+                    continue;
+                }
+                ExprKind::Neg(e) => {
+                    pieces.extend([Piece::Operand(e, 3), Piece::Text("-")]);
+                    continue;
+                }
+                ExprKind::Add(l, r) => (Piece::Operand(l, 1), " + ", Piece::RightOperand(r, 2)),
+                ExprKind::Sub(l, r) => (Piece::Operand(l, 1), " - ", Piece::RightOperand(r, 2)),
+                ExprKind::Mul(l, r) => (Piece::Operand(l, 3), " * ", Piece::RightOperand(r, 5)),
+                ExprKind::Div(l, r) => (Piece::Operand(l, 3), " / ", Piece::RightOperand(r, 5)),
+                // juxtaposed digits would read as one number
+                ExprKind::ImplicitMul(l, r) if r.starts_with_digit() => {
+                    (Piece::Operand(l, 3), " * ", Piece::RightOperand(r, 5))
+                }
+                ExprKind::ImplicitMul(l, r) => {
+                    (Piece::Operand(l, 3), "", Piece::RightOperand(r, 5))
+                }
+                ExprKind::Pow(e, exponent) => {
+                    (Piece::Operand(e, 6), "^", Piece::RightOperand(exponent, 5))
+                }
+            };
+            pieces.extend([r, Piece::Text(operator), l]);
+        }
+        Ok(())
+    }
+}
+
+impl Expr {
+    // Whether the expression, printed after an operator of precedence 5,
+    // begins with a digit. Only a number or a power of a number does, as a
+    // negation begins with '-' and anything binding looser with '('.
+    fn starts_with_digit(&self) -> bool {
+        let is_digit = |c: char| c.is_ascii_digit() || c == '.';
+        match &self.kind {
+            ExprKind::Number(literal) => literal.starts_with(is_digit),
+            ExprKind::Pow(e, _) => {
+                matches!(&e.kind, ExprKind::Number(literal) if literal.starts_with(is_digit))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser64;
+    use crate::polynomial::{Polynomial64, PolynomialQ};
+    use rstest::*;
+
+    fn parse(expr: &str) -> Expr {
+        Parser64::parser_init(String::from(expr))
+            .unwrap()
+            .parse_expr()
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case("3.5x^2yz^5", "3.5x^2yz^5")]
+    #[case("((x + y))*(x - 7)", "(x + y) * (x - 7)")]
+    #[case("x - (y - z) + (x + y)^3", "x - (y - z) + (x + y)^3")]
+    #[case("-(x + 2)*-(x + 2) - -(x + 2)", "-(x + 2) * -(x + 2) - -(x + 2)")]
+    #[case("x - 3y^2z + 4", "x - 3y^2z + 4")]
+    #[case("(1 + 2i)x^2 + 3i*y", "(1 + 2i)x^2 + 3i * y")]
+    #[case("2(x+1)^2(x - 1)x y", "2(x + 1)^2(x - 1)xy")]
+    #[case("x + 2 * y(z)", "x + 2 * yz")]
+    #[case("-x*y^2^3 / 4 + +y", "-x * y^2^3 / 4 + y")]
+    #[case("2^-x*y - (-x)^2", "2^-x * y - (-x)^2")]
+    #[case("(x + 1)^(2 + 1) * (x - y)", "(x + 1)^(2 + 1) * (x - y)")]
+    fn test_display_unevaluated(#[case] expr: &str, #[case] expected: &str) {
+        assert_eq!(format!("{}", parse(expr)), expected);
+    }
+
+    #[rstest]
+    fn test_display_substituted_number() {
+        let expr = parse("2x").substitute(0, &parse("3"));
+        assert_eq!(format!("{}", expr), "2 * 3");
+    }
+
+    #[rstest]
+    fn test_spans() {
+        let expr = parse("2x + (y - 1)^2");
+        assert_eq!(expr.span, Span::new(0, 14));
+        match &expr.kind {
+            ExprKind::Add(l, r) => {
+                assert_eq!(l.span, Span::new(0, 2));
+                assert_eq!(r.span, Span::new(5, 14));
+                assert!(matches!(r.kind, ExprKind::Pow(..)));
+            }
+            _ => panic!("{:?}", expr),
+        }
+    }
+
+    #[rstest]
+    fn test_evaluate_in_different_rings() {
+        let expr = parse("(0.5x - 1)^2");
+        let float: Polynomial64 = expr.evaluate(&Limits::unbounded()).unwrap();
+        let rational: PolynomialQ = expr.evaluate(&Limits::unbounded()).unwrap();
+        assert_eq!(format!("{}", float), "0.25x^2 - x + 1");
+        assert_eq!(format!("{}", rational), "(1/4)x^2 - x + 1");
+    }
+
+    #[rstest]
+    fn test_substitute() {
+        let expr = parse("x^2 + 2x").substitute(0, &parse("y + 1"));
+        assert_eq!(format!("{}", expr), "(y + 1)^2 + 2(y + 1)");
+        let polynomial: Polynomial64 = expr.evaluate(&Limits::unbounded()).unwrap();
+        assert_eq!(format!("{}", polynomial), "y^2 + 4y + 3");
+    }
+
+    #[rstest]
+    fn test_evaluate_long_chains() {
+        let sum = vec!["x"; 20_000].join(" + ");
+        let polynomial: Polynomial64 = parse(&sum).evaluate(&Limits::unbounded()).unwrap();
+        assert_eq!(format!("{}", polynomial), "20000x");
+        let difference = format!("{} - y", vec!["y"; 20_000].join(" - "));
+        let polynomial: Polynomial64 = parse(&difference).evaluate(&Limits::unbounded()).unwrap();
+        assert_eq!(format!("{}", polynomial), "-19999y");
+        let product = vec!["1"; 20_000].join(" * ");
+        let polynomial: Polynomial64 = parse(&format!("{}x", product))
+            .evaluate(&Limits::unbounded())
+            .unwrap();
+        assert_eq!(format!("{}", polynomial), "x");
+    }
+
+    // Deeper than the parser nests, so built by hand.
+    fn nest(depth: usize, leaf: Expr, node: impl Fn(Expr) -> ExprKind) -> Expr {
+        (0..depth).fold(leaf, |e, _| Expr::new(node(e), Span::default()))
+    }
+
+    #[rstest]
+    fn test_evaluate_deep_negation() {
+        let expr = nest(100_001, parse("x"), |e| ExprKind::Neg(Box::new(e)));
+        let polynomial: Polynomial64 = expr.evaluate(&Limits::unbounded()).unwrap();
+        assert_eq!(format!("{}", polynomial), "-x");
+        let printed = format!("{}", expr);
+        assert!(printed.starts_with("-(-(-(") && printed.trim_end_matches(')').ends_with("(-x"));
+        assert_eq!(printed.len(), 3 * 100_001 - 1);
+        let exponent = nest(100_000, parse("2"), |e| ExprKind::Neg(Box::new(e)));
+        assert_eq!(exponent.constant_exponent().unwrap(), 2);
+    }
+
+    #[rstest]
+    fn test_evaluate_deep_powers() {
+        let expr = nest(100_000, parse("x + 1"), |e| {
+            ExprKind::Pow(Box::new(e), Box::new(parse("1")))
+        });
+        let polynomial: Polynomial64 = expr.evaluate(&Limits::unbounded()).unwrap();
+        assert_eq!(format!("{}", polynomial), "x + 1");
+        let printed = format!("{}", expr);
+        assert!(
+            printed.trim_start_matches('(').starts_with("x + 1)^1)^1")
+                && printed.ends_with(")^1)^1")
+        );
+        // a tower of exponents nests to the right
+        let tower = nest(100_000, parse("2"), |e| {
+            ExprKind::Pow(Box::new(parse("1")), Box::new(e))
+        });
+        assert!(format!("{}", tower).starts_with("1^1^1^"));
+        let expr = Expr::new(
+            ExprKind::Pow(Box::new(parse("x")), Box::new(tower)),
+            Span::default(),
+        );
+        let polynomial: Polynomial64 = expr.evaluate(&Limits::unbounded()).unwrap();
+        assert_eq!(format!("{}", polynomial), "x");
+    }
+
+    #[rstest]
+    fn test_display_long_chains() {
+        let sum = vec!["x"; 20_000].join(" + ");
+        assert_eq!(format!("{}", parse(&sum)), sum);
+        let product = format!("{}x", vec!["2"; 20_000].join(" * "));
+        assert_eq!(format!("{}", parse(&product)), product);
+    }
+
+    #[rstest]
+    fn test_evaluation_error_span() {
+        let expr = parse("x + (x + y)^3");
+        let limits = Limits {
+            max_degree: 2,
+            max_terms: usize::MAX,
+        };
+        match expr.evaluate::<f64>(&limits) {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert_eq!(e.diagnostic().span, Span::new(4, 13)),
+        }
+    }
+}
@@ -0,0 +1,864 @@
+use std::clone::Clone;
+use std::f64::consts::PI;
+use std::fmt;
+use std::ops;
+
+use log::error;
+use num::complex::Complex;
+use num::{One, Zero};
+
+use crate::algebra::euclidean_gcd;
+use crate::exponents::Exponents;
+use crate::modint::ModInt;
+use crate::monomial::Monomial;
+use crate::polynomial::Polynomial;
+use crate::render::FormatCoefficient;
+use crate::{CRing, EuclideanDomain, Field, GCDDomain, IntegralDomain};
+
+pub type DensePoly32 = DensePoly<f32>;
+pub type DensePoly64 = DensePoly<f64>;
+
+// Below these lengths the asymptotically faster method loses to the simpler one.
+const KARATSUBA_THRESHOLD: usize = 32;
+const TOOM3_THRESHOLD: usize = 96;
+const NEWTON_DIVISION_THRESHOLD: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub enum DensePolyErr {
+    NotUnivariate(String),
+    NegativeExponent(String),
+    DivisionByZero(String),
+    TransformTooLarge(String),
+}
+
+// A univariate polynomial stored as its full coefficient vector, where
+// coefficients[i] belongs to x^i. Trailing zeros are always trimmed, so the
+// zero polynomial has no coefficients at all.
+#[derive(Debug)]
+pub struct DensePoly<T: CRing> {
+    pub coefficients: Vec<T>,
+}
+
+impl<T> DensePoly<T>
+where
+    T: CRing,
+{
+    pub fn new() -> DensePoly<T> {
+        DensePoly {
+            coefficients: Vec::new(),
+        }
+    }
+
+    pub fn from_coefficients(coefficients: Vec<T>) -> DensePoly<T> {
+        let mut polynomial = DensePoly { coefficients };
+        polynomial.trim();
+        polynomial
+    }
+
+    fn trim(&mut self) {
+        while self.coefficients.last().is_some_and(|c| c.is_zero()) {
+            self.coefficients.pop();
+        }
+    }
+
+    // None for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+}
+
+impl<T> Default for DensePoly<T>
+where
+    T: CRing,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DensePoly<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    // Reads a polynomial in the variable with index var, e.g. 0 for x. Terms
+    // involving any other variable are rejected.
+    pub fn from_polynomial(polynomial: &Polynomial<T>, var: usize) -> Result<Self, DensePolyErr> {
+        let mut coefficients: Vec<T> = Vec::new();
+        for monomial in polynomial.monomials.iter() {
+            if monomial.coefficient.is_zero() {
+                continue;
+            }
+            let other_var =
+                (0..monomial.power_list.len()).any(|ind| ind != var && monomial.power(ind) != 0);
+            if other_var {
+                let msg = format!(
+                    "Term {} is not univariate in variable {}",
+                    monomial.power_list, var
+                );
+                error!("{}", msg);
+                return Err(DensePolyErr::NotUnivariate(msg));
+            }
+            let power = monomial.power(var);
+            if power < 0 {
+                let msg = format!("Term {} has a negative exponent", monomial.power_list);
+                error!("{}", msg);
+                return Err(DensePolyErr::NegativeExponent(msg));
+            }
+            let power = power as usize;
+            if coefficients.len() <= power {
+                coefficients.resize(power + 1, T::zero());
+            }
+            coefficients[power] = coefficients[power].clone() + monomial.coefficient.clone();
+        }
+        Ok(DensePoly::from_coefficients(coefficients))
+    }
+
+    pub fn to_polynomial(&self, var: usize) -> Polynomial<T> {
+        let mut polynomial = Polynomial::new();
+        for (power, coefficient) in self.coefficients.iter().enumerate() {
+            if coefficient.is_zero() {
+                continue;
+            }
+            let mut power_list = Exponents::new();
+            power_list.set(var, power as i32);
+            polynomial += Monomial {
+                coefficient: coefficient.clone(),
+                power_list,
+            };
+        }
+        polynomial
+    }
+
+    pub fn evaluate(&self, point: T) -> T {
+        // Horner's rule
+        let mut res = T::zero();
+        for coefficient in self.coefficients.iter().rev() {
+            res = res * point.clone() + coefficient.clone();
+        }
+        res
+    }
+
+    pub fn mul_schoolbook(&self, other: &Self) -> Self {
+        DensePoly::from_coefficients(schoolbook(&self.coefficients, &other.coefficients))
+    }
+
+    pub fn mul_karatsuba(&self, other: &Self) -> Self {
+        DensePoly::from_coefficients(karatsuba(&self.coefficients, &other.coefficients))
+    }
+
+    // The first len coefficients, i.e. the polynomial mod x^len.
+    fn truncate(&self, len: usize) -> Self {
+        let len = std::cmp::min(len, self.coefficients.len());
+        DensePoly::from_coefficients(self.coefficients[..len].to_vec())
+    }
+
+    // Coefficients in reverse order, padded to len, i.e. x^(len - 1) f(1/x).
+    fn reverse(&self, len: usize) -> Self {
+        let mut coefficients = self.coefficients.clone();
+        coefficients.resize(len, T::zero());
+        coefficients.reverse();
+        DensePoly::from_coefficients(coefficients)
+    }
+}
+
+impl<T> DensePoly<T>
+where
+    T: CRing + Clone + PartialEq + ops::Div<Output = T>,
+{
+    // Toom-Cook 3-way multiplication. The interpolation divides by 2 and 3,
+    // which must be exact in T.
+    pub fn mul_toom3(&self, other: &Self) -> Self {
+        DensePoly::from_coefficients(toom3(&self.coefficients, &other.coefficients))
+    }
+
+    // The power series inverse of self mod x^len, by Newton iteration
+    // g <- g(2 - fg), doubling the number of correct terms each step.
+    pub fn inverse_series(&self, len: usize) -> Result<Self, DensePolyErr> {
+        let constant = match self.coefficients.first() {
+            Some(c) if !c.is_zero() => c.clone(),
+            _ => {
+                let msg = String::from("Power series with zero constant term is not invertible");
+                error!("{}", msg);
+                return Err(DensePolyErr::DivisionByZero(msg));
+            }
+        };
+        let two = DensePoly::from_coefficients(vec![T::one() + T::one()]);
+        let mut inverse = DensePoly::from_coefficients(vec![T::one() / constant]);
+        let mut precision = 1;
+        while precision < len {
+            precision = std::cmp::min(2 * precision, len);
+            let correction =
+                two.clone() - (self.truncate(precision) * inverse.clone()).truncate(precision);
+            inverse = (inverse * correction).truncate(precision);
+        }
+        Ok(inverse.truncate(len))
+    }
+
+    // Quotient and remainder. Large quotients are found from a power series
+    // inverse of the reversed divisor, so division costs a constant number
+    // of multiplications; small ones use long division.
+    pub fn div_rem(&self, divisor: &Self) -> Result<(Self, Self), DensePolyErr> {
+        let divisor_degree = match divisor.degree() {
+            Some(degree) => degree,
+            None => {
+                let msg = String::from("Division by the zero polynomial");
+                error!("{}", msg);
+                return Err(DensePolyErr::DivisionByZero(msg));
+            }
+        };
+        let degree = match self.degree() {
+            Some(degree) if degree >= divisor_degree => degree,
+            _ => return Ok((DensePoly::new(), self.clone())),
+        };
+        let quotient_len = degree - divisor_degree + 1;
+        if quotient_len < NEWTON_DIVISION_THRESHOLD {
+            return self.div_rem_long(divisor);
+        }
+
+        let inverse = divisor
+            .reverse(divisor_degree + 1)
+            .inverse_series(quotient_len)?;
+        let quotient_rev = (self.reverse(degree + 1) * inverse).truncate(quotient_len);
+        let quotient = quotient_rev.reverse(quotient_len);
+        let remainder = self.clone() - quotient.clone() * divisor.clone();
+        Ok((quotient, remainder))
+    }
+
+    pub fn div_rem_long(&self, divisor: &Self) -> Result<(Self, Self), DensePolyErr> {
+        let divisor_degree = match divisor.degree() {
+            Some(degree) => degree,
+            None => {
+                let msg = String::from("Division by the zero polynomial");
+                error!("{}", msg);
+                return Err(DensePolyErr::DivisionByZero(msg));
+            }
+        };
+        let leading = divisor.coefficients[divisor_degree].clone();
+        let mut remainder = self.coefficients.clone();
+        if remainder.len() <= divisor_degree {
+            return Ok((DensePoly::new(), self.clone()));
+        }
+        let mut quotient = vec![T::zero(); remainder.len() - divisor_degree];
+        for ind in (0..quotient.len()).rev() {
+            let coefficient = remainder[ind + divisor_degree].clone() / leading.clone();
+            for (divisor_ind, divisor_coefficient) in divisor.coefficients.iter().enumerate() {
+                remainder[ind + divisor_ind] = remainder[ind + divisor_ind].clone()
+                    - coefficient.clone() * divisor_coefficient.clone();
+            }
+            quotient[ind] = coefficient;
+        }
+        remainder.truncate(divisor_degree);
+        Ok((
+            DensePoly::from_coefficients(quotient),
+            DensePoly::from_coefficients(remainder),
+        ))
+    }
+}
+
+impl<const P: u64> DensePoly<ModInt<P>> {
+    // Exact multiplication by number theoretic transform. The product length
+    // rounded up to a power of two must divide P - 1.
+    pub fn mul_ntt(&self, other: &Self) -> Result<Self, DensePolyErr> {
+        if self.is_zero() || other.is_zero() {
+            return Ok(DensePoly::new());
+        }
+        let result_len = self.coefficients.len() + other.coefficients.len() - 1;
+        let size = result_len.next_power_of_two();
+        if !(P - 1).is_multiple_of(size as u64) {
+            let msg = format!("Transform of length {} is not supported modulo {}", size, P);
+            error!("{}", msg);
+            return Err(DensePolyErr::TransformTooLarge(msg));
+        }
+
+        let mut lhs = self.coefficients.clone();
+        let mut rhs = other.coefficients.clone();
+        lhs.resize(size, ModInt::zero());
+        rhs.resize(size, ModInt::zero());
+        ntt(&mut lhs, false);
+        ntt(&mut rhs, false);
+        for (a, b) in lhs.iter_mut().zip(rhs.iter()) {
+            *a = *a * *b;
+        }
+        ntt(&mut lhs, true);
+        lhs.truncate(result_len);
+        Ok(DensePoly::from_coefficients(lhs))
+    }
+}
+
+impl DensePoly<Complex<f64>> {
+    // Multiplication by floating point FFT, subject to rounding error.
+    pub fn mul_fft(&self, other: &Self) -> Self {
+        DensePoly::from_coefficients(fft_mul(&self.coefficients, &other.coefficients))
+    }
+}
+
+impl DensePoly<f64> {
+    // Multiplication by floating point FFT, subject to rounding error.
+    pub fn mul_fft(&self, other: &Self) -> Self {
+        let lhs: Vec<Complex<f64>> = self
+            .coefficients
+            .iter()
+            .map(|&c| Complex::new(c, 0.0))
+            .collect();
+        let rhs: Vec<Complex<f64>> = other
+            .coefficients
+            .iter()
+            .map(|&c| Complex::new(c, 0.0))
+            .collect();
+        DensePoly::from_coefficients(fft_mul(&lhs, &rhs).iter().map(|c| c.re).collect())
+    }
+}
+
+fn schoolbook<T>(lhs: &[T], rhs: &[T]) -> Vec<T>
+where
+    T: CRing + Clone,
+{
+    if lhs.is_empty() || rhs.is_empty() {
+        return Vec::new();
+    }
+    let mut res = vec![T::zero(); lhs.len() + rhs.len() - 1];
+    for (i, a) in lhs.iter().enumerate() {
+        for (j, b) in rhs.iter().enumerate() {
+            res[i + j] = res[i + j].clone() + a.clone() * b.clone();
+        }
+    }
+    res
+}
+
+fn add_slices<T>(lhs: &[T], rhs: &[T]) -> Vec<T>
+where
+    T: CRing + Clone,
+{
+    let len = std::cmp::max(lhs.len(), rhs.len());
+    (0..len)
+        .map(|ind| match (lhs.get(ind), rhs.get(ind)) {
+            (Some(a), Some(b)) => a.clone() + b.clone(),
+            (Some(a), None) => a.clone(),
+            (None, Some(b)) => b.clone(),
+            (None, None) => T::zero(),
+        })
+        .collect()
+}
+
+fn sub_slices<T>(lhs: &[T], rhs: &[T]) -> Vec<T>
+where
+    T: CRing + Clone,
+{
+    let len = std::cmp::max(lhs.len(), rhs.len());
+    (0..len)
+        .map(|ind| match (lhs.get(ind), rhs.get(ind)) {
+            (Some(a), Some(b)) => a.clone() - b.clone(),
+            (Some(a), None) => a.clone(),
+            (None, Some(b)) => T::zero() - b.clone(),
+            (None, None) => T::zero(),
+        })
+        .collect()
+}
+
+// res[offset + i] += part[i]
+fn add_at<T>(res: &mut [T], part: &[T], offset: usize)
+where
+    T: CRing + Clone,
+{
+    for (ind, coefficient) in part.iter().enumerate() {
+        res[offset + ind] = res[offset + ind].clone() + coefficient.clone();
+    }
+}
+
+fn karatsuba<T>(lhs: &[T], rhs: &[T]) -> Vec<T>
+where
+    T: CRing + Clone,
+{
+    if lhs.len() < KARATSUBA_THRESHOLD || rhs.len() < KARATSUBA_THRESHOLD {
+        return schoolbook(lhs, rhs);
+    }
+    // (a0 + a1 x^m)(b0 + b1 x^m)
+    //   = a0 b0 + ((a0 + a1)(b0 + b1) - a0 b0 - a1 b1) x^m + a1 b1 x^2m
+    let half = std::cmp::max(lhs.len(), rhs.len()) / 2;
+    let (lhs_low, lhs_high) = lhs.split_at(std::cmp::min(half, lhs.len()));
+    let (rhs_low, rhs_high) = rhs.split_at(std::cmp::min(half, rhs.len()));
+
+    let low = karatsuba(lhs_low, rhs_low);
+    let high = karatsuba(lhs_high, rhs_high);
+    let mid = karatsuba(
+        &add_slices(lhs_low, lhs_high),
+        &add_slices(rhs_low, rhs_high),
+    );
+    let mid = sub_slices(&sub_slices(&mid, &low), &high);
+
+    let mut res = vec![T::zero(); lhs.len() + rhs.len() - 1];
+    let mid_len = std::cmp::min(mid.len(), res.len() - half);
+    add_at(&mut res, &low, 0);
+    add_at(&mut res, &mid[..mid_len], half);
+    add_at(&mut res, &high, 2 * half);
+    res
+}
+
+fn toom3<T>(lhs: &[T], rhs: &[T]) -> Vec<T>
+where
+    T: CRing + Clone + ops::Div<Output = T>,
+{
+    if lhs.len() < TOOM3_THRESHOLD || rhs.len() < TOOM3_THRESHOLD {
+        return karatsuba(lhs, rhs);
+    }
+    let part_len = std::cmp::max(lhs.len(), rhs.len()).div_ceil(3);
+    let split = |poly: &[T]| -> [Vec<T>; 3] {
+        let mut parts: [Vec<T>; 3] = [Vec::new(), Vec::new(), Vec::new()];
+        for (ind, chunk) in poly.chunks(part_len).enumerate() {
+            parts[ind] = chunk.to_vec();
+        }
+        parts
+    };
+    let two = T::one() + T::one();
+    let three = two.clone() + T::one();
+    let scale = |poly: &[T], scalar: &T| -> Vec<T> {
+        poly.iter().map(|c| c.clone() * scalar.clone()).collect()
+    };
+    let divide = |poly: &[T], scalar: &T| -> Vec<T> {
+        poly.iter().map(|c| c.clone() / scalar.clone()).collect()
+    };
+
+    // Evaluate both operands at 0, 1, -1, -2 and infinity.
+    let evaluate = |[p0, p1, p2]: &[Vec<T>; 3]| -> [Vec<T>; 5] {
+        let even = add_slices(p0, p2);
+        let at_one = add_slices(&even, p1);
+        let at_minus_one = sub_slices(&even, p1);
+        let at_minus_two = sub_slices(&scale(&add_slices(&at_minus_one, p2), &two), p0);
+        [p0.clone(), at_one, at_minus_one, at_minus_two, p2.clone()]
+    };
+    let lhs_values = evaluate(&split(lhs));
+    let rhs_values = evaluate(&split(rhs));
+    let [r0, r1, r_minus_one, r_minus_two, r_inf]: [Vec<T>; 5] =
+        [0, 1, 2, 3, 4].map(|ind| toom3(&lhs_values[ind], &rhs_values[ind]));
+
+    // Bodrato's interpolation sequence.
+    let r3 = divide(&sub_slices(&r_minus_two, &r1), &three);
+    let r1 = divide(&sub_slices(&r1, &r_minus_one), &two);
+    let r2 = sub_slices(&r_minus_one, &r0);
+    let r3 = add_slices(&divide(&sub_slices(&r2, &r3), &two), &scale(&r_inf, &two));
+    let r2 = sub_slices(&add_slices(&r2, &r1), &r_inf);
+    let r1 = sub_slices(&r1, &r3);
+
+    let mut res = vec![T::zero(); lhs.len() + rhs.len() - 1];
+    for (ind, part) in [r0, r1, r2, r3, r_inf].iter().enumerate() {
+        let offset = ind * part_len;
+        if offset >= res.len() {
+            break;
+        }
+        let len = std::cmp::min(part.len(), res.len() - offset);
+        add_at(&mut res, &part[..len], offset);
+    }
+    res
+}
+
+fn bit_reverse_permute<T>(values: &mut [T]) {
+    let n = values.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+// In place iterative radix 2 transform; values.len() must be a power of two
+// dividing P - 1.
+fn ntt<const P: u64>(values: &mut [ModInt<P>], invert: bool) {
+    let n = values.len();
+    bit_reverse_permute(values);
+    let generator = ModInt::<P>::primitive_root();
+    let mut len = 2;
+    while len <= n {
+        let mut root = generator.pow((P - 1) / len as u64);
+        if invert {
+            root = root.inverse().unwrap();
+        }
+        for start in (0..n).step_by(len) {
+            let mut w = ModInt::one();
+            for ind in 0..len / 2 {
+                let u = values[start + ind];
+                let v = values[start + ind + len / 2] * w;
+                values[start + ind] = u + v;
+                values[start + ind + len / 2] = u - v;
+                w = w * root;
+            }
+        }
+        len <<= 1;
+    }
+    if invert {
+        let n_inverse = ModInt::<P>::new(n as u64).inverse().unwrap();
+        for value in values.iter_mut() {
+            *value = *value * n_inverse;
+        }
+    }
+}
+
+fn fft(values: &mut [Complex<f64>], invert: bool) {
+    let n = values.len();
+    bit_reverse_permute(values);
+    let mut len = 2;
+    while len <= n {
+        let angle = 2.0 * PI / len as f64 * if invert { -1.0 } else { 1.0 };
+        let root = Complex::new(angle.cos(), angle.sin());
+        for start in (0..n).step_by(len) {
+            let mut w = Complex::new(1.0, 0.0);
+            for ind in 0..len / 2 {
+                let u = values[start + ind];
+                let v = values[start + ind + len / 2] * w;
+                values[start + ind] = u + v;
+                values[start + ind + len / 2] = u - v;
+                w *= root;
+            }
+        }
+        len <<= 1;
+    }
+    if invert {
+        for value in values.iter_mut() {
+            *value /= n as f64;
+        }
+    }
+}
+
+fn fft_mul(lhs: &[Complex<f64>], rhs: &[Complex<f64>]) -> Vec<Complex<f64>> {
+    if lhs.is_empty() || rhs.is_empty() {
+        return Vec::new();
+    }
+    let result_len = lhs.len() + rhs.len() - 1;
+    let size = result_len.next_power_of_two();
+    let mut lhs = lhs.to_vec();
+    let mut rhs = rhs.to_vec();
+    lhs.resize(size, Complex::zero());
+    rhs.resize(size, Complex::zero());
+    fft(&mut lhs, false);
+    fft(&mut rhs, false);
+    for (a, b) in lhs.iter_mut().zip(rhs.iter()) {
+        *a *= b;
+    }
+    fft(&mut lhs, true);
+    lhs.truncate(result_len);
+    lhs
+}
+
+impl<T> std::fmt::Display for DensePoly<T>
+where
+    T: FormatCoefficient + CRing + Clone + PartialEq,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_polynomial(0))
+    }
+}
+
+impl<T> Clone for DensePoly<T>
+where
+    T: CRing + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            coefficients: self.coefficients.clone(),
+        }
+    }
+}
+
+impl<T> PartialEq for DensePoly<T>
+where
+    T: CRing + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.coefficients == other.coefficients
+    }
+}
+
+impl<T> Zero for DensePoly<T>
+where
+    T: CRing + Clone,
+{
+    fn zero() -> Self {
+        DensePoly::new()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+}
+
+impl<T> One for DensePoly<T>
+where
+    T: CRing + Clone,
+{
+    fn one() -> Self {
+        DensePoly::from_coefficients(vec![T::one()])
+    }
+}
+
+// Univariate polynomials over a field form a Euclidean domain, with the
+// degree as the Euclidean function.
+impl<T> IntegralDomain for DensePoly<T> where T: Field + Clone + PartialEq {}
+
+impl<T> GCDDomain for DensePoly<T>
+where
+    T: Field + Clone + PartialEq,
+{
+    // The monic gcd, or zero if both are zero.
+    fn gcd(&self, other: &Self) -> Self {
+        let gcd = euclidean_gcd(self, other);
+        match gcd.coefficients.last() {
+            Some(leading) => {
+                let leading = leading.clone();
+                gcd * (T::one() / leading)
+            }
+            None => gcd,
+        }
+    }
+}
+
+impl<T> EuclideanDomain for DensePoly<T>
+where
+    T: Field + Clone + PartialEq,
+{
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        match self.div_rem(other) {
+            Ok(res) => res,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+// DensePoly + DensePoly
+impl<T> ops::Add for DensePoly<T>
+where
+    T: CRing + Clone,
+{
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        DensePoly::from_coefficients(add_slices(&self.coefficients, &other.coefficients))
+    }
+}
+
+// DensePoly - DensePoly
+impl<T> ops::Sub for DensePoly<T>
+where
+    T: CRing + Clone,
+{
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        DensePoly::from_coefficients(sub_slices(&self.coefficients, &other.coefficients))
+    }
+}
+
+// DensePoly * DensePoly
+impl<T> ops::Mul for DensePoly<T>
+where
+    T: CRing + Clone,
+{
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        DensePoly::from_coefficients(karatsuba(&self.coefficients, &other.coefficients))
+    }
+}
+
+// DensePoly * T
+impl<T> ops::Mul<T> for DensePoly<T>
+where
+    T: CRing + Clone,
+{
+    type Output = Self;
+    fn mul(self, other: T) -> Self {
+        DensePoly::from_coefficients(
+            self.coefficients
+                .into_iter()
+                .map(|c| c * other.clone())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::ModIntNtt;
+    use crate::polynomial::Polynomial64;
+    use num::rational::Ratio;
+    use rstest::*;
+
+    // Deterministic pseudo-random coefficients in [-50, 50].
+    fn coefficients(len: usize, seed: i64) -> Vec<i64> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = (state * 1103515245 + 12345) % 2147483648;
+                state % 101 - 50
+            })
+            .collect()
+    }
+
+    #[rstest]
+    fn test_from_polynomial() {
+        let polynomial = Polynomial64::from("3x^4 - x^2 + 2").unwrap();
+        let dense = DensePoly::from_polynomial(&polynomial, 0).unwrap();
+        assert_eq!(dense.coefficients, vec![2.0, 0.0, -1.0, 0.0, 3.0]);
+        assert_eq!(dense.degree(), Some(4));
+        assert_eq!(format!("{}", dense.to_polynomial(0)), "3x^4 - x^2 + 2");
+
+        let polynomial = Polynomial64::from("y^3 + 2y").unwrap();
+        let dense = DensePoly::from_polynomial(&polynomial, 1).unwrap();
+        assert_eq!(format!("{}", dense.to_polynomial(1)), "y^3 + 2y");
+    }
+
+    #[rstest]
+    fn test_from_polynomial_not_univariate() {
+        let polynomial = Polynomial64::from("x^2 + xy").unwrap();
+        match DensePoly::from_polynomial(&polynomial, 0) {
+            Ok(v) => panic!("{:?}", v),
+            Err(e) => assert!(matches!(e, DensePolyErr::NotUnivariate(_))),
+        }
+    }
+
+    #[rstest]
+    fn test_trim_and_zero() {
+        let dense = DensePoly::from_coefficients(vec![1, 2, 0, 0]);
+        assert_eq!(dense.coefficients, vec![1, 2]);
+        let zero = dense.clone() - dense;
+        assert!(zero.is_zero());
+        assert_eq!(zero.degree(), None);
+    }
+
+    #[rstest]
+    fn test_evaluate() {
+        let dense = DensePoly::from_coefficients(vec![1, -3, 0, 2]);
+        assert_eq!(dense.evaluate(2), 11);
+    }
+
+    #[rstest]
+    #[case(5, 7)]
+    #[case(40, 33)]
+    #[case(100, 257)]
+    #[case(300, 301)]
+    fn test_multiplication_algorithms_agree(#[case] lhs_len: usize, #[case] rhs_len: usize) {
+        let lhs = DensePoly::from_coefficients(coefficients(lhs_len, 1));
+        let rhs = DensePoly::from_coefficients(coefficients(rhs_len, 2));
+        let expected = lhs.mul_schoolbook(&rhs);
+        assert_eq!(lhs.mul_karatsuba(&rhs), expected);
+        assert_eq!(lhs.mul_toom3(&rhs), expected);
+        assert_eq!(lhs.clone() * rhs.clone(), expected);
+
+        let to_field = |poly: &DensePoly<i64>| {
+            DensePoly::from_coefficients(
+                poly.coefficients
+                    .iter()
+                    .map(|&c| ModIntNtt::from_i64(c))
+                    .collect(),
+            )
+        };
+        assert_eq!(
+            to_field(&lhs).mul_ntt(&to_field(&rhs)).unwrap(),
+            to_field(&expected)
+        );
+
+        let to_float = |poly: &DensePoly<i64>| {
+            DensePoly::from_coefficients(poly.coefficients.iter().map(|&c| c as f64).collect())
+        };
+        let product = to_float(&lhs).mul_fft(&to_float(&rhs));
+        assert_eq!(product.coefficients.len(), expected.coefficients.len());
+        for (a, &b) in product
+            .coefficients
+            .iter()
+            .zip(expected.coefficients.iter())
+        {
+            assert!((a - b as f64).abs() < 1e-6);
+        }
+    }
+
+    #[rstest]
+    fn test_mul_fft_complex() {
+        // (1 + ix)(1 - ix) = 1 + x^2
+        let lhs =
+            DensePoly::from_coefficients(vec![Complex::new(1.0, 0.0), Complex::new(0.0, 1.0)]);
+        let rhs =
+            DensePoly::from_coefficients(vec![Complex::new(1.0, 0.0), Complex::new(0.0, -1.0)]);
+        let product = lhs.mul_fft(&rhs);
+        let expected = [
+            Complex::new(1.0, 0.0),
+            Complex::zero(),
+            Complex::new(1.0, 0.0),
+        ];
+        for (a, b) in product.coefficients.iter().zip(expected.iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[rstest]
+    fn test_div_rem_long() {
+        // x^3 - 1 = (x - 1)(x^2 + x + 1)
+        let dividend = DensePoly::from_coefficients(vec![-1.0, 0.0, 0.0, 1.0]);
+        let divisor = DensePoly::from_coefficients(vec![-1.0, 1.0]);
+        let (quotient, remainder) = dividend.div_rem(&divisor).unwrap();
+        assert_eq!(quotient.coefficients, vec![1.0, 1.0, 1.0]);
+        assert!(remainder.is_zero());
+
+        let dividend = DensePoly::from_coefficients(vec![3.0, 0.0, 1.0]);
+        let (quotient, remainder) = dividend.div_rem(&divisor).unwrap();
+        assert_eq!(quotient.coefficients, vec![1.0, 1.0]);
+        assert_eq!(remainder.coefficients, vec![4.0]);
+    }
+
+    #[rstest]
+    fn test_div_rem_newton_agrees_with_long() {
+        let to_field = |values: Vec<i64>| {
+            DensePoly::from_coefficients(values.into_iter().map(ModIntNtt::from_i64).collect())
+        };
+        let dividend = to_field(coefficients(300, 3));
+        let divisor = to_field(coefficients(120, 4));
+        let (quotient, remainder) = dividend.div_rem(&divisor).unwrap();
+        let (long_quotient, long_remainder) = dividend.div_rem_long(&divisor).unwrap();
+        assert_eq!(quotient, long_quotient);
+        assert_eq!(remainder, long_remainder);
+        assert!(remainder.degree().unwrap() < divisor.degree().unwrap());
+        assert_eq!(quotient * divisor + remainder, dividend);
+    }
+
+    #[rstest]
+    fn test_inverse_series() {
+        // 1 / (1 - x) = 1 + x + x^2 + ...
+        let dense = DensePoly::from_coefficients(vec![Ratio::new(1, 1), Ratio::new(-1, 1)]);
+        let inverse = dense.inverse_series(6).unwrap();
+        assert_eq!(inverse.coefficients, vec![Ratio::new(1, 1); 6]);
+    }
+
+    #[rstest]
+    fn test_division_by_zero() {
+        let dense = DensePoly::from_coefficients(vec![1.0, 2.0]);
+        match dense.div_rem(&DensePoly::new()) {
+            Ok(v) => panic!("{:?}", v),
+            Err(e) => assert!(matches!(e, DensePolyErr::DivisionByZero(_))),
+        }
+    }
+
+    #[rstest]
+    fn test_gcd() {
+        let to_rational = |values: Vec<i64>| {
+            DensePoly::from_coefficients(values.into_iter().map(Ratio::from_integer).collect())
+        };
+        // (x - 1)(x + 2) and 2(x - 1)(x - 3)
+        let lhs = to_rational(vec![-2, 1, 1]);
+        let rhs = to_rational(vec![6, -8, 2]);
+        assert_eq!(lhs.gcd(&rhs), to_rational(vec![-1, 1]));
+        assert_eq!(lhs.gcd(&DensePoly::zero()), lhs.gcd(&lhs));
+        assert!(DensePoly::<Ratio<i64>>::zero()
+            .gcd(&DensePoly::zero())
+            .is_zero());
+
+        let (quotient, remainder) = rhs.quo_rem(&lhs);
+        assert_eq!(quotient, to_rational(vec![2]));
+        assert_eq!(remainder, to_rational(vec![10, -10]));
+    }
+}
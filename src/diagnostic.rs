@@ -0,0 +1,215 @@
+use std::cmp;
+use std::fmt;
+
+// A half-open range of character (not byte) offsets into the parsed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    // The smallest span covering both.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            start: cmp::min(self.start, other.start),
+            end: cmp::max(self.end, other.end),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// An error located in the input, with what would have been accepted there
+// and any further explanation. Rendering needs the input the spans refer to.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    pub expected: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: String, span: Span) -> Diagnostic {
+        Diagnostic {
+            message,
+            span,
+            expected: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_expected(mut self, expected: Vec<String>) -> Diagnostic {
+        self.expected = expected;
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Diagnostic {
+        self.notes.push(note);
+        self
+    }
+
+    // Renders in the style of rustc, underlining the whole span:
+    //
+    //   error[E0002]: Expected closing parenthesis
+    //    |
+    //  1 | (x + y
+    //    |       ^ expected ')'
+    //    = note: ...
+    pub fn render(&self, code: &str, source: &str) -> String {
+        self.render_lines(code, source, None)
+    }
+
+    // Renders as render does for source read from path, where it starts on
+    // line first_line, numbering the lines to match and giving the location
+    // after the heading:
+    //
+    //   error[E0002]: Expected closing parenthesis
+    //    --> script.poly:3:7
+    //    |
+    //  3 | (x + y
+    //    |       ^ expected ')'
+    pub fn render_in(&self, code: &str, source: &str, path: &str, first_line: usize) -> String {
+        self.render_lines(code, source, Some((path, first_line)))
+    }
+
+    fn render_lines(&self, code: &str, source: &str, location: Option<(&str, usize)>) -> String {
+        let mut output = format!("error[{}]: {}\n", code, self.message);
+
+        // find the line holding the start of the span
+        let mut line_start = 0;
+        let mut line_number = 1;
+        let mut line = "";
+        for (ind, text) in source.split('\n').enumerate() {
+            let len = text.chars().count();
+            line = text;
+            line_number = ind + 1;
+            if self.span.start <= line_start + len {
+                break;
+            }
+            line_start += len + 1;
+        }
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let line_len = line.chars().count();
+
+        let column = self.span.start - line_start;
+        if let Some((_, first_line)) = location {
+            line_number += first_line - 1;
+        }
+        // spans running past the end of the line are cut off there, and an
+        // empty span (such as the end of input) still gets one caret
+        let width = cmp::max(
+            1,
+            cmp::min(self.span.end, line_start + line_len).saturating_sub(self.span.start),
+        );
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        if let Some((path, _)) = location {
+            output.push_str(&format!(
+                "{}--> {}:{}:{}\n",
+                gutter,
+                path,
+                line_number,
+                column + 1
+            ));
+        }
+        output.push_str(&format!("{} |\n", gutter));
+        output.push_str(&format!("{} | {}\n", line_number, line));
+        output.push_str(&format!(
+            "{} | {}{}",
+            gutter,
+            " ".repeat(column),
+            "^".repeat(width)
+        ));
+        match self.expected.len() {
+            0 => {}
+            1 => output.push_str(&format!(" expected {}", self.expected[0])),
+            _ => output.push_str(&format!(" expected one of {}", self.expected.join(", "))),
+        }
+        output.push('\n');
+        for note in self.notes.iter() {
+            output.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+        output
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn test_render_multi_char_span() {
+        let diagnostic = Diagnostic::new(String::from("Invalid literal"), Span::new(4, 7))
+            .with_note(String::from("integers only"));
+        assert_eq!(
+            diagnostic.render("E0005", "x + 2.5y\n"),
+            "error[E0005]: Invalid literal\n  |\n1 | x + 2.5y\n  |     ^^^\n  = note: integers only\n"
+        );
+    }
+
+    #[rstest]
+    fn test_render_end_of_input() {
+        let diagnostic = Diagnostic::new(String::from("Unclosed parenthesis"), Span::new(6, 6))
+            .with_expected(vec![String::from("')'")]);
+        assert_eq!(
+            diagnostic.render("E0002", "(x + y"),
+            "error[E0002]: Unclosed parenthesis\n  |\n1 | (x + y\n  |       ^ expected ')'\n"
+        );
+    }
+
+    #[rstest]
+    fn test_render_later_line() {
+        let diagnostic = Diagnostic::new(String::from("Unexpected token"), Span::new(10, 11))
+            .with_expected(vec![
+                String::from("number"),
+                String::from("variable"),
+                String::from("'('"),
+                String::from("'-'"),
+                String::from("'+'"),
+            ]);
+        assert_eq!(
+            diagnostic.render("E0003", "(x + y\n + *)"),
+            "error[E0003]: Unexpected token\n  |\n2 |  + *)\n  |    ^ expected one of number, variable, '(', '-', '+'\n"
+        );
+    }
+
+    #[rstest]
+    fn test_render_in_file() {
+        let diagnostic = Diagnostic::new(String::from("Unclosed parenthesis"), Span::new(6, 6))
+            .with_expected(vec![String::from("')'")]);
+        assert_eq!(
+            diagnostic.render_in("E0002", "(x + y", "script.poly", 12),
+            "error[E0002]: Unclosed parenthesis\n  --> script.poly:12:7\n   |\n12 | (x + y\n   |       ^ expected ')'\n"
+        );
+    }
+
+    #[rstest]
+    fn test_span_to() {
+        assert_eq!(Span::new(3, 5).to(Span::new(0, 1)), Span::new(0, 5));
+        assert_eq!(Span::new(3, 5).len(), 2);
+        assert!(Span::new(4, 4).is_empty());
+    }
+}
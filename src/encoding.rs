@@ -0,0 +1,509 @@
+use crate::exponents::Exponents;
+use crate::modint::ModInt;
+use crate::monomial::Monomial;
+use crate::polynomial::Polynomial;
+use crate::CRing;
+use log::error;
+use num::bigint::{BigInt, Sign};
+use num::complex::Complex;
+use num::rational::Ratio;
+use num::{Integer, Zero};
+use std::cmp::Ordering;
+
+// A compact binary encoding of polynomials, for those too large to store as
+// JSON. All integers are LEB128 varints, so small exponents take a byte each.
+//
+//   magic      b"poly"
+//   version    1 byte, ENCODING_VERSION
+//   variables  varint, the length of every exponent vector
+//   terms      varint
+//   per term   the exponents, zigzag encoded like i32 so that negative ones
+//              survive, then the coefficient
+//
+// The terms are in canonical order. Coefficients are encoded by Encode, with
+// no type tag, so a polynomial must be decoded with the coefficient type it
+// was encoded with.
+const MAGIC: &[u8; 4] = b"poly";
+const ENCODING_VERSION: u8 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum EncodingErr {
+    InvalidHeader(String),
+    UnexpectedEnd(String),
+    InvalidValue(String),
+}
+
+fn unexpected_end<T>() -> Result<T, EncodingErr> {
+    let msg = String::from("Unexpected end of input");
+    error!("{}", msg);
+    Err(EncodingErr::UnexpectedEnd(msg))
+}
+
+fn invalid_value<T>(msg: String) -> Result<T, EncodingErr> {
+    error!("{}", msg);
+    Err(EncodingErr::InvalidValue(msg))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u128, EncodingErr> {
+    let mut value: u128 = 0;
+    for shift in (0..128).step_by(7) {
+        let (&byte, rest) = match input.split_first() {
+            Some(v) => v,
+            None => return unexpected_end(),
+        };
+        *input = rest;
+        let bits = u128::from(byte & 0x7f);
+        if bits.checked_shl(shift).map(|v| v >> shift) != Some(bits) {
+            break;
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    invalid_value(String::from("Varint overflow"))
+}
+
+fn read_usize(input: &mut &[u8]) -> Result<usize, EncodingErr> {
+    let value = read_varint(input)?;
+    match usize::try_from(value) {
+        Ok(v) => Ok(v),
+        Err(_) => invalid_value(format!("Length {} out of range", value)),
+    }
+}
+
+fn read_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], EncodingErr> {
+    if input.len() < len {
+        return unexpected_end();
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+// Coefficient types with a binary encoding.
+pub trait Encode: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+
+    // Reads a value from the front of input, advancing it past the value.
+    fn decode(input: &mut &[u8]) -> Result<Self, EncodingErr>;
+}
+
+macro_rules! impl_float_encode {
+    ($($t:ty),*) => {$(
+        impl Encode for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn decode(input: &mut &[u8]) -> Result<Self, EncodingErr> {
+                let bytes = read_bytes(input, std::mem::size_of::<$t>())?;
+                Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    )*};
+}
+
+impl_float_encode!(f32, f64);
+
+// Signed integers are zigzag encoded, so small magnitudes of either sign
+// take few bytes.
+macro_rules! impl_integer_encode {
+    ($($t:ty),*) => {$(
+        impl Encode for $t {
+            fn encode(&self, out: &mut Vec<u8>) {
+                let value = *self as i128;
+                write_varint(out, ((value << 1) ^ (value >> 127)) as u128);
+            }
+
+            fn decode(input: &mut &[u8]) -> Result<Self, EncodingErr> {
+                let zigzag = read_varint(input)?;
+                let value = (zigzag >> 1) as i128 ^ -((zigzag & 1) as i128);
+                match <$t>::try_from(value) {
+                    Ok(v) => Ok(v),
+                    Err(_) => invalid_value(format!(
+                        "{} out of range for {}",
+                        value,
+                        stringify!($t)
+                    )),
+                }
+            }
+        }
+    )*};
+}
+
+impl_integer_encode!(i8, i16, i32, i64, i128, isize);
+
+// The byte length shifted left once, with the sign in the low bit, then the
+// magnitude in little endian bytes.
+impl Encode for BigInt {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let (sign, magnitude) = self.to_bytes_le();
+        let magnitude = if self.is_zero() { vec![] } else { magnitude };
+        let negative = u128::from(sign == Sign::Minus);
+        write_varint(out, (magnitude.len() as u128) << 1 | negative);
+        out.extend_from_slice(&magnitude);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, EncodingErr> {
+        let header = read_usize(input)?;
+        let magnitude = read_bytes(input, header >> 1)?;
+        let sign = if header & 1 == 1 {
+            Sign::Minus
+        } else {
+            Sign::Plus
+        };
+        Ok(BigInt::from_bytes_le(sign, magnitude))
+    }
+}
+
+impl<T> Encode for Ratio<T>
+where
+    T: Clone + Integer + Encode,
+{
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.numer().encode(out);
+        self.denom().encode(out);
+    }
+
+    // Only a ratio as encoded, in lowest terms over a positive denominator,
+    // is read. Reducing any other would divide by zero, or for fixed width
+    // integers overflow, as i64::MIN / -1 does.
+    fn decode(input: &mut &[u8]) -> Result<Self, EncodingErr> {
+        let numerator = T::decode(input)?;
+        let denominator = T::decode(input)?;
+        if denominator.is_zero() {
+            return invalid_value(String::from("Zero denominator"));
+        }
+        if denominator < T::zero() {
+            return invalid_value(String::from("Negative denominator"));
+        }
+        if !numerator.gcd(&denominator).is_one() {
+            return invalid_value(String::from("Ratio not in lowest terms"));
+        }
+        Ok(Ratio::new_raw(numerator, denominator))
+    }
+}
+
+impl<T: Encode> Encode for Complex<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.re.encode(out);
+        self.im.encode(out);
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, EncodingErr> {
+        let re = T::decode(input)?;
+        let im = T::decode(input)?;
+        Ok(Complex::new(re, im))
+    }
+}
+
+impl<const P: u64> Encode for ModInt<P> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        write_varint(out, u128::from(self.value));
+    }
+
+    fn decode(input: &mut &[u8]) -> Result<Self, EncodingErr> {
+        let value = read_varint(input)?;
+        match u64::try_from(value) {
+            Ok(v) if v < P => Ok(ModInt { value: v }),
+            _ => invalid_value(format!("{} is not a residue modulo {}", value, P)),
+        }
+    }
+}
+
+impl<T> Polynomial<T>
+where
+    T: CRing + PartialEq + Encode,
+{
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let count = self
+            .monomials
+            .iter()
+            .map(|monomial| monomial.power_list.len())
+            .max()
+            .unwrap_or(0);
+        let mut out = Vec::from(&MAGIC[..]);
+        out.push(ENCODING_VERSION);
+        write_varint(&mut out, count as u128);
+        write_varint(&mut out, self.monomials.len() as u128);
+        for monomial in self.monomials.iter() {
+            for ind in 0..count {
+                monomial.power_list.get(ind).encode(&mut out);
+            }
+            monomial.coefficient.encode(&mut out);
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Polynomial<T>, EncodingErr> {
+        let mut input = bytes;
+        if read_bytes(&mut input, MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            let msg = String::from("Not an encoded polynomial");
+            error!("{}", msg);
+            return Err(EncodingErr::InvalidHeader(msg));
+        }
+        let version = read_bytes(&mut input, 1)?[0];
+        if version != ENCODING_VERSION {
+            let msg = format!("Unsupported encoding version {}", version);
+            error!("{}", msg);
+            return Err(EncodingErr::InvalidHeader(msg));
+        }
+        let count = read_usize(&mut input)?;
+        let terms = read_usize(&mut input)?;
+        // every exponent and coefficient takes at least a byte, so bad counts
+        // cannot allocate more than the input
+        if terms > 0 && count >= input.len() {
+            return unexpected_end();
+        }
+        let mut polynomial = Polynomial::new();
+        polynomial.monomials.reserve(terms.min(input.len()));
+        let mut powers = vec![0; count];
+        for _ in 0..terms {
+            for power in powers.iter_mut() {
+                *power = i32::decode(&mut input)?;
+            }
+            let power_list = match Exponents::checked_from_slice(&powers) {
+                Some(v) => v,
+                None => return invalid_value(format!("Degree overflow in {:?}", powers)),
+            };
+            polynomial.monomials.push(Monomial {
+                coefficient: T::decode(&mut input)?,
+                power_list,
+            });
+        }
+        if !input.is_empty() {
+            return invalid_value(format!("{} bytes after the last term", input.len()));
+        }
+        // a term is encoded once, and summing repeats could overflow a fixed
+        // width coefficient
+        polynomial.monomials.sort_unstable_by(|a, b| a.cmp_terms(b));
+        if let Some(pair) = polynomial
+            .monomials
+            .windows(2)
+            .find(|pair| pair[0].cmp_terms(&pair[1]) == Ordering::Equal)
+        {
+            return invalid_value(format!("Repeated term {:?}", pair[0].power_list));
+        }
+        polynomial.normalize();
+        Ok(polynomial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::ModIntNtt;
+    use crate::polynomial::{Polynomial64, PolynomialC, PolynomialCQ, PolynomialQ, PolynomialZ};
+    use rstest::*;
+
+    const NTT_MINUS_ONE: u64 = crate::modint::NTT_PRIME - 1;
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(-1)]
+    #[case(127)]
+    #[case(-300)]
+    #[case(i64::MAX)]
+    #[case(i64::MIN)]
+    fn test_integer_round_trip(#[case] value: i64) {
+        let mut out = vec![];
+        value.encode(&mut out);
+        let mut input = out.as_slice();
+        assert_eq!(i64::decode(&mut input), Ok(value));
+        assert!(input.is_empty());
+    }
+
+    #[rstest]
+    fn test_small_values_take_a_byte() {
+        let mut out = vec![];
+        (-5i32).encode(&mut out);
+        ModIntNtt::new(100).encode(&mut out);
+        BigInt::from(0).encode(&mut out);
+        assert_eq!(out.len(), 3);
+    }
+
+    #[rstest]
+    #[case("0")]
+    #[case("-7.25")]
+    #[case("(x + y + z)^6 - 3x^200")]
+    #[case("α^2β - 2ω + x")]
+    fn test_round_trip_f64(#[case] expr: &str) {
+        let polynomial = Polynomial64::from(expr).unwrap();
+        assert_eq!(
+            Polynomial64::from_bytes(&polynomial.to_bytes()),
+            Ok(polynomial)
+        );
+    }
+
+    #[rstest]
+    fn test_round_trip_exact() {
+        let polynomial = PolynomialZ::from("(x - 2y)^100").unwrap();
+        assert_eq!(
+            PolynomialZ::from_bytes(&polynomial.to_bytes()),
+            Ok(polynomial)
+        );
+        let polynomial = PolynomialQ::from("(x/3 - 2.5y^2)^7").unwrap();
+        assert_eq!(
+            PolynomialQ::from_bytes(&polynomial.to_bytes()),
+            Ok(polynomial)
+        );
+        let polynomial = PolynomialC::from("(1 + 2i)x - iy").unwrap();
+        assert_eq!(
+            PolynomialC::from_bytes(&polynomial.to_bytes()),
+            Ok(polynomial)
+        );
+        let polynomial = PolynomialCQ::from("(x/(1 + i) - 1/3)^3").unwrap();
+        assert_eq!(
+            PolynomialCQ::from_bytes(&polynomial.to_bytes()),
+            Ok(polynomial)
+        );
+    }
+
+    #[rstest]
+    fn test_round_trip_negative_exponents() {
+        // 3x^-2 + y^-1z^200, which no expression parses to
+        let mut polynomial = Polynomial64::new();
+        for (coefficient, powers) in [(3.0, vec![-2]), (1.0, vec![0, -1, 200])] {
+            polynomial += Monomial {
+                coefficient,
+                power_list: Exponents::from_slice(&powers),
+            };
+        }
+        let bytes = polynomial.to_bytes();
+        assert_eq!(Polynomial64::from_bytes(&bytes), Ok(polynomial));
+    }
+
+    #[rstest]
+    fn test_round_trip_modint() {
+        let mut polynomial = Polynomial::<ModIntNtt>::new();
+        for (ind, value) in [5, NTT_MINUS_ONE, 17].into_iter().enumerate() {
+            let mut power_list = Exponents::new();
+            power_list.set(ind, ind as i32 + 1);
+            polynomial += Monomial {
+                coefficient: ModIntNtt::new(value),
+                power_list,
+            };
+        }
+        assert_eq!(
+            Polynomial::<ModIntNtt>::from_bytes(&polynomial.to_bytes()),
+            Ok(polynomial)
+        );
+    }
+
+    #[rstest]
+    fn test_compact() {
+        // 61 terms of two exponents below 64, a byte each, and a small
+        // coefficient
+        let polynomial = PolynomialZ::from("(x + y)^60").unwrap();
+        let bytes = polynomial.to_bytes();
+        let coefficients: usize = polynomial
+            .monomials
+            .iter()
+            .map(|monomial| {
+                let mut out = vec![];
+                monomial.coefficient.encode(&mut out);
+                out.len()
+            })
+            .sum();
+        assert_eq!(bytes.len(), 4 + 1 + 1 + 1 + 61 * 2 + coefficients);
+    }
+
+    #[rstest]
+    #[case(b"poly".to_vec(), EncodingErr::UnexpectedEnd(String::from("Unexpected end of input")))]
+    #[case(b"ploy\x01\x00\x00".to_vec(), EncodingErr::InvalidHeader(String::from("Not an encoded polynomial")))]
+    #[case(b"poly\x02\x00\x00".to_vec(), EncodingErr::InvalidHeader(String::from("Unsupported encoding version 2")))]
+    #[case(b"poly\x01\x01\x01\x02".to_vec(), EncodingErr::UnexpectedEnd(String::from("Unexpected end of input")))]
+    #[case(b"poly\x01\xff\xff\xff\xff\x0f\x01\x00".to_vec(), EncodingErr::UnexpectedEnd(String::from("Unexpected end of input")))]
+    #[case(b"poly\x01\x00\x00\x00".to_vec(), EncodingErr::InvalidValue(String::from("1 bytes after the last term")))]
+    #[case(b"poly\x01\x01\x01\xff\xff\xff\xff\x1f\x00".to_vec(), EncodingErr::InvalidValue(String::from("-4294967296 out of range for i32")))]
+    fn test_invalid_bytes(#[case] bytes: Vec<u8>, #[case] expected: EncodingErr) {
+        assert_eq!(PolynomialZ::from_bytes(&bytes), Err(expected));
+    }
+
+    #[rstest]
+    #[case(i64::MIN, -1, "Negative denominator")]
+    #[case(1, i64::MIN, "Negative denominator")]
+    #[case(i64::MIN, i64::MIN, "Negative denominator")]
+    #[case(3, 0, "Zero denominator")]
+    #[case(0, 2, "Ratio not in lowest terms")]
+    #[case(i64::MIN, 2, "Ratio not in lowest terms")]
+    #[case(i64::MAX, i64::MAX, "Ratio not in lowest terms")]
+    fn test_invalid_ratio(#[case] numerator: i64, #[case] denominator: i64, #[case] msg: &str) {
+        let mut out = vec![];
+        numerator.encode(&mut out);
+        denominator.encode(&mut out);
+        assert_eq!(
+            Ratio::<i64>::decode(&mut out.as_slice()),
+            Err(EncodingErr::InvalidValue(String::from(msg)))
+        );
+    }
+
+    #[rstest]
+    #[case(i64::MIN, 1)]
+    #[case(-3, 7)]
+    #[case(0, 1)]
+    fn test_ratio_round_trip(#[case] numerator: i64, #[case] denominator: i64) {
+        let ratio = Ratio::new(numerator, denominator);
+        let mut out = vec![];
+        ratio.encode(&mut out);
+        assert_eq!(Ratio::<i64>::decode(&mut out.as_slice()), Ok(ratio));
+    }
+
+    #[rstest]
+    fn test_truncated_bytes() {
+        let bytes = PolynomialCQ::from("(x/(1 + i) - 1/3)^3 - 1000000000y")
+            .unwrap()
+            .to_bytes();
+        for len in 0..bytes.len() {
+            assert!(PolynomialCQ::from_bytes(&bytes[..len]).is_err(), "{}", len);
+        }
+    }
+
+    #[rstest]
+    fn test_corrupted_bytes() {
+        // no byte may make decoding panic, whatever the value read
+        let bytes = PolynomialQ::from("(x/3 - 2.5y^2)^3").unwrap().to_bytes();
+        for ind in 0..bytes.len() {
+            for byte in [0x00, 0x01, 0x7f, 0x80, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[ind] = byte;
+                let _ = PolynomialQ::from_bytes(&corrupted);
+                let _ = Polynomial::<Ratio<i64>>::from_bytes(&corrupted);
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_repeated_term() {
+        // i64::MAX x + i64::MAX x
+        let mut bytes = b"poly\x01\x01\x02".to_vec();
+        for _ in 0..2 {
+            1i32.encode(&mut bytes);
+            Ratio::new(i64::MAX, 1).encode(&mut bytes);
+        }
+        assert!(matches!(
+            Polynomial::<Ratio<i64>>::from_bytes(&bytes),
+            Err(EncodingErr::InvalidValue(msg)) if msg.starts_with("Repeated term")
+        ));
+    }
+
+    #[rstest]
+    fn test_zero_denominator() {
+        // 1 / 0
+        let bytes = b"poly\x01\x00\x01\x02\x01\x00";
+        assert!(matches!(
+            PolynomialQ::from_bytes(bytes),
+            Err(EncodingErr::InvalidValue(_))
+        ));
+    }
+}
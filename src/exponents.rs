@@ -0,0 +1,418 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops;
+
+use smallvec::SmallVec;
+
+// Up to PACKED_VARS exponents in 0..=FIELD_MAX are stored in a single u128,
+// one byte per field with the top bit of every byte kept clear as a guard:
+//
+//   | 0 ... 0 | degree | x | y | z | u | v | w | s | t |
+//
+// from the most to the least significant byte, so that all eight standard
+// variables pack and only the Greek ones fall back.
+//
+// The total degree sits in the most significant byte, so comparing two
+// packed words as integers is the graded lexicographic order, and adding
+// them multiplies the monomials. A carry into a guard bit means a field
+// overflowed. Anything that does not fit falls back to a plain vector.
+const PACKED_VARS: usize = 8;
+const FIELD_MAX: i32 = 0x7f;
+const GUARD_MASK: u128 = 0x80_8080_8080_8080_8080;
+const DEGREE_SHIFT: u32 = 64;
+
+#[macro_export]
+macro_rules! exponents {
+    ($($power:expr),* $(,)?) => {
+        match $crate::exponents::Exponents::checked_from_slice(&[$($power),*]) {
+            Some(v) => v,
+            None => panic!("degree overflow in exponents!"),
+        }
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    Packed(u128),
+    // trailing zeros trimmed
+    Wide(SmallVec<[i32; 8]>),
+}
+
+// The exponent vector of a monomial, indexed by variable. Every value has a
+// single canonical representation (packed whenever it fits), so equality and
+// hashing can work on the representation directly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Exponents {
+    repr: Repr,
+}
+
+fn field_shift(ind: usize) -> u32 {
+    8 * (PACKED_VARS - 1 - ind) as u32
+}
+
+fn pack(powers: &[i32]) -> Option<u128> {
+    if powers.len() > PACKED_VARS {
+        return None;
+    }
+    let mut degree = 0;
+    let mut packed = 0;
+    for (ind, &power) in powers.iter().enumerate() {
+        if !(0..=FIELD_MAX).contains(&power) {
+            return None;
+        }
+        degree += power;
+        packed |= (power as u128) << field_shift(ind);
+    }
+    if degree > FIELD_MAX {
+        return None;
+    }
+    Some(packed | (degree as u128) << DEGREE_SHIFT)
+}
+
+impl Exponents {
+    // The exponents of a constant.
+    pub fn new() -> Exponents {
+        Exponents {
+            repr: Repr::Packed(0),
+        }
+    }
+
+    // Unchecked, for callers that know the degree fits in i32. Others go
+    // through checked_from_slice.
+    pub(crate) fn from_slice(powers: &[i32]) -> Exponents {
+        let len = powers
+            .iter()
+            .rposition(|&power| power != 0)
+            .map_or(0, |ind| ind + 1);
+        let powers = &powers[..len];
+        match pack(powers) {
+            Some(packed) => Exponents {
+                repr: Repr::Packed(packed),
+            },
+            None => Exponents {
+                repr: Repr::Wide(SmallVec::from_slice(powers)),
+            },
+        }
+    }
+
+    pub fn is_packed(&self) -> bool {
+        matches!(self.repr, Repr::Packed(_))
+    }
+
+    pub fn get(&self, ind: usize) -> i32 {
+        match &self.repr {
+            Repr::Packed(packed) => {
+                if ind >= PACKED_VARS {
+                    return 0;
+                }
+                ((packed >> field_shift(ind)) & FIELD_MAX as u128) as i32
+            }
+            Repr::Wide(powers) => powers.get(ind).copied().unwrap_or(0),
+        }
+    }
+
+    // Panics if the degree leaves the range of i32.
+    pub fn set(&mut self, ind: usize, power: i32) {
+        let mut powers = self.to_vec();
+        if powers.len() <= ind {
+            powers.resize(ind + 1, 0);
+        }
+        powers[ind] = power;
+        *self = match Exponents::checked_from_slice(&powers) {
+            Some(v) => v,
+            None => panic!("degree overflow in {:?}", powers.as_slice()),
+        };
+    }
+
+    // One past the last variable with a nonzero exponent.
+    pub fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Packed(_) => (0..PACKED_VARS)
+                .rposition(|ind| self.get(ind) != 0)
+                .map_or(0, |ind| ind + 1),
+            Repr::Wide(powers) => powers.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn degree(&self) -> i32 {
+        match &self.repr {
+            Repr::Packed(packed) => (packed >> DEGREE_SHIFT) as i32,
+            Repr::Wide(powers) => powers.iter().sum(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = i32> + '_ {
+        (0..self.len()).map(move |ind| self.get(ind))
+    }
+
+    pub fn to_vec(&self) -> SmallVec<[i32; 8]> {
+        self.iter().collect()
+    }
+
+    // The exponents of the monomial raised to the given power. Panics if an
+    // exponent or the degree overflows.
+    pub fn scale(&self, factor: i32) -> Exponents {
+        match self.checked_scale(factor) {
+            Some(v) => v,
+            None => panic!("exponent overflow in {} * {}", self, factor),
+        }
+    }
+
+    // None if an exponent or the total degree leaves the range of i32.
+    pub fn checked_scale(&self, factor: i32) -> Option<Exponents> {
+        let powers = self
+            .iter()
+            .map(|power| power.checked_mul(factor))
+            .collect::<Option<SmallVec<[i32; 8]>>>()?;
+        Exponents::checked_from_slice(&powers)
+    }
+
+    // The exponents of the product of the two monomials, or None if an
+    // exponent or the total degree leaves the range of i32.
+    pub fn checked_add(&self, other: &Exponents) -> Option<Exponents> {
+        if let (Repr::Packed(a), Repr::Packed(b)) = (&self.repr, &other.repr) {
+            let sum = a + b;
+            if sum & GUARD_MASK == 0 {
+                return Some(Exponents {
+                    repr: Repr::Packed(sum),
+                });
+            }
+        }
+        let len = std::cmp::max(self.len(), other.len());
+        let powers = (0..len)
+            .map(|ind| self.get(ind).checked_add(other.get(ind)))
+            .collect::<Option<SmallVec<[i32; 8]>>>()?;
+        Exponents::checked_from_slice(&powers)
+    }
+
+    // None if the total degree leaves the range of i32.
+    pub fn checked_from_slice(powers: &[i32]) -> Option<Exponents> {
+        let degree: i64 = powers.iter().map(|&power| i64::from(power)).sum();
+        i32::try_from(degree).ok()?;
+        Some(Exponents::from_slice(powers))
+    }
+}
+
+// The exponents of the product of the two monomials. Panics if an exponent
+// or the degree overflows, like the primitive integer types in debug builds.
+impl ops::Add for &Exponents {
+    type Output = Exponents;
+    fn add(self, other: Self) -> Exponents {
+        match self.checked_add(other) {
+            Some(v) => v,
+            None => panic!("exponent overflow in {} + {}", self, other),
+        }
+    }
+}
+
+impl Default for Exponents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Graded lexicographic order: higher total degree is greater, ties are
+// broken by the exponent of x, then y, and so on.
+impl Ord for Exponents {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if let (Repr::Packed(a), Repr::Packed(b)) = (&self.repr, &other.repr) {
+            return a.cmp(b);
+        }
+        let degree_a: i64 = self.iter().map(i64::from).sum();
+        let degree_b: i64 = other.iter().map(i64::from).sum();
+        degree_a.cmp(&degree_b).then_with(|| {
+            let max_len = std::cmp::max(self.len(), other.len());
+            (0..max_len)
+                .map(|ind| self.get(ind).cmp(&other.get(ind)))
+                .find(|&order| order != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+impl PartialOrd for Exponents {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Exponents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.to_vec().as_slice())
+    }
+}
+
+// Panics if the degree leaves the range of i32, like checked_from_slice
+// returning None.
+impl FromIterator<i32> for Exponents {
+    fn from_iter<I: IntoIterator<Item = i32>>(iter: I) -> Self {
+        let powers: SmallVec<[i32; 8]> = iter.into_iter().collect();
+        match Exponents::checked_from_slice(&powers) {
+            Some(v) => v,
+            None => panic!("degree overflow in {:?}", powers.as_slice()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    fn test_packing() {
+        let exponents = exponents![2, 0, 5];
+        assert!(exponents.is_packed());
+        assert_eq!(exponents.len(), 3);
+        assert_eq!(exponents.degree(), 7);
+        assert_eq!(exponents.get(0), 2);
+        assert_eq!(exponents.get(1), 0);
+        assert_eq!(exponents.get(2), 5);
+        assert_eq!(exponents.get(20), 0);
+    }
+
+    #[rstest]
+    fn test_standard_variables_pack() {
+        // x y z u v w s t
+        let exponents = exponents![1, 2, 3, 4, 5, 6, 7, 8];
+        assert!(exponents.is_packed());
+        assert_eq!(exponents.len(), 8);
+        assert_eq!(exponents.degree(), 36);
+        assert_eq!(exponents.get(7), 8);
+        assert!(exponents![0, 0, 0, 0, 0, 0, 0, 127].is_packed());
+        let sum = &exponents![0, 0, 0, 0, 0, 0, 0, 100] + &exponents![0, 0, 0, 0, 0, 0, 0, 28];
+        assert!(!sum.is_packed());
+        assert_eq!(sum.get(7), 128);
+    }
+
+    #[rstest]
+    fn test_trailing_zeros_are_canonical() {
+        assert_eq!(exponents![1, 2], exponents![1, 2, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(exponents![], exponents![0, 0, 0]);
+        assert!(exponents![0, 0, 0].is_empty());
+    }
+
+    #[rstest]
+    #[case(exponents![128])]
+    #[case(exponents![-1, 2])]
+    #[case(exponents![0, 0, 0, 0, 0, 0, 0, 0, 1])]
+    #[case(exponents![100, 0, 28])]
+    fn test_wide_fallback(#[case] exponents: Exponents) {
+        assert!(!exponents.is_packed());
+        let powers = exponents.to_vec();
+        assert_eq!(Exponents::from_slice(&powers), exponents);
+    }
+
+    #[rstest]
+    fn test_add() {
+        let sum = &exponents![1, 2, 3] + &exponents![4, 0, 1];
+        assert!(sum.is_packed());
+        assert_eq!(sum, exponents![5, 2, 4]);
+        assert_eq!(sum.degree(), 11);
+    }
+
+    #[rstest]
+    fn test_add_overflow() {
+        let sum = &exponents![100] + &exponents![100];
+        assert!(!sum.is_packed());
+        assert_eq!(sum.get(0), 200);
+        assert_eq!(sum.degree(), 200);
+
+        let sum = &exponents![60, 10] + &exponents![60];
+        assert!(!sum.is_packed());
+        assert_eq!(sum, exponents![120, 10]);
+    }
+
+    #[rstest]
+    fn test_checked_add() {
+        assert_eq!(
+            exponents![i32::MAX - 1].checked_add(&exponents![1]),
+            Some(exponents![i32::MAX])
+        );
+        assert_eq!(exponents![i32::MAX].checked_add(&exponents![1]), None);
+        // each exponent fits but the degree does not
+        assert_eq!(
+            exponents![i32::MAX - 1].checked_add(&exponents![0, 2]),
+            None
+        );
+        assert_eq!(
+            exponents![1, 2].checked_add(&exponents![3]),
+            Some(exponents![4, 2])
+        );
+    }
+
+    #[rstest]
+    fn test_checked_scale() {
+        assert_eq!(exponents![2, 3].checked_scale(4), Some(exponents![8, 12]));
+        assert_eq!(exponents![1 << 16].checked_scale(1 << 16), None);
+        assert_eq!(exponents![1 << 20, 1 << 20].checked_scale(1 << 10), None);
+    }
+
+    #[rstest]
+    #[should_panic(expected = "exponent overflow")]
+    fn test_add_overflow_panics() {
+        let _ = &exponents![i32::MAX] + &exponents![1];
+    }
+
+    #[rstest]
+    fn test_add_negative_returns_to_packed() {
+        let sum = &exponents![2, -1] + &exponents![0, 1];
+        assert!(sum.is_packed());
+        assert_eq!(sum, exponents![2]);
+    }
+
+    #[rstest]
+    fn test_ordering() {
+        assert!(exponents![2] > exponents![1, 0, 0]);
+        assert!(exponents![1, 1] > exponents![0, 2]);
+        assert!(exponents![0, 0, 3] > exponents![0, 2]);
+        // mixed representations agree with the packed order
+        assert!(exponents![200] > exponents![1, 1]);
+        assert!(exponents![0, 0, 0, 0, 0, 0, 0, 1] < exponents![1]);
+        assert!(exponents![0, 0, 0, 0, 0, 0, 0, 0, 1] < exponents![1]);
+        assert!(exponents![2, 0, 0, 0, 0, 0, 0, 0, 1] > exponents![1, 2]);
+        assert!(exponents![1, 0, 0, 0, 0, 0, 0, 0, 1] < exponents![1, 1]);
+    }
+
+    #[rstest]
+    fn test_set() {
+        let mut exponents = Exponents::new();
+        exponents.set(2, 4);
+        exponents.set(0, 1);
+        assert_eq!(exponents, exponents![1, 0, 4]);
+        exponents.set(7, 1);
+        assert!(exponents.is_packed());
+        exponents.set(8, 1);
+        assert!(!exponents.is_packed());
+        exponents.set(8, 0);
+        assert!(exponents.is_packed());
+    }
+
+    #[rstest]
+    fn test_checked_from_slice() {
+        assert_eq!(
+            Exponents::checked_from_slice(&[i32::MAX, 0]),
+            Some(exponents![i32::MAX])
+        );
+        // each exponent fits but the degree does not
+        assert_eq!(Exponents::checked_from_slice(&[i32::MAX, 1]), None);
+        assert_eq!(Exponents::checked_from_slice(&[i32::MIN, -1]), None);
+    }
+
+    #[rstest]
+    #[should_panic(expected = "degree overflow")]
+    fn test_set_overflow_panics() {
+        let mut exponents = exponents![i32::MAX];
+        exponents.set(1, 1);
+    }
+
+    #[rstest]
+    #[should_panic(expected = "degree overflow")]
+    fn test_collect_overflow_panics() {
+        let _: Exponents = [i32::MAX, i32::MAX].into_iter().collect();
+    }
+}
@@ -0,0 +1,323 @@
+use std::clone::Clone;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops;
+
+use crate::exponents::Exponents;
+use crate::monomial::Monomial;
+use crate::polynomial::{Polynomial, SparsePolynomial};
+use crate::render::FormatCoefficient;
+use crate::CRing;
+
+pub type HashPolynomial32 = HashPolynomial<f32>;
+pub type HashPolynomial64 = HashPolynomial<f64>;
+
+// A sparse polynomial whose terms are keyed by their exponent vector, which
+// hashes as a single packed word for most terms, so adding a term is an
+// expected O(1) lookup regardless of how many terms the polynomial has.
+// Terms are unordered; convert to a Polynomial for anything that needs the
+// monomial ordering. The map is private so that every key is the exponents
+// of its monomial and no term is zero, which add_term keeps up.
+#[derive(Debug)]
+pub struct HashPolynomial<T: CRing> {
+    terms: HashMap<Exponents, Monomial<T>>,
+}
+
+impl<T> HashPolynomial<T>
+where
+    T: CRing,
+{
+    pub fn new() -> HashPolynomial<T> {
+        HashPolynomial {
+            terms: HashMap::new(),
+        }
+    }
+}
+
+impl<T> HashPolynomial<T>
+where
+    T: CRing,
+{
+    // The term with the exponents, if its coefficient is not zero.
+    pub fn get(&self, power_list: &Exponents) -> Option<&Monomial<T>> {
+        self.terms.get(power_list)
+    }
+
+    // The terms in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Monomial<T>> {
+        self.terms.values()
+    }
+}
+
+impl<T> Default for HashPolynomial<T>
+where
+    T: CRing,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    pub fn to_polynomial(&self) -> Polynomial<T> {
+        let mut monomials: Vec<Monomial<T>> = self.terms.values().cloned().collect();
+        monomials.sort_unstable();
+        Polynomial { monomials }
+    }
+
+    pub fn from_polynomial(polynomial: &Polynomial<T>) -> HashPolynomial<T> {
+        polynomial.convert()
+    }
+}
+
+impl<T> SparsePolynomial<T> for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn empty() -> Self {
+        HashPolynomial::new()
+    }
+
+    fn num_terms(&self) -> usize {
+        self.terms.len()
+    }
+
+    fn terms<'a>(&'a self) -> Box<dyn Iterator<Item = &'a Monomial<T>> + 'a> {
+        Box::new(self.terms.values())
+    }
+
+    fn add_term(&mut self, monomial: Monomial<T>) {
+        if monomial.coefficient.is_zero() {
+            return;
+        }
+        match self.terms.get_mut(&monomial.power_list) {
+            Some(existing) => {
+                existing.coefficient = existing.coefficient.clone() + monomial.coefficient;
+                if existing.coefficient.is_zero() {
+                    self.terms.remove(&monomial.power_list);
+                }
+            }
+            None => {
+                self.terms.insert(monomial.power_list.clone(), monomial);
+            }
+        }
+    }
+}
+
+impl<T> From<Polynomial<T>> for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn from(polynomial: Polynomial<T>) -> Self {
+        HashPolynomial::from_terms(polynomial.monomials)
+    }
+}
+
+impl<T> From<HashPolynomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn from(polynomial: HashPolynomial<T>) -> Self {
+        let mut monomials: Vec<Monomial<T>> = polynomial.terms.into_values().collect();
+        monomials.sort_unstable();
+        Polynomial { monomials }
+    }
+}
+
+impl<T> std::fmt::Display for HashPolynomial<T>
+where
+    T: FormatCoefficient + CRing + Clone + PartialEq,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_polynomial())
+    }
+}
+
+impl<T> Clone for HashPolynomial<T>
+where
+    T: CRing + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            terms: self.terms.clone(),
+        }
+    }
+}
+
+// HashPolynomial += Monomial
+impl<T> ops::AddAssign<Monomial<T>> for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn add_assign(&mut self, other: Monomial<T>) {
+        self.add_term(other);
+    }
+}
+
+// HashPolynomial -= Monomial
+impl<T> ops::SubAssign<Monomial<T>> for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn sub_assign(&mut self, other: Monomial<T>) {
+        self.sub_term(other);
+    }
+}
+
+// HashPolynomial += HashPolynomial
+impl<T> ops::AddAssign for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn add_assign(&mut self, other: Self) {
+        for (_, monomial) in other.terms {
+            self.add_term(monomial);
+        }
+    }
+}
+
+// HashPolynomial -= HashPolynomial
+impl<T> ops::SubAssign for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn sub_assign(&mut self, other: Self) {
+        for (_, monomial) in other.terms {
+            self.sub_term(monomial);
+        }
+    }
+}
+
+// HashPolynomial + HashPolynomial
+impl<T> ops::Add for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let mut new_poly = self;
+        new_poly += other;
+        new_poly
+    }
+}
+
+// HashPolynomial - HashPolynomial
+impl<T> ops::Sub for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        let mut new_poly = self;
+        new_poly -= other;
+        new_poly
+    }
+}
+
+// HashPolynomial * HashPolynomial
+impl<T> ops::Mul for HashPolynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        self.mul_poly(&other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exponents;
+    use crate::polynomial::Polynomial64;
+    use rstest::*;
+
+    #[fixture]
+    fn polynomial_a() -> HashPolynomial64 {
+        HashPolynomial::from(Polynomial64::from("5x^2 + 6y^2").unwrap())
+    }
+
+    #[rstest]
+    fn test_add_term_like_terms() {
+        let mut polynomial = HashPolynomial64::new();
+        polynomial += Monomial {
+            coefficient: 2.0,
+            power_list: exponents![1, 0, 0],
+        };
+        // same term with a different number of allocated variables
+        polynomial += Monomial {
+            coefficient: 3.0,
+            power_list: exponents![1, 0, 0, 0, 0, 0, 0, 0],
+        };
+        assert_eq!(polynomial.num_terms(), 1);
+        assert_eq!(format!("{}", polynomial), "5x");
+    }
+
+    #[rstest]
+    fn test_get(polynomial_a: HashPolynomial64) {
+        let mut polynomial = polynomial_a;
+        assert_eq!(polynomial.get(&exponents![2]).unwrap().coefficient, 5.0);
+        assert!(polynomial.get(&exponents![1]).is_none());
+        // cancelling a term removes it, and a zero term is never stored
+        polynomial -= Monomial {
+            coefficient: 5.0,
+            power_list: exponents![2],
+        };
+        polynomial += Monomial {
+            coefficient: 0.0,
+            power_list: exponents![0, 0, 1],
+        };
+        assert!(polynomial.get(&exponents![2]).is_none());
+        assert!(polynomial.get(&exponents![0, 0, 1]).is_none());
+        let terms: Vec<&Monomial<f64>> = polynomial.iter().collect();
+        assert_eq!(terms.len(), 1);
+        assert_eq!(terms[0].power_list, exponents![0, 2]);
+    }
+
+    #[rstest]
+    fn test_round_trip(polynomial_a: HashPolynomial64) {
+        assert_eq!(polynomial_a.num_terms(), 2);
+        let polynomial: Polynomial64 = polynomial_a.into();
+        assert_eq!(format!("{}", polynomial), "5x^2 + 6y^2");
+        assert_eq!(polynomial.monomials[0].coefficient, 5.0);
+        assert_eq!(polynomial.monomials[1].coefficient, 6.0);
+    }
+
+    #[rstest]
+    fn test_addition(polynomial_a: HashPolynomial64) {
+        let other = HashPolynomial::from(Polynomial64::from("7y^2 - x^2 + z").unwrap());
+        let polynomial = polynomial_a + other;
+        assert_eq!(format!("{}", polynomial), "4x^2 + 13y^2 + z");
+    }
+
+    #[rstest]
+    fn test_subtraction(polynomial_a: HashPolynomial64) {
+        let other = HashPolynomial::from(Polynomial64::from("7y^2 + z").unwrap());
+        let polynomial = polynomial_a - other;
+        assert_eq!(format!("{}", polynomial), "5x^2 - y^2 - z");
+    }
+
+    #[rstest]
+    fn test_mul_agrees_with_polynomial() {
+        let lhs = Polynomial64::from("x^2 + 2xy - z + 3").unwrap();
+        let rhs = Polynomial64::from("x - y^3 + 4z").unwrap();
+        let expected = lhs.clone() * rhs.clone();
+
+        let product: HashPolynomial64 = lhs.convert::<HashPolynomial64>() * rhs.convert();
+        assert_eq!(format!("{}", product), format!("{}", expected));
+    }
+
+    #[rstest]
+    fn test_generic_algorithm_on_both() {
+        fn square<P: SparsePolynomial<f64>>(polynomial: &P) -> P {
+            polynomial.mul_poly(polynomial)
+        }
+        let polynomial = Polynomial64::from("x + y").unwrap();
+        let hash_polynomial = HashPolynomial64::from_polynomial(&polynomial);
+        assert_eq!(format!("{}", square(&polynomial)), "x^2 + 2xy + y^2");
+        assert_eq!(format!("{}", square(&hash_polynomial)), "x^2 + 2xy + y^2");
+    }
+}
@@ -1,61 +1,585 @@
-use crate::parser::{Parser, ParserErr};
+use crate::lexer::{greek_letter_name, Syntax};
+use crate::monomial::{variable_index, variable_symbol};
+use crate::parser::{Parser64, ParserErr};
+use crate::polynomial::Limits;
+use crate::render::FormatOptions;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor, Helper};
+use std::env;
 use std::io;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
 
+// Keeps a mistyped exponent from exhausting memory.
+const LIMITS: Limits = Limits {
+    max_degree: 100_000,
+    max_terms: 1_000_000,
+};
+
+// What expressions are read as and their values printed with, as the
+// commands set them.
+#[derive(Debug, Default)]
+struct Settings {
+    input: Syntax,
+    options: FormatOptions,
+}
+
+// Why a line failed.
+#[derive(Debug)]
+enum Failure {
+    Parser(Vec<ParserErr>),
+    Command(String),
+}
+
+// The commands, with the values their first argument takes. :group also
+// takes the variables.
+const COMMANDS: [(&str, &[&str]); 6] = [
+    ("input", &["standard", "latex", "cas"]),
+    (
+        "output",
+        &[
+            "ascii",
+            "unicode",
+            "latex",
+            "mathml",
+            "sympy",
+            "mathematica",
+            "maple",
+            "sage",
+        ],
+    ),
+    ("order", &["grlex", "lex", "grevlex"]),
+    ("precision", &["off"]),
+    ("scientific", &["on", "off"]),
+    ("group", &["off"]),
+];
+
+// Reads lines from the terminal with a prompt, printing the value of each,
+// until Ctrl-D. The line editor keeps a history, saved in ~/.poly_history,
+// completes commands and variables with Tab, and reads on past the end of a
+// line while a parenthesis is open. Ctrl-C drops the line being typed.
 pub fn run_interpreter() {
     println!("\x1B[36m    ______\n   //   //   ____   //   \\\\ //\n  //___//  //  //  //     \\\\/\n //       //__//  //__    //\n//                       //\x1B[0m");
+    let mut editor = match Editor::<InterpreterHelper, DefaultHistory>::new() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("\x1B[31m{}\x1B[0m", e);
+            return;
+        }
+    };
+    editor.set_helper(Some(InterpreterHelper::default()));
+    let history = env::var_os("HOME").map(|home| PathBuf::from(home).join(".poly_history"));
+    if let Some(path) = &history {
+        // there is none before the first session
+        let _ = editor.load_history(path);
+    }
+    let mut settings = Settings::default();
     loop {
-        print!("~> ");
-        io::stdout().flush().unwrap();
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-
-        let input_copy: String = input.clone();
-        let parser_res = Parser::parser_init(input);
-        match parser_res {
-            Ok(mut parser) => {
-                let res = parser.start_parser();
-                match res {
-                    Ok(v) => {
-                        println!("{}", v);
-                    }
-                    Err(e) => {
-                        handle_parser_error(input_copy, parser.lexer.curr_pos, e);
-                    }
-                }
-            }
+        let input = match editor.readline("~> ") {
+            Ok(v) => v,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
             Err(e) => {
-                handle_parser_error(input_copy, 0, e);
+                eprintln!("\x1B[31m{}\x1B[0m", e);
+                break;
             }
+        };
+        if !input.trim().is_empty() {
+            let _ = editor.add_history_entry(input.as_str());
+        }
+        // the lines of an expression are run as one, and the spaces keep
+        // the error spans matching input
+        match run_line(&input.replace('\n', " "), &mut settings, true) {
+            Ok(Some(text)) => println!("{}", text),
+            Ok(None) => {}
+            Err(failure) => report(&failure, &input, None),
+        }
+        // names complete as the input syntax spells them
+        if let Some(helper) = editor.helper_mut() {
+            helper.input = settings.input;
         }
     }
+    if let Some(path) = &history {
+        if let Err(e) = editor.save_history(path) {
+            eprintln!("poly: cannot save the history to {}: {}", path.display(), e);
+        }
+    }
+}
+
+// Completion and continued lines for the line editor.
+#[derive(Default)]
+struct InterpreterHelper {
+    input: Syntax,
 }
 
-pub fn handle_parser_error(offending_line: String, curr_pos: usize, parser_res: ParserErr) -> bool {
-    match parser_res {
-        ParserErr::ExpectedToken(msg) => {
-            print_syntax_error(offending_line, curr_pos, &msg);
+impl Helper for InterpreterHelper {}
+
+impl Completer for InterpreterHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(completions(&line[..pos], self.input))
+    }
+}
+
+impl Hinter for InterpreterHelper {
+    type Hint = String;
+}
+
+impl Highlighter for InterpreterHelper {}
+
+impl Validator for InterpreterHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_open(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
         }
-        ParserErr::UnexpectedToken(msg) => {
-            print_syntax_error(offending_line, curr_pos, &msg);
+    }
+}
+
+// The completions of the word ending the line, and where that word starts:
+// the names of commands, the values of their arguments, and in expressions
+// the variables, spelled as input reads them.
+fn completions(line: &str, input: Syntax) -> (usize, Vec<String>) {
+    if !line.trim_start().starts_with(':') {
+        return variable_completions(line, input);
+    }
+    let start = line.rfind(char::is_whitespace).map_or(0, |ind| ind + 1);
+    let word = &line[start..];
+    let words: Vec<&str> = line[..start].split_whitespace().collect();
+    let candidates: Vec<String> = match words.as_slice() {
+        [] if word.starts_with(':') => COMMANDS
+            .iter()
+            .map(|(name, _)| format!(":{}", name))
+            .collect(),
+        [command] => {
+            let name = command.strip_prefix(':').unwrap_or_default();
+            let values = COMMANDS
+                .iter()
+                .filter(|(command, _)| *command == name)
+                .flat_map(|(_, values)| values.iter().map(|value| String::from(*value)));
+            if name == "group" {
+                let variables = (0..)
+                    .map(variable_symbol)
+                    .take_while(|&symbol| symbol != "?")
+                    .map(String::from);
+                variables.chain(values).collect()
+            } else {
+                values.collect()
+            }
         }
-        ParserErr::LexerErr(msg) => {
-            print_syntax_error(offending_line, curr_pos, &msg);
+        [":order", _] => vec![String::from("ascending"), String::from("descending")],
+        _ => Vec::new(),
+    };
+    let candidates = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .collect();
+    (start, candidates)
+}
+
+// The variables whose symbol or, for a Greek letter, name begins with the
+// letters ending the line, such as α for al. As juxtaposed variables
+// multiply, the name being typed is the longest run of those letters that
+// begins one, so 2xal completes al. After a backslash in LaTeX, the letters
+// are a command, so \al completes to \alpha. In CAS input a run of letters
+// is a single name, spelled out, so al completes to alpha.
+fn variable_completions(line: &str, input: Syntax) -> (usize, Vec<String>) {
+    if input == Syntax::Cas {
+        let letters = line
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+            .len();
+        let prefix = &line[letters..];
+        let candidates = (0..)
+            .map(variable_symbol)
+            .take_while(|&symbol| symbol != "?")
+            .filter_map(|symbol| match symbol.chars().next()? {
+                letter if letter.is_ascii() => Some(symbol),
+                letter => greek_letter_name(letter),
+            })
+            .filter(|name| !prefix.is_empty() && name.starts_with(prefix))
+            .map(String::from)
+            .collect();
+        return (letters, candidates);
+    }
+    let letters = line.trim_end_matches(char::is_alphabetic).len();
+    if input == Syntax::Latex && line[..letters].ends_with('\\') {
+        let prefix = &line[letters..];
+        let candidates = (0..)
+            .map(variable_symbol)
+            .take_while(|&symbol| symbol != "?")
+            .filter_map(|symbol| greek_letter_name(symbol.chars().next()?))
+            .chain(LATEX_COMMANDS)
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| format!("\\{}", name))
+            .collect();
+        return (letters - 1, candidates);
+    }
+    for (start, _) in line[letters..].char_indices() {
+        let prefix = &line[letters + start..];
+        let candidates: Vec<String> = (0..)
+            .map(variable_symbol)
+            .take_while(|&symbol| symbol != "?")
+            .filter(|&symbol| {
+                let mut chars = symbol.chars();
+                let name = match (chars.next().and_then(greek_letter_name), chars.next()) {
+                    (Some(name), None) => name,
+                    _ => symbol,
+                };
+                symbol.starts_with(prefix) || name.starts_with(prefix)
+            })
+            .map(String::from)
+            .collect();
+        if !candidates.is_empty() {
+            return (letters + start, candidates);
         }
-        ParserErr::InvalidSyntax(msg) => {
-            print_syntax_error(offending_line, curr_pos, &msg);
+    }
+    (line.len(), Vec::new())
+}
+
+// The LaTeX commands other than the Greek letters that complete.
+const LATEX_COMMANDS: [&str; 3] = ["frac", "cdot", "times"];
+
+// Whether an expression opens more parentheses than it closes, so that it
+// goes on to the next line.
+fn is_open(input: &str) -> bool {
+    if input.trim_start().starts_with([':', '#']) {
+        return false;
+    }
+    let depth = input.chars().fold(0i64, |depth, c| match c {
+        '(' => depth + 1,
+        ')' => depth - 1,
+        _ => depth,
+    });
+    depth > 0
+}
+
+// Runs each line of source, read from path, printing the values to stdout
+// and the errors, located in path, to stderr. Lines after a failing one
+// still run. Returns whether every line ran.
+pub fn run_script(source: &str, path: &str) -> bool {
+    let mut settings = Settings::default();
+    let mut stdout = io::stdout().lock();
+    let mut succeeded = true;
+    for (ind, line) in source.lines().enumerate() {
+        match run_line(line, &mut settings, false) {
+            Ok(Some(text)) => {
+                // a closed pipe, as to head, ends the script
+                if writeln!(stdout, "{}", text).is_err() {
+                    return false;
+                }
+            }
+            Ok(None) => {}
+            Err(failure) => {
+                let _ = stdout.flush();
+                report(&failure, line, Some((path, ind + 1)));
+                succeeded = false;
+            }
         }
     }
-    false
+    succeeded
+}
+
+// Runs a line, an expression or a command, giving the text to print: the
+// value of an expression, or a setting. Commands show the setting they
+// change only when interactive, and always when asked for it. Blank lines
+// and comments, which start with '#', do nothing.
+fn run_line(
+    line: &str,
+    settings: &mut Settings,
+    interactive: bool,
+) -> Result<Option<String>, Failure> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return Ok(None);
+    }
+    if let Some(command) = trimmed.strip_prefix(':') {
+        let setting = run_command(command, settings).map_err(Failure::Command)?;
+        let is_query = command.split_whitespace().nth(1).is_none();
+        return Ok(Some(setting).filter(|_| interactive || is_query));
+    }
+    // leading whitespace stays, so that error spans match the line
+    let mut parser = Parser64::parser_init_with(String::from(line.trim_end()), settings.input)
+        .map_err(|e| Failure::Parser(vec![e]))?;
+    parser.limits = LIMITS;
+    match parser.parse_all() {
+        Ok(v) => Ok(Some(format!("{}", v.render_with(settings.options)))),
+        Err(errors) => Err(Failure::Parser(errors)),
+    }
 }
 
-fn print_syntax_error(offending_line: String, curr_pos: usize, msg: &str) {
-    print!("  {}", offending_line);
+// Prints the errors of a failed line: interactively to stdout, and from a
+// script to stderr with the file and line they are on. Headings are red on
+// a terminal.
+fn report(failure: &Failure, line: &str, location: Option<(&str, usize)>) {
+    let source = line.trim_end_matches(['\n', '\r']);
+    let rendered: Vec<String> = match (failure, location) {
+        (Failure::Parser(errors), None) => errors.iter().map(|e| e.render(source)).collect(),
+        (Failure::Parser(errors), Some((path, line_number))) => errors
+            .iter()
+            .map(|e| e.render_in(source, path, line_number))
+            .collect(),
+        (Failure::Command(e), None) => vec![format!("{}\n", e)],
+        (Failure::Command(e), Some((path, line_number))) => {
+            vec![format!("{}:{}: {}\n", path, line_number, e)]
+        }
+    };
+    let is_terminal = match location {
+        None => io::stdout().is_terminal(),
+        Some(_) => io::stderr().is_terminal(),
+    };
+    for text in rendered {
+        let (heading, body) = text.split_once('\n').unwrap_or((&text, ""));
+        let text = if is_terminal {
+            format!("\x1B[31m{}\x1B[0m\n{}", heading, body)
+        } else {
+            format!("{}\n{}", heading, body)
+        };
+        match location {
+            None => print!("{}", text),
+            Some(_) => eprint!("{}", text),
+        }
+    }
     io::stdout().flush().unwrap();
-    if curr_pos > 0 {
-        println!("  {: <1$}^", "", curr_pos - 1);
-    } else {
-        println!("  {: <1$}^", "", curr_pos);
+}
+
+// Commands start with ':'. Each sets how expressions are read or one of the
+// options results are printed with, or shows it when given no value.
+//
+//   :input <syntax>               one of the names Syntax parses
+//   :output <notation>            one of the names Notation parses
+//   :order <order> [ascending]    one of the names TermOrder parses
+//   :precision <digits>|off       digits after the point
+//   :scientific on|off            numbers with an exponent
+//   :group <variable>|off         collect the terms by a main variable
+fn run_command(command: &str, settings: &mut Settings) -> Result<String, String> {
+    let Settings { input, options } = settings;
+    let mut words = command.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let values: Vec<&str> = words.collect();
+    let result = match (name, values.as_slice()) {
+        ("input", []) | ("output", []) | ("order", []) | ("precision", []) | ("scientific", [])
+        | ("group", []) => Ok(()),
+        ("input", [syntax]) => syntax.parse().map(|v| *input = v),
+        ("output", [notation]) => notation.parse().map(|v| options.notation = v),
+        ("order", [order]) | ("order", [order, "descending"]) => order.parse().map(|v| {
+            options.order = v;
+            options.ascending = false;
+        }),
+        ("order", [order, "ascending"]) => order.parse().map(|v| {
+            options.order = v;
+            options.ascending = true;
+        }),
+        ("precision", ["off"]) => {
+            options.precision = None;
+            Ok(())
+        }
+        ("precision", [digits]) => match digits.parse() {
+            Ok(v) => {
+                options.precision = Some(v);
+                Ok(())
+            }
+            Err(_) => Err(format!("Expected a number of digits or off, found {:?}", digits)),
+        },
+        ("scientific", ["on"]) | ("scientific", ["off"]) => {
+            options.scientific = values[0] == "on";
+            Ok(())
+        }
+        ("group", ["off"]) => {
+            options.group_by = None;
+            Ok(())
+        }
+        ("group", [variable]) => match variable_index(variable) {
+            Some(v) => {
+                options.group_by = Some(v);
+                Ok(())
+            }
+            None => Err(format!("Unknown variable {:?}", variable)),
+        },
+        _ => Err(format!(
+            "Unknown command :{}\n  commands are :input [standard|latex|cas], :output [ascii|unicode|latex|mathml|sympy|mathematica|maple|sage], :order [grlex|lex|grevlex] [ascending|descending], :precision [digits|off], :scientific [on|off], :group [variable|off]",
+            command
+        )),
+    };
+    result?;
+    Ok(match name {
+        "input" => format!("input is {}", input),
+        "output" => format!("output is {}", options.notation),
+        "order" if options.ascending => format!("order is {} ascending", options.order),
+        "order" => format!("order is {} descending", options.order),
+        "precision" => match options.precision {
+            Some(digits) => format!("precision is {} digits", digits),
+            None => String::from("precision is off"),
+        },
+        "scientific" if options.scientific => String::from("scientific is on"),
+        "scientific" => String::from("scientific is off"),
+        _ => match options.group_by {
+            Some(var) => format!("group is {}", variable_symbol(var)),
+            None => String::from("group is off"),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("(x + 1)^2", Some("x^2 + 2x + 1"))]
+    #[case("  0.1 + 0.2\n", Some("0.30000000000000004"))]
+    #[case("", None)]
+    #[case("\n", None)]
+    #[case("# a comment", None)]
+    fn test_run_line(#[case] line: &str, #[case] expected: Option<&str>) {
+        let mut settings = Settings::default();
+        assert_eq!(
+            run_line(line, &mut settings, false).unwrap(),
+            expected.map(String::from)
+        );
+    }
+
+    #[rstest]
+    fn test_run_line_commands() {
+        let mut settings = Settings::default();
+        assert_eq!(
+            run_line(":precision 2", &mut settings, true).unwrap(),
+            Some(String::from("precision is 2 digits"))
+        );
+        assert_eq!(run_line(":group y", &mut settings, false).unwrap(), None);
+        assert_eq!(
+            run_line(":group", &mut settings, false).unwrap(),
+            Some(String::from("group is y"))
+        );
+        assert_eq!(
+            run_line("x^2y + x + 0.5y", &mut settings, false).unwrap(),
+            Some(String::from("(x^2 + 0.50)y + x"))
+        );
+    }
+
+    #[rstest]
+    fn test_run_line_input() {
+        let mut settings = Settings::default();
+        assert_eq!(
+            run_line(":input", &mut settings, false).unwrap(),
+            Some(String::from("input is standard"))
+        );
+        assert_eq!(
+            run_line(":input LaTeX", &mut settings, false).unwrap(),
+            None
+        );
+        assert_eq!(
+            run_line("\\frac{x}{2} + \\alpha", &mut settings, false).unwrap(),
+            Some(String::from("0.5x + α"))
+        );
+        assert!(run_line(":input klingon", &mut settings, false).is_err());
+        assert_eq!(settings.input, Syntax::Latex);
+        assert_eq!(run_line(":input cas", &mut settings, false).unwrap(), None);
+        assert_eq!(
+            run_line("x**2 - 2*alpha*x + lambda_", &mut settings, false).unwrap(),
+            Some(String::from("x^2 - 2xα + λ"))
+        );
+        assert_eq!(
+            run_line(":input", &mut settings, false).unwrap(),
+            Some(String::from("input is cas"))
+        );
+    }
+
+    #[rstest]
+    #[case("(x + 1")]
+    #[case("x^-1")]
+    #[case(":bogus")]
+    #[case(":precision many")]
+    #[case(":group q")]
+    #[case(":input cobol")]
+    #[case("\\frac{x}{2}")]
+    fn test_run_line_failure(#[case] line: &str) {
+        let mut settings = Settings::default();
+        assert!(run_line(line, &mut settings, false).is_err());
+    }
+
+    #[rstest]
+    #[case(":o", 0, vec![":output", ":order"])]
+    #[case(":input ", 7, vec!["standard", "latex", "cas"])]
+    #[case("  :sc", 2, vec![":scientific"])]
+    #[case(":output la", 8, vec!["latex"])]
+    #[case(":order lex a", 11, vec!["ascending"])]
+    #[case(":scientific ", 12, vec!["on", "off"])]
+    #[case(":group ω", 7, vec!["ω"])]
+    #[case(":group o", 7, vec!["off"])]
+    #[case("x + ", 4, vec![])]
+    #[case(":bogus ", 7, vec![])]
+    #[case("x + al", 4, vec!["α"])]
+    #[case("2xal", 2, vec!["α"])]
+    #[case("α + be", 5, vec!["β"])]
+    #[case("(x + 1)e", 7, vec!["ε", "η"])]
+    #[case("3p", 1, vec!["π", "φ", "ψ"])]
+    #[case("y^2 x", 4, vec!["x", "ξ"])]
+    #[case("y + q", 5, vec![])]
+    fn test_completions(#[case] line: &str, #[case] start: usize, #[case] expected: Vec<&str>) {
+        assert_eq!(
+            completions(line, Syntax::Standard),
+            (start, expected.into_iter().map(String::from).collect())
+        );
+    }
+
+    #[rstest]
+    #[case("x + \\al", 4, vec!["\\alpha"])]
+    #[case("\\fr", 0, vec!["\\frac"])]
+    #[case("2\\t", 1, vec!["\\theta", "\\tau", "\\times"])]
+    #[case("x + al", 4, vec!["α"])]
+    #[case("\\q", 0, vec![])]
+    fn test_completions_latex(
+        #[case] line: &str,
+        #[case] start: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        assert_eq!(
+            completions(line, Syntax::Latex),
+            (start, expected.into_iter().map(String::from).collect())
+        );
+    }
+
+    #[rstest]
+    #[case("x + al", 4, vec!["alpha"])]
+    #[case("2*x", 2, vec!["x", "xi"])]
+    #[case("(x + 1)*e", 8, vec!["epsilon", "eta"])]
+    #[case("x**2 + p", 7, vec!["pi", "phi", "psi"])]
+    #[case("y + q", 4, vec![])]
+    #[case("x + ", 4, vec![])]
+    fn test_completions_cas(#[case] line: &str, #[case] start: usize, #[case] expected: Vec<&str>) {
+        assert_eq!(
+            completions(line, Syntax::Cas),
+            (start, expected.into_iter().map(String::from).collect())
+        );
+    }
+
+    #[rstest]
+    #[case("(x + 1", true)]
+    #[case("((x + 1)\n * (y", true)]
+    #[case("(x + 1)^2", false)]
+    #[case("x + 1)", false)]
+    #[case("# (a comment", false)]
+    #[case(":group (", false)]
+    fn test_is_open(#[case] input: &str, #[case] expected: bool) {
+        assert_eq!(is_open(input), expected);
+    }
+
+    #[rstest]
+    #[case("x + 1\n:output latex\n\n# done\n", true)]
+    #[case("x + 1\n(x + 1\nx - 1\n", false)]
+    fn test_run_script(#[case] source: &str, #[case] succeeded: bool) {
+        assert_eq!(run_script(source, "test.poly"), succeeded);
     }
-    println!("\x1B[31mSyntaxError: {}\x1B[0m", msg);
 }
@@ -1,4 +1,7 @@
+use crate::diagnostic::Span;
 use log::{debug, error};
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum TokType {
@@ -16,19 +19,96 @@ pub enum TokType {
     Number,
     Period,
     Xvar,
+    Imaginary,
     Caret,
+    // an exponent written in superscript digits, such as the ² of x²
+    Superscript,
+    Frac,
     Identifier,
     UnknownToken,
     End,
 }
 
+impl TokType {
+    // How the token is referred to in diagnostics.
+    pub fn describe(&self) -> String {
+        let text = match self {
+            TokType::Exit => "exit",
+            TokType::Newl => "newline",
+            TokType::Mul => "'*'",
+            TokType::Div => "'/'",
+            TokType::Plus => "'+'",
+            TokType::PlusEq => "'+='",
+            TokType::Minus => "'-'",
+            TokType::MinusEq => "'-='",
+            TokType::Lpar => "'('",
+            TokType::Rpar => "')'",
+            TokType::Equal => "'='",
+            TokType::Number => "number",
+            TokType::Period => "'.'",
+            TokType::Xvar => "variable",
+            TokType::Imaginary => "imaginary unit",
+            TokType::Caret => "'^'",
+            TokType::Superscript => "superscript",
+            TokType::Frac => "'\\frac'",
+            TokType::Identifier => "identifier",
+            TokType::UnknownToken => "unknown token",
+            TokType::End => "end of input",
+        };
+        String::from(text)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub token_type: TokType,
     pub token_content: String,
+    pub span: Span,
+}
+
+// The notation the input is written in. LaTeX adds braces for grouping and
+// the commands \cdot, \times, \frac and the Greek letters, such as \alpha.
+// Cas reads the output of computer algebra systems such as SymPy, Mathematica,
+// Maple and Sage, adding ** for '^', I for i, *^ for the exponent of a number
+// as in 1.5*^3, and the Greek letters spelled out, such as alpha or lambda_.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Syntax {
+    #[default]
+    Standard,
+    Latex,
+    Cas,
+}
+
+impl FromStr for Syntax {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" => Ok(Syntax::Standard),
+            "latex" => Ok(Syntax::Latex),
+            "cas" => Ok(Syntax::Cas),
+            _ => {
+                let msg = format!("Unknown syntax {:?}, expected standard, latex or cas", s);
+                error!("{}", msg);
+                Err(msg)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Syntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Syntax::Standard => "standard",
+            Syntax::Latex => "latex",
+            Syntax::Cas => "cas",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 pub struct Lexer {
+    pub syntax: Syntax,
     pub current_line: Vec<char>,
     pub line_size: usize,
     pub curr_pos: usize,
@@ -42,18 +122,22 @@ pub enum LexerErr {
     InvalidCharPos,
     UnexpectedChar,
     UnknownChar,
+    UnknownCommand,
+    UnknownName,
     EndOfInput,
 }
 
 impl Lexer {
     pub fn lexer_init(current_line: String) -> Self {
         Lexer {
+            syntax: Syntax::Standard,
             current_line: current_line.clone().chars().collect(),
-            line_size: current_line.len(),
+            line_size: current_line.chars().count(),
             curr_pos: 0,
             curr_tok: Token {
                 token_type: TokType::End,
                 token_content: String::from(""),
+                span: Span::default(),
             },
             next_pos: 0,
             next_tok: None,
@@ -119,6 +203,7 @@ impl Lexer {
 
         if self.curr_pos == self.line_size {
             self.curr_tok.token_type = TokType::End;
+            self.curr_tok.span = Span::new(self.line_size, self.line_size);
             debug!("No more input, returning tok {:?}", TokType::End);
             return Ok(());
         }
@@ -130,16 +215,50 @@ impl Lexer {
 
         if self.curr_pos == self.line_size {
             self.curr_tok.token_type = TokType::End;
+            self.curr_tok.span = Span::new(self.line_size, self.line_size);
             debug!("No more input, returning tok {:?}", TokType::End);
             return Ok(());
         }
+        let start = self.curr_pos;
 
         match ch {
             '\n' => self.curr_tok.token_type = TokType::Newl,
-            '*' => self.curr_tok.token_type = TokType::Mul,
+            '*' if self.syntax == Syntax::Cas && self.current_line.get(start + 1) == Some(&'*') => {
+                self.curr_pos += 1;
+                self.curr_tok.token_type = TokType::Caret;
+            }
+            '*' | '·' | '⋅' | '×' => self.curr_tok.token_type = TokType::Mul,
+            'I' if self.syntax == Syntax::Cas && !is_letter(&self.current_line, start + 1) => {
+                self.curr_tok.token_type = TokType::Imaginary;
+            }
+            'A'..='Z' | 'a'..='z'
+                if self.syntax == Syntax::Cas
+                    && (ch.is_ascii_uppercase() || is_letter(&self.current_line, start + 1)) =>
+            {
+                let end = start
+                    + self.current_line[start..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_alphabetic())
+                        .count();
+                let name: String = self.current_line[start..end].iter().collect();
+                // a name the system reserves is written with a trailing '_'
+                let end = end + usize::from(self.current_line.get(end) == Some(&'_'));
+                match greek_letter(&name) {
+                    Some(letter) => {
+                        self.curr_tok.token_type = TokType::Xvar;
+                        self.curr_tok.token_content = String::from(letter);
+                    }
+                    None => {
+                        error!("Unknown name: {}", name);
+                        return Err(LexerErr::UnknownName);
+                    }
+                }
+                self.curr_pos = end - 1;
+                ch = self.current_line[self.curr_pos];
+            }
             '/' => self.curr_tok.token_type = TokType::Div,
             '+' => self.curr_tok.token_type = TokType::Plus,
-            '-' => self.curr_tok.token_type = TokType::Minus,
+            '-' | '−' => self.curr_tok.token_type = TokType::Minus,
             '(' => self.curr_tok.token_type = TokType::Lpar,
             ')' => self.curr_tok.token_type = TokType::Rpar,
             '=' => self.curr_tok.token_type = TokType::Equal,
@@ -147,26 +266,94 @@ impl Lexer {
                 self.curr_tok.token_type = TokType::Xvar;
                 self.curr_tok.token_content = String::from(ch);
             }
+            // omicron and final sigma are left out as they read as o and s
+            'α'..='ω' if ch != 'ο' && ch != 'ς' => {
+                self.curr_tok.token_type = TokType::Xvar;
+                self.curr_tok.token_content = String::from(ch);
+            }
+            'i' => self.curr_tok.token_type = TokType::Imaginary,
             '^' => self.curr_tok.token_type = TokType::Caret,
-            '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                let mut number = String::from("");
-                while ch.is_digit(10) {
-                    number.push(ch);
-                    ch = self.march_pos()?;
-                }
-                if ch == '.' {
-                    number.push(ch);
-                    ch = self.march_pos()?;
-                    while ch.is_digit(10) {
-                        number.push(ch);
-                        ch = self.march_pos()?;
+            '0'..='9' | '.' if is_digit(&self.current_line, start + usize::from(ch == '.')) => {
+                let (mut number, mut end) = match read_number(&self.current_line, start) {
+                    Ok(v) => v,
+                    Err(pos) => {
+                        error!("Misplaced digit separator at {}", pos);
+                        self.curr_pos = pos;
+                        return Err(LexerErr::UnexpectedChar);
+                    }
+                };
+                // Mathematica writes 1.5e3 as 1.5*^3
+                if self.syntax == Syntax::Cas && self.current_line[end..].starts_with(&['*', '^']) {
+                    let sign = matches!(self.current_line.get(end + 2), Some('+' | '-'));
+                    let digits = end + 2 + usize::from(sign);
+                    if is_digit(&self.current_line, digits) && !number.contains('e') {
+                        number.push('e');
+                        if sign {
+                            number.push(self.current_line[end + 2]);
+                        }
+                        end = digits;
+                        if let Err(pos) = read_digits(&self.current_line, &mut end, &mut number) {
+                            error!("Misplaced digit separator at {}", pos);
+                            self.curr_pos = pos;
+                            return Err(LexerErr::UnexpectedChar);
+                        }
                     }
                 }
-                ch = self.unmarch_pos()?; // went too far in last loop
+                self.curr_pos = end - 1;
+                ch = self.current_line[self.curr_pos];
                 self.curr_tok.token_type = TokType::Number;
                 self.curr_tok.token_content = number;
             }
             '.' => self.curr_tok.token_type = TokType::Period,
+            '⁰' | '¹' | '²' | '³' | '⁴'..='⁹' | '⁻'
+                if read_superscript(&self.current_line, start).is_some() =>
+            {
+                let (exponent, end) = read_superscript(&self.current_line, start).unwrap();
+                self.curr_pos = end - 1;
+                ch = self.current_line[self.curr_pos];
+                self.curr_tok.token_type = TokType::Superscript;
+                self.curr_tok.token_content = exponent;
+            }
+            '{' if self.syntax == Syntax::Latex => self.curr_tok.token_type = TokType::Lpar,
+            '}' if self.syntax == Syntax::Latex => self.curr_tok.token_type = TokType::Rpar,
+            '\\' if self.syntax == Syntax::Latex => {
+                let end = start
+                    + 1
+                    + self.current_line[start + 1..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_alphabetic())
+                        .count();
+                let command: String = self.current_line[start + 1..end].iter().collect();
+                match command.as_str() {
+                    "cdot" | "times" => self.curr_tok.token_type = TokType::Mul,
+                    "frac" | "dfrac" | "tfrac" => self.curr_tok.token_type = TokType::Frac,
+                    // sizing and spacing only change how the input looks
+                    "left" | "right" => {
+                        self.curr_pos = end;
+                        return self.get_next_token();
+                    }
+                    "" if matches!(
+                        self.current_line.get(end),
+                        Some(',' | ';' | ':' | '!' | ' ')
+                    ) =>
+                    {
+                        self.curr_pos = end + 1;
+                        return self.get_next_token();
+                    }
+                    _ => match greek_letter(&command) {
+                        Some(letter) => {
+                            self.curr_tok.token_type = TokType::Xvar;
+                            self.curr_tok.token_content = String::from(letter);
+                        }
+                        None => {
+                            error!("Unknown command: \\{}", command);
+                            return Err(LexerErr::UnknownCommand);
+                        }
+                    },
+                }
+                self.curr_pos = end - 1;
+                ch = self.current_line[self.curr_pos];
+            }
             _ => {
                 error!("Unknown character: {} ", ch);
                 return Err(LexerErr::UnknownChar);
@@ -178,10 +365,151 @@ impl Lexer {
         );
 
         self.march_pos()?;
+        self.curr_tok.span = Span::new(start, self.curr_pos);
         Ok(())
     }
 }
 
+// Reads the exponent written in superscript digits starting at start, with
+// an optional superscript minus. Returns the exponent in ASCII and the
+// position just past it, or None if there are no digits.
+fn read_superscript(line: &[char], start: usize) -> Option<(String, usize)> {
+    let mut exponent = String::new();
+    let mut pos = start;
+    if line.get(pos) == Some(&'⁻') {
+        exponent.push('-');
+        pos += 1;
+    }
+    let digits = pos;
+    while let Some(digit) = line.get(pos).and_then(|&c| superscript_digit(c)) {
+        exponent.push(digit);
+        pos += 1;
+    }
+    if pos == digits {
+        None
+    } else {
+        Some((exponent, pos))
+    }
+}
+
+fn superscript_digit(ch: char) -> Option<char> {
+    match ch {
+        '⁰' => Some('0'),
+        '¹' => Some('1'),
+        '²' => Some('2'),
+        '³' => Some('3'),
+        '⁴'..='⁹' => char::from_digit(ch as u32 - '⁴' as u32 + 4, 10),
+        _ => None,
+    }
+}
+
+// The names of the Greek letters used as variables, which are also their
+// LaTeX commands.
+const GREEK_LETTERS: [(&str, char); 23] = [
+    ("alpha", 'α'),
+    ("beta", 'β'),
+    ("gamma", 'γ'),
+    ("delta", 'δ'),
+    ("epsilon", 'ε'),
+    ("zeta", 'ζ'),
+    ("eta", 'η'),
+    ("theta", 'θ'),
+    ("iota", 'ι'),
+    ("kappa", 'κ'),
+    ("lambda", 'λ'),
+    ("mu", 'μ'),
+    ("nu", 'ν'),
+    ("xi", 'ξ'),
+    ("pi", 'π'),
+    ("rho", 'ρ'),
+    ("sigma", 'σ'),
+    ("tau", 'τ'),
+    ("upsilon", 'υ'),
+    ("phi", 'φ'),
+    ("chi", 'χ'),
+    ("psi", 'ψ'),
+    ("omega", 'ω'),
+];
+
+// The Greek letter with the name, as in LaTeX, such as α for alpha.
+fn greek_letter(command: &str) -> Option<char> {
+    let command = match command {
+        "varepsilon" => "epsilon",
+        "vartheta" => "theta",
+        "varphi" => "phi",
+        _ => command,
+    };
+    GREEK_LETTERS
+        .iter()
+        .find(|&&(name, _)| name == command)
+        .map(|&(_, letter)| letter)
+}
+
+// The name of a Greek letter, as in LaTeX, such as alpha for α.
+pub fn greek_letter_name(letter: char) -> Option<&'static str> {
+    GREEK_LETTERS
+        .iter()
+        .find(|&&(_, c)| c == letter)
+        .map(|&(name, _)| name)
+}
+
+fn is_letter(line: &[char], pos: usize) -> bool {
+    line.get(pos).is_some_and(|c| c.is_ascii_alphabetic())
+}
+
+fn is_digit(line: &[char], pos: usize) -> bool {
+    line.get(pos).is_some_and(|c| c.is_ascii_digit())
+}
+
+// Reads the numeric literal starting at start, one of
+//
+//   1_000   2.5   .5   3.   1e-6   2.5E+3
+//
+// where '_' separates digits. A fraction such as 3/4 is two literals, left to
+// the parser so that 3/2^2 is 3/(2^2). Returns the literal without separators
+// and the position just past it, or the position of a separator that is not
+// between two digits.
+fn read_number(line: &[char], start: usize) -> Result<(String, usize), usize> {
+    let mut number = String::new();
+    let mut pos = start;
+    read_digits(line, &mut pos, &mut number)?;
+    if line.get(pos) == Some(&'.') {
+        number.push('.');
+        pos += 1;
+        read_digits(line, &mut pos, &mut number)?;
+    }
+    // an e without digits after it is not part of the number
+    if matches!(line.get(pos), Some('e' | 'E')) {
+        let sign = matches!(line.get(pos + 1), Some('+' | '-'));
+        let digits = pos + 1 + usize::from(sign);
+        if is_digit(line, digits) {
+            number.push('e');
+            if sign {
+                number.push(line[pos + 1]);
+            }
+            pos = digits;
+            read_digits(line, &mut pos, &mut number)?;
+        }
+    }
+    Ok((number, pos))
+}
+
+fn read_digits(line: &[char], pos: &mut usize, number: &mut String) -> Result<(), usize> {
+    while let Some(&c) = line.get(*pos) {
+        if c.is_ascii_digit() {
+            number.push(c);
+        } else if c == '_' {
+            if *pos == 0 || !is_digit(line, *pos - 1) || !is_digit(line, *pos + 1) {
+                return Err(*pos);
+            }
+        } else {
+            break;
+        }
+        *pos += 1;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -254,6 +582,41 @@ mod tests {
         assert_eq!(token.token_content.as_str(), "123");
     }
 
+    #[rstest]
+    fn test_lexer_tokenize_big_int() {
+        let literal = "123456789012345678901234567890123456789";
+        let mut lexer = Lexer::lexer_init(format!("{}x\n", literal));
+
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Number);
+        assert_eq!(lexer.curr_tok.token_content.as_str(), literal);
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Xvar);
+    }
+
+    #[rstest]
+    fn test_token_spans() {
+        let mut lexer = Lexer::lexer_init(String::from("  12.5x ^ 3"));
+        let mut spans = Vec::new();
+        loop {
+            lexer.get_next_token().unwrap();
+            spans.push(lexer.curr_tok.span);
+            if lexer.curr_tok.token_type == TokType::End {
+                break;
+            }
+        }
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(2, 6),
+                Span::new(6, 7),
+                Span::new(8, 9),
+                Span::new(10, 11),
+                Span::new(11, 11),
+            ]
+        );
+    }
+
     #[rstest]
     fn test_lexer_peeking() {
         let string = String::from("x^3 + y\n");
@@ -315,4 +678,126 @@ mod tests {
         assert_eq!(lexer.curr_tok.token_type, TokType::Caret);
         assert_eq!(lexer.curr_pos, 2);
     }
+
+    #[rstest]
+    #[case("1_000_000", "1000000", 9)]
+    #[case(".5", ".5", 2)]
+    #[case("3.", "3.", 2)]
+    #[case("1e-6", "1e-6", 4)]
+    #[case("2.5E+3", "2.5e+3", 6)]
+    #[case("6.02e23", "6.02e23", 7)]
+    #[case("3/4", "3", 1)]
+    #[case("1_0/2_5", "10", 3)]
+    #[case("2e", "2", 1)]
+    #[case("2e+", "2", 1)]
+    #[case("1.5/2", "1.5", 3)]
+    #[case("3/x", "3", 1)]
+    fn test_lexer_numeric_literals(#[case] input: &str, #[case] content: &str, #[case] end: usize) {
+        let mut lexer = Lexer::lexer_init(String::from(input));
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Number);
+        assert_eq!(lexer.curr_tok.token_content, content);
+        assert_eq!(lexer.curr_tok.span, Span::new(0, end));
+    }
+
+    #[rstest]
+    fn test_lexer_fraction_is_division() {
+        let mut lexer = Lexer::lexer_init(String::from("3/2^2"));
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_content, "3");
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Div);
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_content, "2");
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Caret);
+    }
+
+    #[rstest]
+    fn test_lexer_imaginary_unit() {
+        let mut lexer = Lexer::lexer_init(String::from("2.5iy"));
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_content, "2.5");
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Imaginary);
+        assert_eq!(lexer.curr_tok.span, Span::new(3, 4));
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Xvar);
+    }
+
+    #[rstest]
+    #[case("x²", vec![TokType::Xvar, TokType::Superscript])]
+    #[case("2·x×y", vec![TokType::Number, TokType::Mul, TokType::Xvar, TokType::Mul, TokType::Xvar])]
+    #[case("α−β", vec![TokType::Xvar, TokType::Minus, TokType::Xvar])]
+    fn test_lexer_unicode(#[case] input: &str, #[case] expected: Vec<TokType>) {
+        let mut lexer = Lexer::lexer_init(String::from(input));
+        for token_type in expected {
+            lexer.get_next_token().unwrap();
+            assert_eq!(lexer.curr_tok.token_type, token_type);
+        }
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::End);
+    }
+
+    #[rstest]
+    #[case("x¹⁰", "10", Span::new(1, 3))]
+    #[case("x⁻²³", "-23", Span::new(1, 4))]
+    fn test_lexer_superscript(#[case] input: &str, #[case] exponent: &str, #[case] span: Span) {
+        let mut lexer = Lexer::lexer_init(String::from(input));
+        lexer.get_next_token().unwrap();
+        lexer.get_next_token().unwrap();
+        assert_eq!(lexer.curr_tok.token_type, TokType::Superscript);
+        assert_eq!(lexer.curr_tok.token_content, exponent);
+        assert_eq!(lexer.curr_tok.span, span);
+    }
+
+    #[rstest]
+    fn test_lexer_lone_superscript_minus() {
+        let mut lexer = Lexer::lexer_init(String::from("x⁻"));
+        lexer.get_next_token().unwrap();
+        assert!(matches!(lexer.get_next_token(), Err(LexerErr::UnknownChar)));
+    }
+
+    #[rstest]
+    fn test_lexer_latex_commands() {
+        let mut lexer = Lexer::lexer_init(String::from(
+            "\\frac{\\alpha}{2} \\cdot \\left( x \\right) \\sqrt",
+        ));
+        lexer.syntax = Syntax::Latex;
+        let expected = [
+            TokType::Frac,
+            TokType::Lpar,
+            TokType::Xvar,
+            TokType::Rpar,
+            TokType::Lpar,
+            TokType::Number,
+            TokType::Rpar,
+            TokType::Mul,
+            TokType::Lpar,
+            TokType::Xvar,
+            TokType::Rpar,
+        ];
+        for token_type in expected {
+            lexer.get_next_token().unwrap();
+            assert_eq!(lexer.curr_tok.token_type, token_type);
+        }
+        assert!(matches!(
+            lexer.get_next_token(),
+            Err(LexerErr::UnknownCommand)
+        ));
+    }
+
+    #[rstest]
+    #[case("1__0", 1)]
+    #[case("1_", 1)]
+    #[case("2._5", 2)]
+    #[case("1e5_", 3)]
+    fn test_lexer_misplaced_separator(#[case] input: &str, #[case] pos: usize) {
+        let mut lexer = Lexer::lexer_init(String::from(input));
+        assert!(matches!(
+            lexer.get_next_token(),
+            Err(LexerErr::UnexpectedChar)
+        ));
+        assert_eq!(lexer.curr_pos, pos);
+    }
 }
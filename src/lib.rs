@@ -1,8 +1,21 @@
+pub mod algebra;
+pub mod ast;
+pub mod dense;
+pub mod diagnostic;
+pub mod encoding;
+pub mod exponents;
+pub mod hash_polynomial;
 pub mod interpreter;
 mod lexer;
+pub mod modint;
 pub mod monomial;
 pub mod parser;
 pub mod polynomial;
+pub mod render;
+#[cfg(feature = "serde")]
+mod serialize;
+
+pub use algebra::{EuclideanDomain, Field, GCDDomain, IntegralDomain, OrderedRing};
 
 use num::{One, Zero};
 use std::ops::{Add, Mul, Sub};
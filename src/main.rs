@@ -1,12 +1,88 @@
 extern crate log;
 extern crate poly;
 
-use num::complex::Complex;
-use poly::interpreter::run_interpreter;
-use poly::monomial::Monomial;
-use poly::polynomial::Polynomial;
-
-fn main() {
-    env_logger::init();
-    run_interpreter();
+use poly::interpreter::{run_interpreter, run_script};
+use std::io::{self, IsTerminal, Read};
+use std::process::ExitCode;
+use std::{env, fs};
+
+const USAGE: &str = "usage: poly [FILE | -e EXPR...]
+
+Runs each line of FILE, or of stdin when FILE is - or missing, printing the
+value of each expression. Reading from a terminal starts the interpreter.
+
+  -e EXPR     run EXPR as a line, in order with any other -e
+  -h, --help  print this message
+
+Exits with 1 when a line fails, and 2 when the arguments are wrong or FILE
+cannot be read.";
+
+// What the arguments ask to run.
+enum Input {
+    Stdin,
+    File(String),
+    Lines(Vec<String>),
+    Help,
+}
+
+fn parse_args(args: &[String]) -> Result<Input, String> {
+    match args {
+        [] => return Ok(Input::Stdin),
+        [flag] if flag == "-h" || flag == "--help" => return Ok(Input::Help),
+        [path] if path == "-" => return Ok(Input::Stdin),
+        [path] if !path.starts_with('-') => return Ok(Input::File(path.clone())),
+        _ => {}
+    }
+    let mut lines = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("-e", Some(line)) => lines.push(line.clone()),
+            ("-e", None) => return Err(String::from("-e needs an expression")),
+            _ => return Err(format!("Unexpected argument {:?}", arg)),
+        }
+    }
+    Ok(Input::Lines(lines))
+}
+
+fn main() -> ExitCode {
+    // failures are reported as diagnostics, so the library's logs are only
+    // written when RUST_LOG asks for them, keeping stderr to one report
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("off")).init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (source, path) = match parse_args(&args) {
+        Ok(Input::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Input::Stdin) if args.is_empty() && io::stdin().is_terminal() => {
+            run_interpreter();
+            return ExitCode::SUCCESS;
+        }
+        Ok(Input::Stdin) => {
+            let mut source = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut source) {
+                eprintln!("poly: cannot read stdin: {}", e);
+                return ExitCode::from(2);
+            }
+            (source, String::from("<stdin>"))
+        }
+        Ok(Input::File(path)) => match fs::read_to_string(&path) {
+            Ok(source) => (source, path),
+            Err(e) => {
+                eprintln!("poly: cannot read {}: {}", path, e);
+                return ExitCode::from(2);
+            }
+        },
+        Ok(Input::Lines(lines)) => (lines.join("\n"), String::from("-e")),
+        Err(e) => {
+            eprintln!("poly: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if run_script(&source, &path) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
@@ -0,0 +1,241 @@
+use std::fmt;
+use std::ops;
+
+use num::{One, Zero};
+
+use crate::{EuclideanDomain, Field, GCDDomain, IntegralDomain};
+
+// 119 * 2^23 + 1, which admits number theoretic transforms of any power of
+// two length up to 2^23.
+pub const NTT_PRIME: u64 = 998244353;
+
+pub type ModIntNtt = ModInt<NTT_PRIME>;
+
+// An element of the prime field Z/PZ, stored as its least non-negative
+// residue. P must be a prime below 2^63 so that sums fit in a u64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "u64", into = "u64")
+)]
+pub struct ModInt<const P: u64> {
+    pub value: u64,
+}
+
+impl<const P: u64> ModInt<P> {
+    pub fn new(value: u64) -> Self {
+        ModInt { value: value % P }
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        ModInt {
+            value: value.rem_euclid(P as i64) as u64,
+        }
+    }
+
+    pub fn pow(self, exponent: u64) -> Self {
+        let mut res = Self::one();
+        let mut base = self;
+        let mut ind = exponent;
+        while ind > 0 {
+            if ind & 1 == 1 {
+                res = res * base;
+            }
+            base = base * base;
+            ind >>= 1;
+        }
+        res
+    }
+
+    // Fermat's little theorem, a^(P - 2) = a^-1. None for zero.
+    pub fn inverse(self) -> Option<Self> {
+        if self.value == 0 {
+            return None;
+        }
+        Some(self.pow(P - 2))
+    }
+
+    // The smallest generator of the multiplicative group, found by checking
+    // g^((P - 1) / q) != 1 for every prime q dividing P - 1.
+    pub fn primitive_root() -> Self {
+        let mut factors = Vec::new();
+        let mut rest = P - 1;
+        let mut factor = 2;
+        while factor * factor <= rest {
+            if rest.is_multiple_of(factor) {
+                factors.push(factor);
+                while rest.is_multiple_of(factor) {
+                    rest /= factor;
+                }
+            }
+            factor += 1;
+        }
+        if rest > 1 {
+            factors.push(rest);
+        }
+
+        let mut candidate = 2;
+        loop {
+            let root = Self::new(candidate);
+            if factors
+                .iter()
+                .all(|&factor| !root.pow((P - 1) / factor).is_one())
+            {
+                return root;
+            }
+            candidate += 1;
+        }
+    }
+}
+
+// The residue of any u64, as when deserializing.
+impl<const P: u64> From<u64> for ModInt<P> {
+    fn from(value: u64) -> Self {
+        ModInt::new(value)
+    }
+}
+
+impl<const P: u64> From<ModInt<P>> for u64 {
+    fn from(value: ModInt<P>) -> Self {
+        value.value
+    }
+}
+
+impl<const P: u64> fmt::Display for ModInt<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<const P: u64> Zero for ModInt<P> {
+    fn zero() -> Self {
+        ModInt { value: 0 }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+}
+
+impl<const P: u64> One for ModInt<P> {
+    fn one() -> Self {
+        ModInt { value: 1 % P }
+    }
+}
+
+impl<const P: u64> ops::Add for ModInt<P> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        let sum = self.value + other.value;
+        ModInt {
+            value: if sum >= P { sum - P } else { sum },
+        }
+    }
+}
+
+impl<const P: u64> ops::Sub for ModInt<P> {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        ModInt {
+            value: if self.value >= other.value {
+                self.value - other.value
+            } else {
+                self.value + P - other.value
+            },
+        }
+    }
+}
+
+impl<const P: u64> ops::Mul for ModInt<P> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        ModInt {
+            value: ((self.value as u128 * other.value as u128) % P as u128) as u64,
+        }
+    }
+}
+
+// Panics on division by zero, like the primitive integer types.
+impl<const P: u64> ops::Div for ModInt<P> {
+    type Output = Self;
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse().expect("division by zero in ModInt")
+    }
+}
+
+impl<const P: u64> ops::Neg for ModInt<P> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::zero() - self
+    }
+}
+
+// Z/PZ is a field since P is prime.
+impl<const P: u64> IntegralDomain for ModInt<P> {}
+
+impl<const P: u64> GCDDomain for ModInt<P> {
+    fn gcd(&self, other: &Self) -> Self {
+        if self.is_zero() && other.is_zero() {
+            Self::zero()
+        } else {
+            Self::one()
+        }
+    }
+}
+
+impl<const P: u64> EuclideanDomain for ModInt<P> {
+    fn quo_rem(&self, other: &Self) -> (Self, Self) {
+        (*self / *other, Self::zero())
+    }
+}
+
+impl<const P: u64> Field for ModInt<P> {
+    fn inverse(&self) -> Option<Self> {
+        ModInt::inverse(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    type F7 = ModInt<7>;
+
+    #[rstest]
+    fn test_arithmetic() {
+        assert_eq!(F7::new(5) + F7::new(4), F7::new(2));
+        assert_eq!(F7::new(2) - F7::new(5), F7::new(4));
+        assert_eq!(F7::new(3) * F7::new(5), F7::new(1));
+        assert_eq!(-F7::new(3), F7::new(4));
+        assert_eq!(F7::from_i64(-1), F7::new(6));
+    }
+
+    #[rstest]
+    fn test_inverse() {
+        for value in 1..7 {
+            let element = F7::new(value);
+            assert_eq!(element * element.inverse().unwrap(), F7::one());
+        }
+        assert_eq!(F7::zero().inverse(), None);
+        assert_eq!(F7::new(6) / F7::new(3), F7::new(2));
+    }
+
+    #[rstest]
+    fn test_field() {
+        fn inverse<F: Field>(value: F) -> Option<F> {
+            value.inverse()
+        }
+        assert_eq!(inverse(F7::new(3)), Some(F7::new(5)));
+        assert_eq!(inverse(F7::zero()), None);
+        assert_eq!(F7::new(6).quo_rem(&F7::new(3)), (F7::new(2), F7::zero()));
+    }
+
+    #[rstest]
+    fn test_primitive_root() {
+        assert_eq!(F7::primitive_root(), F7::new(3));
+        assert_eq!(ModIntNtt::primitive_root(), ModIntNtt::new(3));
+    }
+}
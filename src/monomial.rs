@@ -1,4 +1,5 @@
-use crate::parser::{Parser, ParserErr};
+use crate::parser::{FromLiteral, Parser, ParserErr};
+use crate::render::{FormatCoefficient, FormatOptions, Notation, Rendered};
 use crate::CRing;
 
 use std::clone::Clone;
@@ -6,16 +7,23 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::ops;
 
+use crate::exponents::Exponents;
+use num::bigint::BigInt;
+use num::complex::Complex;
+use num::rational::BigRational;
 use num::One;
-use smallvec::{smallvec, SmallVec};
 
 pub type Monomial32 = Monomial<f32>;
 pub type Monomial64 = Monomial<f64>;
+pub type MonomialZ = Monomial<BigInt>;
+pub type MonomialQ = Monomial<BigRational>;
+pub type MonomialC = Monomial<Complex<f64>>;
+pub type MonomialCQ = Monomial<Complex<BigRational>>;
 
 #[derive(Debug)]
 pub struct Monomial<T: CRing> {
     pub coefficient: T,
-    pub power_list: SmallVec<[i32; 3]>,
+    pub power_list: Exponents,
 }
 
 impl<T> Monomial<T>
@@ -25,106 +33,239 @@ where
     pub fn new() -> Self {
         Monomial {
             coefficient: One::one(),
-            power_list: smallvec![0, 0, 0],
+            power_list: Exponents::new(),
         }
     }
+    // Terms of higher degree come first, ties broken by the power of x, then
+    // y, and so on.
     pub fn cmp_terms(&self, other: &Self) -> Ordering {
-        let degree_a: i32 = self.degree();
-        let degree_b: i32 = other.degree();
+        other.power_list.cmp(&self.power_list)
+    }
+}
 
-        if degree_a > degree_b {
-            return Ordering::Less;
-        }
+impl<T> Default for Monomial<T>
+where
+    T: CRing + PartialEq,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Monomial<T>
+where
+    T: CRing,
+{
+    pub fn power(&self, ind: usize) -> i32 {
+        self.power_list.get(ind)
+    }
+
+    pub fn degree(&self) -> i32 {
+        self.power_list.degree()
+    }
+}
 
-        if degree_a < degree_b {
-            return Ordering::Greater;
+impl<T> Monomial<T>
+where
+    T: CRing + Clone + PartialEq + FromLiteral,
+{
+    pub fn from(expr: &str) -> Result<Monomial<T>, ParserErr> {
+        let mut parser = Parser::<T>::parser_init(String::from(expr))?;
+        parser.parse_poly_expr()?.evaluate_monomial()
+    }
+}
+
+impl<T> Monomial<T>
+where
+    T: CRing + Clone,
+{
+    // Multiplies two terms without consuming them. Unlike Mul, the powers are
+    // kept even if the coefficient product vanishes, so the result still has
+    // a well defined position in the monomial ordering.
+    pub fn product(&self, other: &Monomial<T>) -> Monomial<T> {
+        Monomial {
+            coefficient: self.coefficient.clone() * other.coefficient.clone(),
+            power_list: &self.power_list + &other.power_list,
         }
+    }
 
-        let max_len = std::cmp::max(self.power_list.len(), other.power_list.len());
-        for ind in 0..max_len {
-            let power_a = self.power(ind);
-            let power_b = other.power(ind);
+    // Like Mul but None if an exponent or the degree overflows.
+    pub fn checked_mul(&self, other: &Monomial<T>) -> Option<Monomial<T>> {
+        let coefficient = self.coefficient.clone() * other.coefficient.clone();
+        let power_list = if coefficient.is_zero() {
+            Exponents::new()
+        } else {
+            self.power_list.checked_add(&other.power_list)?
+        };
+        Some(Monomial {
+            coefficient,
+            power_list,
+        })
+    }
 
-            if power_a < power_b {
-                return Ordering::Greater;
+    // Panics if an exponent or the degree overflows.
+    pub fn pow(&self, exponent: u32) -> Monomial<T> {
+        match self.checked_pow(exponent) {
+            Some(v) => v,
+            None => panic!("exponent overflow in ({})^{}", self.power_list, exponent),
+        }
+    }
+
+    pub fn checked_pow(&self, exponent: u32) -> Option<Monomial<T>> {
+        let power_list = self
+            .power_list
+            .checked_scale(i32::try_from(exponent).ok()?)?;
+        // square-and-multiply on the coefficient, scale the powers directly
+        let mut coefficient = T::one();
+        let mut base = self.coefficient.clone();
+        let mut ind = exponent;
+        while ind > 0 {
+            if ind & 1 == 1 {
+                coefficient = coefficient * base.clone();
             }
-            if power_a > power_b {
-                return Ordering::Less;
+            ind >>= 1;
+            if ind > 0 {
+                base = base.clone() * base;
             }
         }
-        Ordering::Equal
+        Some(Monomial {
+            coefficient,
+            power_list,
+        })
     }
 }
 
+// The variables, in the order of their exponents. The Greek letters are the
+// lowercase ones other than omicron and final sigma.
+const VARIABLES: [&str; 31] = [
+    "x", "y", "z", "u", "v", "w", "s", "t", "α", "β", "γ", "δ", "ε", "ζ", "η", "θ", "ι", "κ", "λ",
+    "μ", "ν", "ξ", "π", "ρ", "σ", "τ", "υ", "φ", "χ", "ψ", "ω",
+];
+
+pub fn variable_symbol(ind: usize) -> &'static str {
+    VARIABLES.get(ind).copied().unwrap_or("?")
+}
+
+pub fn variable_index(symbol: &str) -> Option<usize> {
+    VARIABLES.iter().position(|&v| v == symbol)
+}
+
+// Complex numbers print as re+imi, so 3i prints as 0+3i and 3 as 3+0i. Drops
+// the zero part and a unit imaginary coefficient, giving 3i, 3, 1-i. None if
+// the coefficient is not complex.
+fn complex_coefficient(coeff: &str) -> Option<String> {
+    let body = coeff.strip_suffix('i')?;
+    let split = body
+        .char_indices()
+        .skip(1)
+        .find(|&(_, c)| c == '+' || c == '-')?
+        .0;
+    let (re, im) = body.split_at(split);
+    let (sign, im) = im.split_at(1);
+    // f64 prints a negative zero imaginary part as +-0
+    let (sign, im) = match im.strip_prefix('-') {
+        Some(rest) => (if sign == "-" { "+" } else { "-" }, rest),
+        None => (sign, im),
+    };
+    let is_real = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '.' || c == '/')
+    };
+    if !is_real(re.strip_prefix('-').unwrap_or(re)) || !is_real(im) {
+        return None;
+    }
+    let is_zero = |part: &str| matches!(part, "0" | "-0");
+    let im = if im == "1" { "" } else { im };
+    Some(match (is_zero(re), is_zero(im)) {
+        (_, true) => String::from(re),
+        (true, false) if sign == "-" => format!("-{}i", im),
+        (true, false) => format!("{}i", im),
+        (false, false) => format!("{}{}{}i", re, sign, im),
+    })
+}
+
+// A sum or difference, as complex numbers and polynomials print, rather than
+// a number with an exponent such as 1e-5.
+fn is_compound(coeff: &str) -> bool {
+    coeff
+        .char_indices()
+        .any(|(ind, c)| matches!(c, '+' | '-') && !coeff[..ind].ends_with(['e', 'E']))
+}
+
 impl<T> Monomial<T>
 where
-    T: CRing,
+    T: FormatCoefficient + CRing + Clone + PartialEq,
 {
-    pub fn power(&self, ind: usize) -> i32 {
-        let power: i32;
-        match self.power_list.get(ind) {
-            Some(res) => power = *res,
-            None => power = 0,
-        }
-        power
+    // The sign of the coefficient, and its magnitude as written before the
+    // variables: empty for one, and parenthesized where it would otherwise
+    // run into them, as in (1+2i)x or -(9/4)x. text writes a coefficient.
+    pub(crate) fn signed_coefficient(&self, text: impl Fn(&T) -> String) -> (bool, String) {
+        let is_negative = self.coefficient.is_negative();
+        let magnitude = if is_negative {
+            T::zero() - self.coefficient.clone()
+        } else {
+            self.coefficient.clone()
+        };
+        let is_unit = magnitude.is_one();
+        let magnitude = text(&magnitude);
+        let magnitude = complex_coefficient(&magnitude).unwrap_or(magnitude);
+        let magnitude = if self.power_list.iter().all(|power| power == 0) {
+            // a complex constant with both parts reads as a sum
+            if magnitude.ends_with('i') && is_compound(&magnitude) {
+                format!("({})", magnitude)
+            } else {
+                magnitude
+            }
+        } else if is_unit {
+            String::new()
+        } else if is_compound(&magnitude) || magnitude.contains('/') {
+            // a compound coefficient, such as a polynomial, needs parentheses
+            // before the variables, as does a fraction
+            format!("({})", magnitude)
+        } else {
+            magnitude
+        };
+        (is_negative, magnitude)
     }
 
-    pub fn degree(&self) -> i32 {
-        self.power_list.iter().sum()
+    // The variables with their powers, leaving out those to the zeroth power.
+    pub(crate) fn variable_powers(&self) -> impl Iterator<Item = (&'static str, i32)> + '_ {
+        self.power_list
+            .iter()
+            .enumerate()
+            .filter(|&(_, power)| power != 0)
+            .map(|(ind, power)| (variable_symbol(ind), power))
+    }
+
+    pub fn render(&self, notation: Notation) -> Rendered<'_, T> {
+        Rendered::new(std::slice::from_ref(self), notation)
     }
 
-    pub fn from(expr: &str) -> Result<Monomial64, ParserErr> {
-        let mut parser = Parser::parser_init(String::from(expr))?;
-        let monomial = parser.parse_monomial()?;
-        Ok(monomial)
+    pub fn render_with(&self, options: FormatOptions) -> Rendered<'_, T> {
+        Rendered::with_options(std::slice::from_ref(self), options)
     }
 }
 
 impl<T> std::fmt::Display for Monomial<T>
 where
-    T: std::fmt::Display + CRing + PartialEq,
+    T: FormatCoefficient + CRing + Clone + PartialEq,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let coeff: String;
-        let mut term_expr = format!("");
-        for (ind, &power) in self.power_list.iter().enumerate() {
-            let symbol: &str;
-            let power_component: String;
-            if power == 0 {
-                continue;
-            }
-            match ind {
-                0 => symbol = "x",
-                1 => symbol = "y",
-                2 => symbol = "z",
-                3 => symbol = "u",
-                4 => symbol = "v",
-                5 => symbol = "w",
-                6 => symbol = "s",
-                7 => symbol = "t",
-                _ => symbol = "?",
-            }
-
-            if power != 1 {
-                power_component = format!("^{power}");
+        let (is_negative, magnitude) = self.signed_coefficient(|c| format!("{}", c));
+        if is_negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", magnitude)?;
+        for (symbol, power) in self.variable_powers() {
+            if power == 1 {
+                write!(f, "{}", symbol)?;
             } else {
-                power_component = format!("");
+                write!(f, "{}^{}", symbol, power)?;
             }
-
-            term_expr.push_str(&format!("{symbol}{power_component}"));
         }
-
-        if term_expr.is_empty() {
-            coeff = format!("{}", self.coefficient)
-        } else if format!("{}", self.coefficient) == "-1" {
-            coeff = format!("-");
-        } else if self.coefficient.is_one() {
-            coeff = format!("");
-        } else {
-            coeff = format!("{}", self.coefficient);
-        }
-
-        write!(f, "{}{}", coeff, term_expr)
+        Ok(())
     }
 }
 
@@ -160,105 +301,142 @@ impl<T> PartialEq for Monomial<T>
 where
     T: CRing + PartialEq,
 {
+    // Agrees with Ord: two monomials are equal when they are like terms.
     fn eq(&self, other: &Self) -> bool {
-        if self.coefficient != other.coefficient {
-            return false;
-        }
-        if self.power_list != other.power_list {
-            return false;
-        }
-        true
+        self.cmp_terms(other) == Ordering::Equal
     }
 }
 
 impl<T> Eq for Monomial<T> where T: CRing + PartialEq {}
 
-// Monomial* Monomial
+// Monomial * Monomial
 impl<T: CRing + Clone> ops::Mul for Monomial<T> {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
-        let coefficient = self.coefficient.clone() * other.coefficient.clone();
-        let mut power_list;
-        if coefficient.is_zero() {
-            power_list = smallvec![0; self.power_list.len().try_into().unwrap()];
-        } else {
-            let max_len = std::cmp::max(self.power_list.len(), other.power_list.len());
-            power_list = smallvec![0; max_len];
-            for ind in 0..max_len {
-                let power_a = self.power(ind);
-                let power_b = other.power(ind);
+        &self * &other
+    }
+}
 
-                power_list[ind] = power_a + power_b;
-            }
-        }
-        Monomial {
-            coefficient: coefficient,
-            power_list: power_list,
+// Monomial * &Monomial
+impl<T: CRing + Clone> ops::Mul<&Monomial<T>> for Monomial<T> {
+    type Output = Self;
+    fn mul(self, other: &Self) -> Self {
+        &self * other
+    }
+}
+
+// &Monomial * Monomial
+impl<T: CRing + Clone> ops::Mul<Monomial<T>> for &Monomial<T> {
+    type Output = Monomial<T>;
+    fn mul(self, other: Monomial<T>) -> Monomial<T> {
+        self * &other
+    }
+}
+
+// &Monomial * &Monomial
+impl<T: CRing + Clone> ops::Mul for &Monomial<T> {
+    type Output = Monomial<T>;
+    fn mul(self, other: Self) -> Monomial<T> {
+        let mut monomial = self.product(other);
+        if monomial.coefficient.is_zero() {
+            monomial.power_list = Exponents::new();
         }
+        monomial
     }
 }
 
-// Monomial* T
+// Monomial * T
 impl<T: CRing + Clone> ops::Mul<T> for Monomial<T> {
     type Output = Monomial<T>;
 
+    fn mul(mut self, other: T) -> Monomial<T> {
+        self.coefficient = other * self.coefficient;
+        self
+    }
+}
+
+// &Monomial * T
+impl<T: CRing + Clone> ops::Mul<T> for &Monomial<T> {
+    type Output = Monomial<T>;
+
     fn mul(self, other: T) -> Monomial<T> {
-        let mut monomial = self.clone();
-        monomial.coefficient = other * monomial.coefficient;
-        monomial
+        Monomial {
+            coefficient: other * self.coefficient.clone(),
+            power_list: self.power_list.clone(),
+        }
+    }
+}
+
+// -Monomial
+impl<T: CRing + Clone> ops::Neg for Monomial<T> {
+    type Output = Self;
+    fn neg(mut self) -> Self {
+        self.coefficient = T::zero() - self.coefficient;
+        self
+    }
+}
+
+// -&Monomial
+impl<T: CRing + Clone> ops::Neg for &Monomial<T> {
+    type Output = Monomial<T>;
+    fn neg(self) -> Monomial<T> {
+        Monomial {
+            coefficient: T::zero() - self.coefficient.clone(),
+            power_list: self.power_list.clone(),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exponents;
     use num::complex::Complex;
     use num::rational::Ratio;
     use rstest::*;
-    use smallvec::smallvec;
 
     #[fixture]
     fn monomial_a() -> Monomial64 {
-        return Monomial {
+        Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 1, 1],
-        };
+            power_list: exponents![1, 1, 1],
+        }
     }
 
     #[fixture]
     fn monomial_b() -> Monomial64 {
-        return Monomial {
+        Monomial {
             coefficient: 3.5,
-            power_list: smallvec![2, 1, 5],
-        };
+            power_list: exponents![2, 1, 5],
+        }
     }
 
     #[fixture]
     fn monomial_c() -> Monomial64 {
-        return Monomial {
+        Monomial {
             coefficient: 5.0,
-            power_list: smallvec![1, 2, 0],
-        };
+            power_list: exponents![1, 2, 0],
+        }
     }
 
     #[fixture]
     fn monomial_d() -> Monomial64 {
-        return Monomial {
+        Monomial {
             coefficient: 100.0,
-            power_list: smallvec![0, 2, 5],
-        };
+            power_list: exponents![0, 2, 5],
+        }
     }
 
     #[rstest]
     fn test_equality_i32() {
         let coefficient: i32 = 2;
         let monomial_a = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         let monomial_b = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         assert_eq!(monomial_a, monomial_b);
     }
@@ -267,12 +445,12 @@ mod tests {
     fn test_equality_i64() {
         let coefficient: i64 = 2;
         let monomial_a = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         let monomial_b = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         assert_eq!(monomial_a, monomial_b);
     }
@@ -281,12 +459,12 @@ mod tests {
     fn test_equality_f32() {
         let coefficient: f32 = 2.0;
         let monomial_a = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         let monomial_b = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         assert_eq!(monomial_a, monomial_b);
     }
@@ -295,12 +473,12 @@ mod tests {
     fn test_equality_f64() {
         let coefficient: f64 = 2.0;
         let monomial_a = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         let monomial_b = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         assert_eq!(monomial_a, monomial_b);
     }
@@ -309,12 +487,12 @@ mod tests {
     fn test_equality_complex() {
         let coefficient = Complex::new(2, 3);
         let monomial_a = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         let monomial_b = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         assert_eq!(monomial_a, monomial_b);
     }
@@ -323,12 +501,12 @@ mod tests {
     fn test_equality_rational() {
         let coefficient = Ratio::new(2, 3);
         let monomial_a = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         let monomial_b = Monomial {
-            coefficient: coefficient,
-            power_list: smallvec![1, 1, 1],
+            coefficient,
+            power_list: exponents![1, 1, 1],
         };
         assert_eq!(monomial_a, monomial_b);
     }
@@ -338,11 +516,11 @@ mod tests {
         // 6x == 5x
         let monomial_a = Monomial {
             coefficient: 5.0,
-            power_list: smallvec![1, 0, 0],
+            power_list: exponents![1, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 6.0,
-            power_list: smallvec![1, 0, 0],
+            power_list: exponents![1, 0, 0],
         };
         assert_eq!(monomial_a, monomial_b);
     }
@@ -352,11 +530,11 @@ mod tests {
         // xy > x^2
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 1, 0],
+            power_list: exponents![1, 1, 0],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -366,11 +544,11 @@ mod tests {
         // y^2 > xy
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 2, 0],
+            power_list: exponents![0, 2, 0],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 1, 0],
+            power_list: exponents![1, 1, 0],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -380,11 +558,11 @@ mod tests {
         // y^2 > xz
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 2, 0],
+            power_list: exponents![0, 2, 0],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 0, 1],
+            power_list: exponents![1, 0, 1],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -394,11 +572,11 @@ mod tests {
         // yz > xz
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 1, 1],
+            power_list: exponents![0, 1, 1],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 0, 1],
+            power_list: exponents![1, 0, 1],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -408,11 +586,11 @@ mod tests {
         // z^2 > yz
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 0, 2],
+            power_list: exponents![0, 0, 2],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 1, 1],
+            power_list: exponents![0, 1, 1],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -422,11 +600,11 @@ mod tests {
         // x^2yz^3 > x^5y
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 1, 3],
+            power_list: exponents![2, 1, 3],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![5, 1],
+            power_list: exponents![5, 1],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -436,11 +614,11 @@ mod tests {
         // x^2y > x^3
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 1],
+            power_list: exponents![2, 1],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![3],
+            power_list: exponents![3],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -450,11 +628,11 @@ mod tests {
         // y > x
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 1],
+            power_list: exponents![0, 1],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1],
+            power_list: exponents![1],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -464,11 +642,11 @@ mod tests {
         // z > y
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 0, 1],
+            power_list: exponents![0, 0, 1],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 1],
+            power_list: exponents![0, 1],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -478,11 +656,11 @@ mod tests {
         // z^2 > y^2
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 0, 2],
+            power_list: exponents![0, 0, 2],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 2],
+            power_list: exponents![0, 2],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -492,11 +670,11 @@ mod tests {
         // z > x
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 0, 1],
+            power_list: exponents![0, 0, 1],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1],
+            power_list: exponents![1],
         };
         assert!(monomial_a > monomial_b);
     }
@@ -506,11 +684,11 @@ mod tests {
         // x^2 < x
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 0, 0],
+            power_list: exponents![1, 0, 0],
         };
         assert!(monomial_a < monomial_b);
     }
@@ -520,11 +698,11 @@ mod tests {
         // x^2y^2 < x^3
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 2, 0],
+            power_list: exponents![2, 2, 0],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![3, 0, 0],
+            power_list: exponents![3, 0, 0],
         };
         assert!(monomial_a < monomial_b);
     }
@@ -540,7 +718,7 @@ mod tests {
                 assert_eq!(v.power(1), 1);
                 assert_eq!(v.power(2), 1);
             }
-            Err(_) => assert!(false),
+            Err(_) => panic!(),
         }
     }
 
@@ -555,7 +733,7 @@ mod tests {
                 assert_eq!(v.power(1), 1);
                 assert_eq!(v.power(2), 5);
             }
-            Err(_) => assert!(false),
+            Err(_) => panic!(),
         }
     }
 
@@ -570,7 +748,7 @@ mod tests {
                 assert_eq!(v.power(1), 2);
                 assert_eq!(v.power(2), 0);
             }
-            Err(_) => assert!(false),
+            Err(_) => panic!(),
         }
     }
 
@@ -593,11 +771,33 @@ mod tests {
         assert_eq!(res.power(2), 1);
     }
 
+    #[rstest]
+    fn test_monomial_borrowed_mul(monomial_a: Monomial64, monomial_c: Monomial64) {
+        let product = format!("{}", monomial_a.clone() * monomial_c.clone());
+        assert_eq!(product, "5x^2y^3z");
+        assert_eq!(format!("{}", &monomial_a * &monomial_c), product);
+        assert_eq!(format!("{}", monomial_a.clone() * &monomial_c), product);
+        assert_eq!(format!("{}", &monomial_a * monomial_c.clone()), product);
+        assert_eq!(format!("{}", &monomial_c * 2.0), "10xy^2");
+        assert_eq!(format!("{}", -&monomial_c), "-5xy^2");
+        assert_eq!(format!("{}", -monomial_c), "-5xy^2");
+    }
+
+    #[rstest]
+    fn test_monomial_pow(monomial_c: Monomial64) {
+        let res = monomial_c.pow(3);
+        assert_eq!(res.coefficient, 125.0);
+        assert_eq!(res.power(0), 3);
+        assert_eq!(res.power(1), 6);
+        assert_eq!(res.power(2), 0);
+        assert_eq!(format!("{}", monomial_c.pow(0)), "1");
+    }
+
     #[rstest]
     fn test_monomial_display() {
         let monomial_a = Monomial {
             coefficient: 5.0,
-            power_list: smallvec![1, 0, 1],
+            power_list: exponents![1, 0, 1],
         };
         assert_eq!("5xz", format!("{}", monomial_a).as_str());
     }
@@ -605,7 +805,7 @@ mod tests {
     fn test_monomial_display_one() {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 1, 0],
+            power_list: exponents![1, 1, 0],
         };
         assert_eq!("xy", format!("{}", monomial_a).as_str());
     }
@@ -614,7 +814,7 @@ mod tests {
     fn test_monomial_display_negative() {
         let monomial_a = Monomial {
             coefficient: -4.0,
-            power_list: smallvec![1, 0, 0],
+            power_list: exponents![1, 0, 0],
         };
         assert_eq!("-4x", format!("{}", monomial_a).as_str());
     }
@@ -623,16 +823,83 @@ mod tests {
     fn test_monomial_display_negative_one() {
         let monomial_a = Monomial {
             coefficient: -1.0,
-            power_list: smallvec![1, 0, 0],
+            power_list: exponents![1, 0, 0],
         };
         assert_eq!("-x", format!("{}", monomial_a).as_str());
     }
 
+    #[rstest]
+    fn test_monomial_display_rational() {
+        let monomial = MonomialQ::from("2.25xy").unwrap();
+        assert_eq!(format!("{}", monomial), "(9/4)xy");
+        assert_eq!(format!("{}", -monomial), "-(9/4)xy");
+        let monomial = MonomialQ::from("3x").unwrap();
+        assert_eq!(format!("{}", monomial), "3x");
+    }
+
+    #[rstest]
+    #[case(Complex::new(1.0, 2.0), "(1+2i)x^2")]
+    #[case(Complex::new(0.0, 3.0), "3ix^2")]
+    #[case(Complex::new(0.0, -3.0), "-3ix^2")]
+    #[case(Complex::new(0.0, 1.0), "ix^2")]
+    #[case(Complex::new(0.0, -1.0), "-ix^2")]
+    #[case(Complex::new(-1.0, 0.0), "-x^2")]
+    #[case(Complex::new(2.5, -0.0), "2.5x^2")]
+    #[case(Complex::new(-1.0, -1.0), "(-1-i)x^2")]
+    fn test_monomial_display_complex(#[case] coefficient: Complex<f64>, #[case] expected: &str) {
+        let monomial = Monomial {
+            coefficient,
+            power_list: exponents![2],
+        };
+        assert_eq!(format!("{}", monomial), expected);
+    }
+
+    #[rstest]
+    #[case(Complex::new(1.0, 2.0), "(1+2i)")]
+    #[case(Complex::new(0.0, 2.0), "2i")]
+    #[case(Complex::new(-3.0, 0.0), "-3")]
+    fn test_monomial_display_complex_constant(
+        #[case] coefficient: Complex<f64>,
+        #[case] expected: &str,
+    ) {
+        let monomial = Monomial {
+            coefficient,
+            power_list: Exponents::new(),
+        };
+        assert_eq!(format!("{}", monomial), expected);
+    }
+
+    #[rstest]
+    fn test_monomial_display_complex_rational() {
+        let monomial = MonomialCQ::from("1/2ixy").unwrap();
+        assert_eq!(format!("{}", monomial), "(1/2i)xy");
+        assert_eq!(format!("{}", -monomial), "-(1/2i)xy");
+    }
+
+    #[rstest]
+    fn test_monomial_checked_overflow() {
+        let monomial = Monomial {
+            coefficient: 2.0,
+            power_list: exponents![i32::MAX - 1, 1],
+        };
+        let other = Monomial {
+            coefficient: 3.0,
+            power_list: exponents![0, 1],
+        };
+        assert!(monomial.checked_mul(&other).is_none());
+        assert!(monomial.checked_pow(2).is_none());
+        assert!(other.checked_pow(u32::MAX).is_none());
+        let square = other.checked_pow(2).unwrap();
+        assert_eq!(square.coefficient, 9.0);
+        assert_eq!(square.power(1), 2);
+        assert_eq!(other.checked_mul(&other), Some(square));
+    }
+
     #[rstest]
     fn test_monomial_display_empty() {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![0, 0, 0],
+            power_list: exponents![0, 0, 0],
         };
         assert_eq!("1", format!("{}", monomial_a).as_str());
     }
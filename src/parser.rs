@@ -1,332 +1,624 @@
-use crate::lexer::{Lexer, TokType, Token};
-use crate::monomial::Monomial64;
-use crate::polynomial::Polynomial64;
-use log::{debug, error, info};
-use smallvec::smallvec;
+use crate::ast::{Expr, ExprKind};
+use crate::diagnostic::{Diagnostic, Span};
+pub use crate::lexer::Syntax;
+use crate::lexer::{Lexer, LexerErr, TokType, Token};
+use crate::monomial::variable_index;
+use crate::polynomial::{Limits, Polynomial};
+use crate::CRing;
+use log::{error, info};
+use num::bigint::BigInt;
+use num::complex::Complex;
+use num::rational::Ratio;
+use num::{CheckedMul, Integer, Num, Zero};
+use std::cmp;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
 use std::time::Instant;
 
-pub struct Parser {
+pub type Parser32 = Parser<f32>;
+pub type Parser64 = Parser<f64>;
+pub type ParserZ = Parser<BigInt>;
+pub type ParserQ = Parser<Ratio<BigInt>>;
+pub type ParserC = Parser<Complex<f64>>;
+pub type ParserCQ = Parser<Complex<Ratio<BigInt>>>;
+
+// Parses expressions into polynomials with coefficients of type T.
+pub struct Parser<T> {
     pub lexer: Lexer,
+    // bounds the results of * and ^ in parsed expressions
+    pub limits: Limits,
+    // span of the most recently consumed token, where subexpressions end
+    prev_span: Span,
+    // parentheses opened and not yet closed by the tokens read so far
+    depth: i32,
+    // levels of nesting the parser is currently inside, see nested
+    nesting: usize,
+    coefficient: PhantomData<T>,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum ParserErr {
-    ExpectedToken(String),
-    UnexpectedToken(String),
-    LexerErr(String),
-    InvalidSyntax(String),
+    ExpectedToken(Diagnostic),
+    UnexpectedToken(Diagnostic),
+    LexerErr(Diagnostic),
+    InvalidSyntax(Diagnostic),
+    InvalidLiteral(Diagnostic),
+    EvaluationErr(Diagnostic),
 }
-const NUM_VARS: usize = 8;
 
-impl Parser {
-    pub fn parser_init(current_line: String) -> Result<Self, ParserErr> {
-        let lexer = Lexer::lexer_init(current_line);
-        let mut parser = Parser { lexer: lexer };
-        parser.get_next_token()?;
-        Ok(parser)
+impl ParserErr {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParserErr::LexerErr(_) => "E0001",
+            ParserErr::ExpectedToken(_) => "E0002",
+            ParserErr::UnexpectedToken(_) => "E0003",
+            ParserErr::InvalidSyntax(_) => "E0004",
+            ParserErr::InvalidLiteral(_) => "E0005",
+            ParserErr::EvaluationErr(_) => "E0006",
+        }
     }
 
-    pub fn get_next_token(&mut self) -> Result<(), ParserErr> {
-        match self.lexer.get_next_token() {
-            Ok(()) => (),
-            Err(e) => {
-                let msg = format!(
-                    "Error received while getting next token from lexer: {:?}",
-                    e
-                );
-                error!("{}", msg);
-                return Err(ParserErr::LexerErr(msg));
-            }
+    pub fn diagnostic(&self) -> &Diagnostic {
+        match self {
+            ParserErr::ExpectedToken(d)
+            | ParserErr::UnexpectedToken(d)
+            | ParserErr::LexerErr(d)
+            | ParserErr::InvalidSyntax(d)
+            | ParserErr::InvalidLiteral(d)
+            | ParserErr::EvaluationErr(d) => d,
         }
-        Ok(())
     }
 
-    pub fn peek_next_token(&mut self) -> Result<Token, ParserErr> {
-        match self.lexer.peek_next_token() {
-            Ok(v) => {
-                return Ok(v);
-            }
-            Err(e) => {
-                let msg = format!(
-                    "Error received while peeking next token from lexer: {:?}",
-                    e
-                );
-                error!("{}", msg);
-                return Err(ParserErr::LexerErr(msg));
+    // The error underlined in source, which must be the parsed input.
+    pub fn render(&self, source: &str) -> String {
+        self.diagnostic().render(self.code(), source)
+    }
+
+    // The error underlined in source, read from path starting at line
+    // first_line.
+    pub fn render_in(&self, source: &str, path: &str, first_line: usize) -> String {
+        self.diagnostic()
+            .render_in(self.code(), source, path, first_line)
+    }
+}
+
+impl fmt::Display for ParserErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error[{}]: {}", self.code(), self.diagnostic())
+    }
+}
+
+// Coefficient types that can be read from a numeric literal: a decimal such
+// as 12, 2.5, .5 or 1e-6, as produced by the lexer, or a fraction of two
+// integers such as 3/4. None if the literal does not denote a value of the
+// type, such as 2.5 or 3/4 for an integer type.
+pub trait FromLiteral: Sized {
+    // A literal that is not a fraction.
+    fn from_decimal(literal: &str) -> Option<Self>;
+
+    // The exact quotient, for '/' in expressions. None when dividing by zero
+    // or when the quotient is not a value of the type, as 3 / 2 is not an
+    // integer.
+    fn divide(&self, divisor: &Self) -> Option<Self>;
+
+    // The value of i, for types with an imaginary unit.
+    fn imaginary_unit() -> Option<Self> {
+        None
+    }
+
+    fn from_literal(literal: &str) -> Option<Self> {
+        match literal.split_once('/') {
+            Some((numerator, denominator)) => {
+                Self::from_decimal(numerator)?.divide(&Self::from_decimal(denominator)?)
             }
+            None => Self::from_decimal(literal),
         }
     }
+}
 
-    pub fn parse_monomial(&mut self) -> Result<Monomial64, ParserErr> {
-        let now = Instant::now();
-        let start_ind = self.lexer.curr_pos;
-
-        // get minus symbol
-        let mut coefficient = 1.0;
-        if self.lexer.curr_tok.token_type == TokType::Minus {
-            coefficient = -1.0;
-            self.get_next_token()?;
-        }
-
-        // get coefficient
-        if self.lexer.curr_tok.token_type == TokType::Number {
-            let abs_coeff = self.lexer.curr_tok.token_content.parse::<f64>().unwrap();
-            if coefficient > 0.0 {
-                coefficient = abs_coeff;
-            } else {
-                coefficient = -1.0 * abs_coeff;
+macro_rules! impl_float_from_literal {
+    ($($t:ty),*) => {$(
+        impl FromLiteral for $t {
+            fn from_decimal(literal: &str) -> Option<Self> {
+                literal.parse::<$t>().ok()
             }
-            self.get_next_token()?;
-        }
 
-        let mut power_list = smallvec![0; NUM_VARS];
-        // A single loop will parse x ^ num
-        loop {
-            let prev_position = self.lexer.curr_pos;
-
-            // get xvar
-            if self.lexer.curr_tok.token_type == TokType::Xvar {
-                let ind: usize;
-                match self.lexer.curr_tok.token_content.as_str() {
-                    "x" => ind = 0,
-                    "y" => ind = 1,
-                    "z" => ind = 2,
-                    "u" => ind = 3,
-                    "v" => ind = 4,
-                    "w" => ind = 5,
-                    "s" => ind = 6,
-                    "t" => ind = 7,
-                    _ => {
-                        let msg = format!(
-                            "Received unknown token content {}",
-                            self.lexer.curr_tok.token_content
-                        );
-                        error!("{}", msg);
-                        return Err(ParserErr::UnexpectedToken(msg));
-                    }
-                }
-                self.get_next_token()?;
-                let exponent: i32;
-                // get caret
-                if self.lexer.curr_tok.token_type == TokType::Caret {
-                    self.get_next_token()?;
-                    // get number
-                    if self.lexer.curr_tok.token_type != TokType::Number {
-                        let msg = format!(
-                            "Expected number after ^, found {:?}",
-                            self.lexer.curr_tok.token_type
-                        );
-                        error!("{}", msg);
-                        return Err(ParserErr::ExpectedToken(msg));
-                    }
-                    exponent = self.lexer.curr_tok.token_content.parse::<i32>().unwrap();
-                    self.get_next_token()?;
+            fn divide(&self, divisor: &Self) -> Option<Self> {
+                if *divisor == 0.0 {
+                    None
                 } else {
-                    exponent = 1;
+                    Some(self / divisor)
                 }
-                power_list[ind] = exponent;
             }
+        }
+    )*};
+}
 
-            if prev_position == self.lexer.curr_pos {
-                break;
+impl_float_from_literal!(f32, f64);
+
+macro_rules! impl_integer_from_literal {
+    ($($t:ty),*) => {$(
+        impl FromLiteral for $t {
+            fn from_decimal(literal: &str) -> Option<Self> {
+                literal.parse::<$t>().ok()
+            }
+
+            fn divide(&self, divisor: &Self) -> Option<Self> {
+                match self.checked_rem(*divisor)? {
+                    0 => self.checked_div(*divisor),
+                    _ => None,
+                }
             }
         }
-        let elapsed = now.elapsed();
-        let end_ind = self.lexer.curr_pos;
-        debug!(
-            "Parsed {:?} in {:.5?}",
-            &self.lexer.current_line[start_ind..end_ind],
-            elapsed
-        );
-        Ok(Monomial64 {
-            coefficient,
-            power_list,
-        })
+    )*};
+}
+
+impl_integer_from_literal!(i8, i16, i32, i64, i128, isize);
+
+impl FromLiteral for BigInt {
+    fn from_decimal(literal: &str) -> Option<Self> {
+        literal.parse::<BigInt>().ok()
     }
 
-    pub fn parse_polynomial(&mut self) -> Result<Polynomial64, ParserErr> {
-        let now = Instant::now();
-        let mut polynomial = Polynomial64::new();
-        // Get the first term
-        match self.lexer.curr_tok.token_type {
-            TokType::Number | TokType::Xvar => {
-                let monomial_res = self.parse_monomial()?;
-                polynomial += monomial_res;
-            }
-            _ => {
-                return Err(ParserErr::InvalidSyntax(String::from("Invalid Syntax")));
-            }
+    fn divide(&self, divisor: &Self) -> Option<Self> {
+        if divisor.is_zero() {
+            return None;
         }
-        loop {
-            match self.lexer.curr_tok.token_type {
-                TokType::Minus => {
-                    let next_token = self.peek_next_token()?;
-                    if next_token.token_type == TokType::Number
-                        || next_token.token_type == TokType::Xvar
-                    {
-                        self.get_next_token()?;
-                        let monomial_res = self.parse_monomial()?;
-                        polynomial += monomial_res * -1.0;
-                    } else {
-                        info!(
-                            "token is back to MINUS: {:?}",
-                            self.lexer.curr_tok.token_type
-                        );
-                        break;
-                    }
-                }
-                TokType::Plus => {
-                    let next_token = self.peek_next_token()?;
-                    if next_token.token_type == TokType::Number
-                        || next_token.token_type == TokType::Xvar
-                    {
-                        self.get_next_token()?;
-                        let monomial_res = self.parse_monomial()?;
-                        polynomial += monomial_res;
-                    } else {
-                        info!(
-                            "token is back to PLUS: {:?}",
-                            self.lexer.curr_tok.token_type
-                        );
-                        break;
-                    }
-                }
-                _ => break,
-            }
+        let (quo, rem) = self.div_rem(divisor);
+        if rem.is_zero() {
+            Some(quo)
+        } else {
+            None
         }
-        let elapsed = now.elapsed();
-        let line: String = self.lexer.current_line.iter().collect();
-        debug!("Parsed {:?} in {:.5?}", line, elapsed);
+    }
+}
+
+// Keeps a literal such as 1e1000000000 from exhausting memory.
+const MAX_DECIMAL_EXPONENT: i32 = 10_000;
+
+// Decimal literals are exact, 2.25 is 9/4 and 1e-3 is 1/1000.
+impl<T> FromLiteral for Ratio<T>
+where
+    T: Clone + Integer + FromStr + CheckedMul,
+{
+    fn from_decimal(literal: &str) -> Option<Self> {
+        let (mantissa, exponent) = match literal.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+            None => (literal, 0),
+        };
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let numerator = format!("{}{}", whole, fraction).parse::<T>().ok()?;
+        // the value is numerator * 10^(exponent - digits after the point)
+        let shift = exponent.checked_sub(i32::try_from(fraction.len()).ok()?)?;
+        if shift.abs() > MAX_DECIMAL_EXPONENT {
+            return None;
+        }
+        let ten = "10".parse::<T>().ok()?;
+        let mut power = T::one();
+        for _ in 0..shift.unsigned_abs() {
+            power = power.checked_mul(&ten)?;
+        }
+        if shift < 0 {
+            Some(Ratio::new(numerator, power))
+        } else {
+            Some(Ratio::from_integer(numerator.checked_mul(&power)?))
+        }
+    }
 
-        Ok(polynomial)
+    fn divide(&self, divisor: &Self) -> Option<Self> {
+        if divisor.is_zero() {
+            None
+        } else {
+            Some(self.clone() / divisor.clone())
+        }
     }
+}
 
-    pub fn parse_factor_expr(&mut self) -> Result<Polynomial64, ParserErr> {
-        info!(
-            "parse_factor_expr: recieved token {:?}",
-            self.lexer.curr_tok.token_type
-        );
-        let polynomial: Result<Polynomial64, ParserErr>;
+// Complex literals are real, the imaginary part is written with i, as in
+// 1 + 2i. Division is exact in the same sense as it is for T.
+impl<T> FromLiteral for Complex<T>
+where
+    T: Clone + Num + FromLiteral,
+{
+    fn from_decimal(literal: &str) -> Option<Self> {
+        Some(Complex::new(T::from_decimal(literal)?, T::zero()))
+    }
+
+    fn divide(&self, divisor: &Self) -> Option<Self> {
+        // (a + bi) / (c + di) = ((ac + bd) + (bc - ad)i) / (c^2 + d^2)
+        let norm = divisor.norm_sqr();
+        if norm.is_zero() {
+            return None;
+        }
+        let (a, b) = (self.re.clone(), self.im.clone());
+        let (c, d) = (divisor.re.clone(), divisor.im.clone());
+        let re = a.clone() * c.clone() + b.clone() * d.clone();
+        let im = b * c - a * d;
+        Some(Complex::new(re.divide(&norm)?, im.divide(&norm)?))
+    }
+
+    fn imaginary_unit() -> Option<Self> {
+        Some(Complex::i())
+    }
+}
+
+// Unary plus and minus bind tighter than + and - but looser than products,
+// so -xy is -(xy).
+const PREFIX_BINDING_POWER: u8 = 15;
+
+// How deeply operands may nest, far beyond handwritten input. Each level
+// takes about 10 KiB of stack in a debug build, so the deepest input still
+// parses on a thread with 2 MiB of stack.
+const MAX_NESTING: usize = 128;
+
+// Binding powers of the infix operators on their left and right, the table
+// the Pratt loop dispatches on. Juxtaposed factors multiply, binding like
+// '*', and '^' is right associative.
+const INFIX_BINDING_POWERS: [(TokType, (u8, u8)); 10] = [
+    (TokType::Plus, (10, 11)),
+    (TokType::Minus, (10, 11)),
+    (TokType::Mul, (20, 21)),
+    (TokType::Div, (20, 21)),
+    (TokType::Caret, (40, 39)),
+    // a superscript is a power written without '^', as x² is x^2
+    (TokType::Superscript, (40, 39)),
+    // a number after a factor is left an error since (x + 1)2 reads
+    // ambiguously
+    (TokType::Lpar, (20, 21)),
+    (TokType::Xvar, (20, 21)),
+    (TokType::Imaginary, (20, 21)),
+    (TokType::Frac, (20, 21)),
+];
+
+// The tokens an operand starts with, the table parse_prefix dispatches on.
+const PREFIX_TOKENS: [TokType; 7] = [
+    TokType::Number,
+    TokType::Xvar,
+    TokType::Imaginary,
+    TokType::Lpar,
+    TokType::Frac,
+    TokType::Minus,
+    TokType::Plus,
+];
+
+fn infix_binding_power(token_type: &TokType) -> Option<(u8, u8)> {
+    INFIX_BINDING_POWERS
+        .iter()
+        .find(|(t, _)| t == token_type)
+        .map(|&(_, bp)| bp)
+}
+
+impl<T> Parser<T> {
+    pub fn parser_init(current_line: String) -> Result<Self, ParserErr> {
+        Parser::parser_init_with(current_line, Syntax::Standard)
+    }
+
+    pub fn parser_init_with(current_line: String, syntax: Syntax) -> Result<Self, ParserErr> {
+        let mut lexer = Lexer::lexer_init(current_line);
+        lexer.syntax = syntax;
+        let mut parser = Parser {
+            lexer,
+            limits: Limits::default(),
+            prev_span: Span::default(),
+            depth: 0,
+            nesting: 0,
+            coefficient: PhantomData,
+        };
+        parser.get_next_token()?;
+        Ok(parser)
+    }
+
+    pub fn get_next_token(&mut self) -> Result<(), ParserErr> {
+        self.prev_span = self.lexer.curr_tok.span;
+        match self.lexer.get_next_token() {
+            Ok(()) => (),
+            Err(e) => return Err(self.lexer_error(e)),
+        }
         match self.lexer.curr_tok.token_type {
-            TokType::Lpar => {
-                self.get_next_token()?;
-                let inner = self.parse_poly_expr()?;
+            TokType::Lpar => self.depth += 1,
+            TokType::Rpar => self.depth -= 1,
+            _ => (),
+        }
+        Ok(())
+    }
 
-                if self.lexer.curr_tok.token_type == TokType::Rpar {
-                    self.get_next_token()?;
-                } else {
-                    let msg = String::from("Expected closing parenthesis at end of expression");
-                    error!("{}", msg);
-                    return Err(ParserErr::ExpectedToken(msg));
-                }
-                // Check for exponent on closing parenthesis
-                if self.lexer.curr_tok.token_type == TokType::Caret {
-                    self.get_next_token()?;
-                    if self.lexer.curr_tok.token_type == TokType::Number {
-                        self.get_next_token()?;
-                        let exponent = self.lexer.curr_tok.token_content.parse::<i32>().unwrap();
-                        polynomial = Ok(inner.pow(exponent));
-                    } else {
-                        let msg = format!(
-                            "Expected number after caret, received {:?}",
-                            self.lexer.curr_tok.token_type
-                        );
-                        error!("{}", msg);
-                        return Err(ParserErr::ExpectedToken(msg));
-                    }
-                } else {
-                    polynomial = Ok(inner);
-                }
+    pub fn peek_next_token(&mut self) -> Result<Token, ParserErr> {
+        match self.lexer.peek_next_token() {
+            Ok(v) => Ok(v),
+            Err(e) => Err(self.lexer_error(e)),
+        }
+    }
+
+    // A failed lexer is left on the character it could not read.
+    fn lexer_error(&self, e: LexerErr) -> ParserErr {
+        let pos = self.lexer.curr_pos;
+        let span = Span::new(pos, pos + 1);
+        let diagnostic = match (&e, self.lexer.current_line.get(pos)) {
+            (LexerErr::UnknownChar, Some(ch)) => {
+                Diagnostic::new(format!("Unknown character {:?}", ch), span)
             }
-            TokType::Minus => {
-                self.get_next_token()?;
-                if self.lexer.curr_tok.token_type == TokType::Lpar {
-                    let mut inner = self.parse_factor_expr()?;
-                    inner.scale(-1.0);
-                    polynomial = Ok(inner);
-                } else {
-                    let msg = format!(
-                        "Unexpected token received {:?}",
-                        self.lexer.curr_tok.token_type
-                    );
-                    error!("{}", msg);
-                    return Err(ParserErr::UnexpectedToken(msg));
-                }
+            (LexerErr::UnknownCommand, Some(_)) => {
+                let command: String = self.lexer.current_line[pos + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphabetic())
+                    .collect();
+                Diagnostic::new(
+                    format!("Unknown command \\{}", command),
+                    Span::new(pos, pos + 1 + command.len()),
+                )
+                .with_note(String::from(
+                    "commands are \\cdot, \\times, \\frac and the Greek letters",
+                ))
             }
-            _ => {
-                polynomial = self.parse_polynomial();
+            (LexerErr::UnknownName, Some(_)) => {
+                let name: String = self.lexer.current_line[pos..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_alphabetic())
+                    .collect();
+                Diagnostic::new(
+                    format!("Unknown name {}", name),
+                    Span::new(pos, pos + name.len()),
+                )
+                .with_note(String::from(
+                    "names are the variables, I, and the Greek letters spelled out",
+                ))
             }
-        }
+            (LexerErr::UnexpectedChar, Some(ch)) => {
+                Diagnostic::new(format!("Unexpected character {:?} in number", ch), span)
+                    .with_note(String::from("'_' may only separate two digits"))
+            }
+            _ => Diagnostic::new(format!("Unable to read the next token: {:?}", e), span),
+        };
+        error!("{}", diagnostic.message);
+        ParserErr::LexerErr(diagnostic)
+    }
+
+    // The current token where one of expected should have been.
+    fn unexpected_token(&self, context: &str, expected: &[TokType]) -> Diagnostic {
+        let msg = format!(
+            "Expected {}, found {}",
+            context,
+            self.lexer.curr_tok.token_type.describe()
+        );
+        error!("{}", msg);
+        Diagnostic::new(msg, self.lexer.curr_tok.span)
+            .with_expected(expected.iter().map(|t| t.describe()).collect())
+    }
+
+    // The tokens of the table that the input's syntax can produce, as \frac
+    // only comes from LaTeX.
+    fn lexable<'a>(&self, tokens: impl Iterator<Item = &'a TokType>) -> Vec<TokType> {
+        tokens
+            .filter(|&t| *t != TokType::Frac || self.lexer.syntax == Syntax::Latex)
+            .cloned()
+            .collect()
+    }
 
-        polynomial
+    // What may start an operand.
+    fn expected_operand(&self) -> Vec<TokType> {
+        self.lexable(PREFIX_TOKENS.iter())
     }
 
-    pub fn parse_term_expr(&mut self) -> Result<Polynomial64, ParserErr> {
+    // What may follow a whole operand: an operator, a juxtaposed factor or
+    // the end of the input.
+    fn expected_operator(&self) -> Vec<TokType> {
+        let mut expected = self.lexable(INFIX_BINDING_POWERS.iter().map(|(t, _)| t));
+        expected.push(TokType::End);
+        expected
+    }
+
+    // The span from start to the end of the last consumed token.
+    fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.prev_span.end)
+    }
+
+    // The whole expression up to the first token that cannot continue it.
+    pub fn parse_poly_expr(&mut self) -> Result<Expr, ParserErr> {
+        self.parse_expr_bp(0)
+    }
+
+    // Every nested operand, parenthesized, signed or a right operand, is
+    // parsed by a call of this one.
+    fn parse_expr_bp(&mut self, min_bp: u8) -> Result<Expr, ParserErr> {
+        self.nested(|parser| parser.parse_operators(min_bp))
+    }
+
+    // Runs parse one level of nesting deeper, failing instead once the input
+    // nests too deeply for the parser's recursion.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Expr, ParserErr>,
+    ) -> Result<Expr, ParserErr> {
+        if self.nesting == MAX_NESTING {
+            let msg = String::from("Expression nested too deeply");
+            error!("{}", msg);
+            return Err(ParserErr::InvalidSyntax(
+                Diagnostic::new(msg, self.lexer.curr_tok.span).with_note(format!(
+                    "at most {} levels of nesting are supported",
+                    MAX_NESTING
+                )),
+            ));
+        }
+        self.nesting += 1;
+        let expr = parse(self);
+        self.nesting -= 1;
+        expr
+    }
+
+    // Precedence climbing: parses an operand, then extends it with every
+    // infix operator binding at least as tightly as min_bp.
+    fn parse_operators(&mut self, min_bp: u8) -> Result<Expr, ParserErr> {
         info!(
-            "parse_term_expr: recieved token {:?}",
-            self.lexer.curr_tok.token_type
+            "parse_expr_bp: recieved token {:?} at {}",
+            self.lexer.curr_tok.token_type, min_bp
         );
-        let polynomial = self.parse_factor_expr()?;
-        match self.lexer.curr_tok.token_type {
-            TokType::Mul => {
+        let start = self.lexer.curr_tok.span.start;
+        let mut lhs = self.parse_prefix(min_bp)?;
+        while let Some((left_bp, right_bp)) = infix_binding_power(&self.lexer.curr_tok.token_type) {
+            if left_bp < min_bp {
+                break;
+            }
+            if self.lexer.curr_tok.token_type == TokType::Superscript {
+                let exponent = self.parse_superscript()?;
+                lhs = Expr::new(
+                    ExprKind::Pow(Box::new(lhs), Box::new(exponent)),
+                    self.span_from(start),
+                );
+                continue;
+            }
+            let kind: fn(Box<Expr>, Box<Expr>) -> ExprKind = match self.lexer.curr_tok.token_type {
+                TokType::Plus => ExprKind::Add,
+                TokType::Minus => ExprKind::Sub,
+                TokType::Mul => ExprKind::Mul,
+                TokType::Div => ExprKind::Div,
+                TokType::Caret => ExprKind::Pow,
+                _ => ExprKind::ImplicitMul,
+            };
+            // juxtaposed factors have no operator token to consume
+            if !matches!(
+                self.lexer.curr_tok.token_type,
+                TokType::Lpar | TokType::Xvar | TokType::Imaginary | TokType::Frac
+            ) {
                 self.get_next_token()?;
-                let other = self.parse_factor_expr()?;
-                let mut mul = polynomial * other;
+            }
+            let rhs = self.parse_expr_bp(right_bp)?;
+            lhs = Expr::new(kind(Box::new(lhs), Box::new(rhs)), self.span_from(start));
+        }
+        Ok(lhs)
+    }
 
-                while self.lexer.curr_tok.token_type == TokType::Mul {
-                    self.get_next_token()?;
-                    let other = self.parse_factor_expr()?;
-                    mul = mul * other;
-                }
+    // The exponent of a superscript token, a number with an optional minus.
+    fn parse_superscript(&mut self) -> Result<Expr, ParserErr> {
+        let span = self.lexer.curr_tok.span;
+        let content = self.lexer.curr_tok.token_content.clone();
+        self.get_next_token()?;
+        Ok(match content.strip_prefix('-') {
+            Some(digits) => Expr::new(
+                ExprKind::Neg(Box::new(Expr::new(
+                    ExprKind::Number(String::from(digits)),
+                    Span::new(span.start + 1, span.end),
+                ))),
+                span,
+            ),
+            None => Expr::new(ExprKind::Number(content), span),
+        })
+    }
 
-                return Ok(mul);
-            }
-            _ => {
-                return Ok(polynomial);
-            }
+    // A group in braces, as for \frac{a}{b}.
+    fn parse_group(&mut self, context: &str) -> Result<Expr, ParserErr> {
+        if self.lexer.curr_tok.token_type != TokType::Lpar {
+            return Err(ParserErr::ExpectedToken(
+                self.unexpected_token(context, &[])
+                    .with_expected(vec![String::from("'{'")]),
+            ));
         }
+        self.nested(|parser| parser.parse_prefix(0))
     }
 
-    pub fn parse_poly_expr(&mut self) -> Result<Polynomial64, ParserErr> {
-        info!(
-            "parse_poly_expr: recieved token {:?}",
-            self.lexer.curr_tok.token_type
-        );
-        let polynomial = self.parse_term_expr()?;
+    // An operand: a number, a variable, the imaginary unit, a parenthesized
+    // expression, or one of these after unary plus or minus.
+    fn parse_prefix(&mut self, min_bp: u8) -> Result<Expr, ParserErr> {
+        let start = self.lexer.curr_tok.span.start;
         match self.lexer.curr_tok.token_type {
-            TokType::Plus | TokType::Minus => {
-                let mut sum = polynomial;
-                loop {
-                    match self.lexer.curr_tok.token_type {
-                        TokType::Plus => {
-                            self.get_next_token()?;
-                            let other = self.parse_term_expr()?;
-                            sum = sum + other
-                        }
-                        TokType::Minus => {
-                            self.get_next_token()?;
-                            let other = self.parse_term_expr()?;
-                            sum = sum - other
-                        }
-                        _ => break,
+            TokType::Number => {
+                let expr = Expr::new(
+                    ExprKind::Number(self.lexer.curr_tok.token_content.clone()),
+                    self.lexer.curr_tok.span,
+                );
+                self.get_next_token()?;
+                Ok(expr)
+            }
+            TokType::Xvar => {
+                let ind = match variable_index(&self.lexer.curr_tok.token_content) {
+                    Some(v) => v,
+                    None => {
+                        let msg = format!(
+                            "Received unknown token content {}",
+                            self.lexer.curr_tok.token_content
+                        );
+                        error!("{}", msg);
+                        return Err(ParserErr::UnexpectedToken(Diagnostic::new(
+                            msg,
+                            self.lexer.curr_tok.span,
+                        )));
                     }
+                };
+                let expr = Expr::new(ExprKind::Var(ind), self.lexer.curr_tok.span);
+                self.get_next_token()?;
+                Ok(expr)
+            }
+            TokType::Imaginary => {
+                let expr = Expr::new(ExprKind::Imaginary, self.lexer.curr_tok.span);
+                self.get_next_token()?;
+                Ok(expr)
+            }
+            TokType::Frac => {
+                self.get_next_token()?;
+                let numerator = self.parse_group("'{' after \\frac")?;
+                let denominator = self.parse_group("'{' for the denominator")?;
+                Ok(Expr::new(
+                    ExprKind::Div(Box::new(numerator), Box::new(denominator)),
+                    self.span_from(start),
+                ))
+            }
+            TokType::Lpar => {
+                let lpar = self.lexer.curr_tok.span;
+                self.get_next_token()?;
+                let mut inner = self.parse_expr_bp(0)?;
+                if self.lexer.curr_tok.token_type != TokType::Rpar {
+                    let msg = String::from("Expected closing parenthesis at end of expression");
+                    // braces group in LaTeX
+                    let (open, close) = match self.lexer.current_line[lpar.start] {
+                        '{' => ('{', '}'),
+                        _ => ('(', ')'),
+                    };
+                    error!("{}", msg);
+                    return Err(ParserErr::ExpectedToken(
+                        Diagnostic::new(msg, self.lexer.curr_tok.span)
+                            .with_expected(vec![format!("'{}'", close)])
+                            .with_note(format!(
+                                "to match the '{}' at column {}",
+                                open,
+                                lpar.start + 1
+                            )),
+                    ));
                 }
-                return Ok(sum);
+                self.get_next_token()?;
+                // the group spans its parentheses
+                inner.span = self.span_from(start);
+                Ok(inner)
             }
-            _ => {
-                return Ok(polynomial);
+            TokType::Minus | TokType::Plus => {
+                let negative = self.lexer.curr_tok.token_type == TokType::Minus;
+                self.get_next_token()?;
+                // the operand extends no further than the enclosing operator
+                // allows, so 2^-x*y is (2^-x)*y
+                let mut operand = self.parse_expr_bp(cmp::max(PREFIX_BINDING_POWER, min_bp))?;
+                if negative {
+                    operand = Expr::new(ExprKind::Neg(Box::new(operand)), self.span_from(start));
+                } else {
+                    operand.span = self.span_from(start);
+                }
+                Ok(operand)
             }
+            _ => Err(ParserErr::InvalidSyntax(
+                self.unexpected_token("a term", &self.expected_operand()),
+            )),
         }
     }
 
-    pub fn start_parser(&mut self) -> Result<Polynomial64, ParserErr> {
+    // Parses the whole input into an expression, without evaluating it.
+    // Empty input is zero.
+    pub fn parse_expr(&mut self) -> Result<Expr, ParserErr> {
         let now = Instant::now();
         // Check for empty input
         while self.lexer.curr_tok.token_type == TokType::Newl {
             self.get_next_token()?
         }
         if self.lexer.curr_tok.token_type == TokType::End {
-            return Ok(Polynomial64::new());
+            let span = self.lexer.curr_tok.span;
+            return Ok(Expr::new(ExprKind::Number(String::from("0")), span));
         }
 
         let mut parser_res = self.parse_poly_expr();
@@ -335,41 +627,144 @@ impl Parser {
         let line: String = self.lexer.current_line.iter().collect();
         info!("Parsed {:?} in {:.5?}", line, elapsed);
 
-        if self.lexer.curr_tok.token_type != TokType::End
+        // an earlier error leaves the lexer mid-expression, keep that error
+        if parser_res.is_ok()
+            && self.lexer.curr_tok.token_type != TokType::End
             && self.lexer.curr_tok.token_type != TokType::Newl
         {
-            error!("{:?}", self.lexer.curr_tok.token_type);
-            parser_res = Err(ParserErr::InvalidSyntax("Invalid syntax".to_string()));
+            parser_res = Err(ParserErr::InvalidSyntax(self.unexpected_token(
+                "an operator or end of input",
+                &self.expected_operator(),
+            )));
         }
         parser_res
     }
+
+    // Parses like parse_expr, but after an error skips ahead to the next
+    // operator and carries on, so that one pass finds every error in the
+    // input. The errors are returned in the order they appear.
+    pub fn parse_expr_all(&mut self) -> Result<Expr, Vec<ParserErr>> {
+        let mut errors = match self.parse_expr() {
+            Ok(v) => return Ok(v),
+            Err(e) => vec![e],
+        };
+        while self.synchronize(&mut errors) {
+            match self.parse_poly_expr() {
+                Ok(_) => match self.lexer.curr_tok.token_type {
+                    TokType::End | TokType::Newl => break,
+                    // skipped, and reported if unmatched, while synchronizing
+                    TokType::Rpar => (),
+                    _ => errors.push(ParserErr::InvalidSyntax(self.unexpected_token(
+                        "an operator or end of input",
+                        &self.expected_operator(),
+                    ))),
+                },
+                Err(e) => errors.push(e),
+            }
+        }
+        Err(errors)
+    }
+
+    // Skips tokens up to and including the next operator, leaving the parser
+    // at the start of an expression. False if the input ran out first.
+    fn synchronize(&mut self, errors: &mut Vec<ParserErr>) -> bool {
+        if let Some(ParserErr::LexerErr(_)) = errors.last() {
+            self.skip_unreadable(errors);
+        }
+        loop {
+            let at_operator = match self.lexer.curr_tok.token_type {
+                TokType::End | TokType::Newl => return false,
+                TokType::Plus | TokType::Minus | TokType::Mul | TokType::Div => true,
+                // the parenthesis may close a group opened before the error
+                TokType::Rpar if self.depth < 0 => {
+                    let span = self.lexer.curr_tok.span;
+                    if errors.last().map(|e| e.diagnostic().span) != Some(span) {
+                        let msg = String::from("Unmatched closing parenthesis");
+                        error!("{}", msg);
+                        errors.push(ParserErr::UnexpectedToken(Diagnostic::new(msg, span)));
+                    }
+                    self.depth = 0;
+                    false
+                }
+                _ => false,
+            };
+            if let Err(e) = self.get_next_token() {
+                errors.push(e);
+                self.skip_unreadable(errors);
+            }
+            if at_operator {
+                return true;
+            }
+        }
+    }
+
+    // Moves past the character the lexer stopped on, and any unreadable
+    // characters after it, to the next token.
+    fn skip_unreadable(&mut self, errors: &mut Vec<ParserErr>) {
+        loop {
+            self.lexer.curr_pos = cmp::min(self.lexer.curr_pos + 1, self.lexer.line_size);
+            self.lexer.next_tok = None;
+            match self.get_next_token() {
+                Ok(()) => return,
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+}
+
+impl<T> Parser<T>
+where
+    T: CRing + Clone + PartialEq + FromLiteral,
+{
+    pub fn start_parser(&mut self) -> Result<Polynomial<T>, ParserErr> {
+        self.parse_expr()?.evaluate(&self.limits)
+    }
+
+    // Evaluates only if the whole input parsed, returning every syntax error
+    // otherwise.
+    pub fn parse_all(&mut self) -> Result<Polynomial<T>, Vec<ParserErr>> {
+        self.parse_expr_all()?
+            .evaluate(&self.limits)
+            .map_err(|e| vec![e])
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::*;
-    use smallvec::smallvec;
     use std::mem;
 
     #[rstest]
     fn parser_monomial_degree_one() {
-        let mut parser = Parser::parser_init(String::from("x\n")).unwrap();
-        let mut monomial = parser.parse_monomial().unwrap();
+        let mut parser = Parser64::parser_init(String::from("x\n")).unwrap();
+        let mut monomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate_monomial::<f64>()
+            .unwrap();
 
         assert_eq!(monomial.coefficient, 1.0);
         assert_eq!(monomial.power(0), 1);
         assert_eq!(monomial.degree(), 1);
 
-        parser = Parser::parser_init(String::from("y\n")).unwrap();
-        monomial = parser.parse_monomial().unwrap();
+        parser = Parser64::parser_init(String::from("y\n")).unwrap();
+        monomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate_monomial::<f64>()
+            .unwrap();
 
         assert_eq!(monomial.coefficient, 1.0);
         assert_eq!(monomial.power(1), 1);
         assert_eq!(monomial.degree(), 1);
 
-        parser = Parser::parser_init(String::from("z\n")).unwrap();
-        monomial = parser.parse_monomial().unwrap();
+        parser = Parser64::parser_init(String::from("z\n")).unwrap();
+        monomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate_monomial::<f64>()
+            .unwrap();
 
         assert_eq!(monomial.coefficient, 1.0);
         assert_eq!(monomial.power(2), 1);
@@ -378,8 +773,12 @@ mod tests {
 
     #[rstest]
     fn parser_monomial_multivariate() {
-        let mut parser = Parser::parser_init(String::from("xyz\n")).unwrap();
-        let monomial = parser.parse_monomial().unwrap();
+        let mut parser = Parser64::parser_init(String::from("xyz\n")).unwrap();
+        let monomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate_monomial::<f64>()
+            .unwrap();
         assert_eq!(monomial.coefficient, 1.0);
         assert_eq!(monomial.power(0), 1);
         assert_eq!(monomial.power(1), 1);
@@ -389,8 +788,12 @@ mod tests {
 
     #[rstest]
     fn parser_monomial_multivariate_2() {
-        let mut parser = Parser::parser_init(String::from("3.5x^2yz^5\n")).unwrap();
-        let monomial = parser.parse_monomial().unwrap();
+        let mut parser = Parser64::parser_init(String::from("3.5x^2yz^5\n")).unwrap();
+        let monomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate_monomial::<f64>()
+            .unwrap();
 
         assert_eq!(monomial.coefficient, 3.5);
         assert_eq!(monomial.degree(), 8);
@@ -402,16 +805,24 @@ mod tests {
     #[rstest]
     fn parse_polynomial_simple() {
         let mut parser =
-            Parser::parser_init(String::from("2x + y + z + 2x + y + y + y + z\n")).unwrap();
-        let polynomial = parser.parse_polynomial().unwrap();
+            Parser64::parser_init(String::from("2x + y + z + 2x + y + y + y + z\n")).unwrap();
+        let polynomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate::<f64>(&Limits::unbounded())
+            .unwrap();
 
         assert_eq!(polynomial.monomials.len(), 3);
     }
 
     #[rstest]
     fn parse_polynomial_multivariate_a() {
-        let mut parser = Parser::parser_init(String::from("2xyz + yzx + zxy + xy \n")).unwrap();
-        let polynomial = parser.parse_polynomial().unwrap();
+        let mut parser = Parser64::parser_init(String::from("2xyz + yzx + zxy + xy \n")).unwrap();
+        let polynomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate::<f64>(&Limits::unbounded())
+            .unwrap();
 
         assert_eq!(polynomial.monomials.len(), 2);
         assert_eq!(polynomial.monomials[0].coefficient, 4.0);
@@ -421,8 +832,13 @@ mod tests {
     #[rstest]
     fn parse_polynomial_multivariate_b() {
         let mut parser =
-            Parser::parser_init(String::from("2xyz + zyx+ zy + 2x + zy + x + yzx + yz\n")).unwrap();
-        let polynomial = parser.parse_polynomial().unwrap();
+            Parser64::parser_init(String::from("2xyz + zyx+ zy + 2x + zy + x + yzx + yz\n"))
+                .unwrap();
+        let polynomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate::<f64>(&Limits::unbounded())
+            .unwrap();
 
         assert_eq!(polynomial.monomials.len(), 3);
         assert_eq!(format!("{}", polynomial.monomials[0]), "4xyz");
@@ -432,8 +848,12 @@ mod tests {
 
     #[rstest]
     fn parse_polynomial_numbers_only() {
-        let mut parser = Parser::parser_init(String::from("2 + 3 + 4.5\n")).unwrap();
-        let polynomial = parser.parse_polynomial().unwrap();
+        let mut parser = Parser64::parser_init(String::from("2 + 3 + 4.5\n")).unwrap();
+        let polynomial = parser
+            .parse_poly_expr()
+            .unwrap()
+            .evaluate::<f64>(&Limits::unbounded())
+            .unwrap();
 
         assert_eq!(polynomial.monomials.len(), 1);
         assert_eq!(polynomial.monomials[0].coefficient, 9.5);
@@ -441,413 +861,488 @@ mod tests {
 
     #[rstest]
     fn parse_polynomial_error_a() {
-        let mut parser = Parser::parser_init(String::from("+ y + z")).unwrap();
-        let res = parser.parse_polynomial();
+        let mut parser = Parser64::parser_init(String::from("* y + z")).unwrap();
+        let res = parser.parse_poly_expr();
         match res {
-            Ok(_) => assert!(false),
+            Ok(_) => panic!(),
             Err(e) => assert_eq!(
                 mem::discriminant(&e),
-                mem::discriminant(&ParserErr::InvalidSyntax(String::from("")))
+                mem::discriminant(&ParserErr::InvalidSyntax(Diagnostic::default()))
             ),
         }
     }
 
     #[rstest]
     fn parse_polynomial_error_b() {
-        let mut parser = Parser::parser_init(String::from("- y + z")).unwrap();
-        let res = parser.parse_polynomial();
+        let mut parser = Parser64::parser_init(String::from("^ y + z")).unwrap();
+        let res = parser.parse_poly_expr();
         match res {
-            Ok(_) => assert!(false),
+            Ok(_) => panic!(),
             Err(e) => assert_eq!(
                 mem::discriminant(&e),
-                mem::discriminant(&ParserErr::InvalidSyntax(String::from("")))
+                mem::discriminant(&ParserErr::InvalidSyntax(Diagnostic::default()))
             ),
         }
     }
 
     #[rstest]
     fn parse_polynomial_expr_mulitplication_a() {
-        let mut parser = Parser::parser_init(String::from("(x + y) * (x + y)")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x + y) * (x + y)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^2 + 2xy + y^2"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_expr_mulitplication_b() {
         let mut parser =
-            Parser::parser_init(String::from("(((x + y) * (x + y))) * (x + y)")).unwrap();
+            Parser64::parser_init(String::from("(((x + y) * (x + y))) * (x + y)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + 3x^2y + 3xy^2 + y^3"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_expr_mulitplication_c() {
         let mut parser =
-            Parser::parser_init(String::from("(x^4 + 1) * ((x^3 + 2x) * (x + 1))")).unwrap();
+            Parser64::parser_init(String::from("(x^4 + 1) * ((x^3 + 2x) * (x + 1))")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(
                 format!("{}", v),
                 "x^8 + x^7 + 2x^6 + 2x^5 + x^4 + x^3 + 2x^2 + 2x"
             ),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_expr_mulitplication_d() {
-        let mut parser = Parser::parser_init(String::from(
+        let mut parser = Parser64::parser_init(String::from(
             "(((x + y + z)*(x + y + z))*(x + y + z))*(x + y + z)",
         ))
         .unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^4 + 4x^3y + 4x^3z + 6x^2y^2 + 12x^2yz + 6x^2z^2 + 4xy^3 + 12xy^2z + 12xyz^2 + 4xz^3 + y^4 + 4y^3z + 6y^2z^2 + 4yz^3 + z^4"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_expr_mulitplication_negative_a() {
         let mut parser =
-            Parser::parser_init(String::from("((x - y) * (x + y)) * (x + y)")).unwrap();
+            Parser64::parser_init(String::from("((x - y) * (x + y)) * (x + y)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + x^2y - xy^2 - y^3"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_expr_mulitplication_negative_b() {
         let mut parser =
-            Parser::parser_init(String::from("(((x - y) * (x + y)) * (x + y)) * (x + y)")).unwrap();
+            Parser64::parser_init(String::from("(((x - y) * (x + y)) * (x + y)) * (x + y)"))
+                .unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^4 + 2x^3y - 2xy^3 - y^4"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_expr_parentheses() {
-        let mut parser = Parser::parser_init(String::from("(((x + y)))")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(((x + y)))")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x + y"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_addition_and_multiplication() {
-        let mut parser = Parser::parser_init(String::from("x^3 + x^2 + (x + 5)*(x - 7)")).unwrap();
+        let mut parser =
+            Parser64::parser_init(String::from("x^3 + x^2 + (x + 5)*(x - 7)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + 2x^2 - 2x - 35"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_subtraction_and_multiplication_a() {
-        let mut parser = Parser::parser_init(String::from("x^3 - x^2 + (x + 5)*(x - 7)")).unwrap();
+        let mut parser =
+            Parser64::parser_init(String::from("x^3 - x^2 + (x + 5)*(x - 7)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 - 2x - 35"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_subtraction_and_multiplication_b() {
-        let mut parser = Parser::parser_init(String::from("x^3 - x^2 - (x + 5)*(x - 7)")).unwrap();
+        let mut parser =
+            Parser64::parser_init(String::from("x^3 - x^2 - (x + 5)*(x - 7)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => {
                 println!("{:?}", v);
                 assert_eq!(format!("{}", v), "x^3 - 2x^2 + 2x + 35");
             }
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_subtraction_and_multiplication_c() {
-        let mut parser = Parser::parser_init(String::from("x^2 - (x + 5)*(x - 7)")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("x^2 - (x + 5)*(x - 7)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => {
                 println!("{:?}", v);
                 assert_eq!(format!("{}", v), "2x + 35");
             }
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_multiplication_linear_factors() {
-        let mut parser = Parser::parser_init(String::from("(x + 5)*(x - 7)*(x - 4)")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x + 5)*(x - 7)*(x - 4)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 - 6x^2 - 27x + 140"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_multiplication_a() {
         let mut parser =
-            Parser::parser_init(String::from("x^4 + x^3 + (x + 5)*(x - 7)*(x - 4)")).unwrap();
+            Parser64::parser_init(String::from("x^4 + x^3 + (x + 5)*(x - 7)*(x - 4)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^4 + 2x^3 - 6x^2 - 27x + 140"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_multiplication_b() {
-        let mut parser = Parser::parser_init(String::from(
+        let mut parser = Parser64::parser_init(String::from(
             "(x + 5)*(x - 7)*(x - 4) + (x + 5)*(x - 7)*(x - 4)",
         ))
         .unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "2x^3 - 12x^2 - 54x + 280"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_multiplication_quadratic_factor() {
         let mut parser =
-            Parser::parser_init(String::from("(2x + 10)*(x - 7)*(x^2 + x + 1)")).unwrap();
+            Parser64::parser_init(String::from("(2x + 10)*(x - 7)*(x^2 + x + 1)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "2x^4 - 2x^3 - 72x^2 - 74x - 70"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_multiplication_multivariate_factor_a() {
         let mut parser =
-            Parser::parser_init(String::from("(2x + 10)*(x - 7)*(x^2 + y + z + 1)")).unwrap();
+            Parser64::parser_init(String::from("(2x + 10)*(x - 7)*(x^2 + y + z + 1)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(
                 format!("{}", v),
                 "2x^4 - 4x^3 + 2x^2y + 2x^2z - 68x^2 - 4xy - 4xz - 4x - 70y - 70z - 70"
             ),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_multiplication_multivariate_factor_b() {
-        let mut parser = Parser::parser_init(String::from(
+        let mut parser = Parser64::parser_init(String::from(
             "(x^2 + y + z + 1)*(x - 7 + y)*(x^2 + y + z + 1)",
         ))
         .unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^5 + x^4y - 7x^4 + 2x^3y + 2x^3z + 2x^2y^2 + 2x^2yz + 2x^3 - 12x^2y - 14x^2z + xy^2 + 2xyz + xz^2 + y^3 + 2y^2z + yz^2 - 14x^2 + 2xy + 2xz - 5y^2 - 12yz - 7z^2 + x - 13y - 14z - 7"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_exponentiation_a() {
-        let mut parser = Parser::parser_init(String::from("(x + 2)^3")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x + 2)^3")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + 6x^2 + 12x + 8"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_exponentiation_b() {
-        let mut parser = Parser::parser_init(String::from("(x + 3)^2*(x + 3)")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x + 3)^2*(x + 3)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + 9x^2 + 27x + 27"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_exponentiation_multivariate() {
-        let mut parser = Parser::parser_init(String::from("(x + y + z)^5")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x + y + z)^5")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^5 + 5x^4y + 5x^4z + 10x^3y^2 + 20x^3yz + 10x^3z^2 + 10x^2y^3 + 30x^2y^2z + 30x^2yz^2 + 10x^2z^3 + 5xy^4 + 20xy^3z + 30xy^2z^2 + 20xyz^3 + 5xz^4 + y^5 + 5y^4z + 10y^3z^2 + 10y^2z^3 + 5yz^4 + z^5"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_exponentiation_expression() {
-        let mut parser = Parser::parser_init(String::from("(x^2 + (x + 2)*(x + 2))^2")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x^2 + (x + 2)*(x + 2))^2")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "4x^4 + 16x^3 + 32x^2 + 32x + 16"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_addition_a() {
-        let mut parser = Parser::parser_init(String::from("x^3 + (x^3) + x^3 ")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("x^3 + (x^3) + x^3 ")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "3x^3"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_addition_b() {
-        let mut parser = Parser::parser_init(String::from("x^3 + (x + 3)*(x + 3) + x^2")).unwrap();
+        let mut parser =
+            Parser64::parser_init(String::from("x^3 + (x + 3)*(x + 3) + x^2")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + 2x^2 + 6x + 9"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_addition_c() {
         let mut parser =
-            Parser::parser_init(String::from("x^3 + (x + 5)^3*(x + 4) + x^3 ")).unwrap();
+            Parser64::parser_init(String::from("x^3 + (x + 5)^3*(x + 4) + x^3 ")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^4 + 21x^3 + 135x^2 + 425x + 500"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_addition_d() {
-        let mut parser = Parser::parser_init(String::from(
+        let mut parser = Parser64::parser_init(String::from(
             "x^3 + (x + 2)*(x + 2) + x^3 + (x + 2)*(x + 2) - x^3",
         ))
         .unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + 2x^2 + 8x + 8"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_addition_e() {
-        let mut parser = Parser::parser_init(String::from("x^3 + (x + 3)*(x + 3) - x^2")).unwrap();
+        let mut parser =
+            Parser64::parser_init(String::from("x^3 + (x + 3)*(x + 3) - x^2")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 + 6x + 9"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_addition_f() {
-        let mut parser = Parser::parser_init(String::from("x^3 - (x^3) - x^3 ")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("x^3 - (x^3) - x^3 ")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "-x^3"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_repeated_addition_g() {
-        let mut parser = Parser::parser_init(String::from("x^3 - (x + 3)*(x + 3) + x^2")).unwrap();
+        let mut parser =
+            Parser64::parser_init(String::from("x^3 - (x + 3)*(x + 3) + x^2")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^3 - 6x - 9"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_distribute_negative_a() {
-        let mut parser = Parser::parser_init(String::from("-(x + y + z)")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("-(x + y + z)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "-x - y - z"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_distribute_negative_b() {
-        let mut parser = Parser::parser_init(String::from("-(x + 2)*(x + 2)")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("-(x + 2)*(x + 2)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "-x^2 - 4x - 4"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_distribute_negative_c() {
         let mut parser =
-            Parser::parser_init(String::from("-(x^3 - (x + 3)*(x + 3) + x^2)")).unwrap();
+            Parser64::parser_init(String::from("-(x^3 - (x + 3)*(x + 3) + x^2)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "-x^3 + 6x + 9"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_distribute_negative_d() {
-        let mut parser = Parser::parser_init(String::from("-(x + 2)*-(x + 2)*-(x + 2)")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("-(x + 2)*-(x + 2)*-(x + 2)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "-x^3 - 6x^2 - 12x - 8"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn parse_polynomial_distribute_negative_e() {
-        let mut parser = Parser::parser_init(String::from("-(x + 2)*-(x + 2) - -(x + 2)")).unwrap();
+        let mut parser =
+            Parser64::parser_init(String::from("-(x + 2)*-(x + 2) - -(x + 2)")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
             Ok(v) => assert_eq!(format!("{}", v), "x^2 + 5x + 6"),
-            Err(e) => assert!(false, "{:?}", e),
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn test_invalid_syntax_a() {
-        let mut parser = Parser::parser_init(String::from("(x + 2)5")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x + 2)5")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
-            Ok(v) => assert!(false, "{:?}", v),
-            Err(e) => assert_eq!(ParserErr::InvalidSyntax(String::from("Invalid syntax")), e),
+            Ok(v) => panic!("{:?}", v),
+            Err(e) => assert_eq!(
+                ParserErr::InvalidSyntax(
+                    Diagnostic::new(
+                        String::from("Expected an operator or end of input, found number"),
+                        Span::new(7, 8)
+                    )
+                    .with_expected(vec![
+                        String::from("'+'"),
+                        String::from("'-'"),
+                        String::from("'*'"),
+                        String::from("'/'"),
+                        String::from("'^'"),
+                        String::from("superscript"),
+                        String::from("'('"),
+                        String::from("variable"),
+                        String::from("imaginary unit"),
+                        String::from("end of input"),
+                    ])
+                ),
+                e
+            ),
         }
     }
 
+    #[rstest]
+    fn test_prefix_tokens_start_operands() {
+        let operands = [
+            (TokType::Number, "2"),
+            (TokType::Xvar, "x"),
+            (TokType::Imaginary, "i"),
+            (TokType::Lpar, "(x)"),
+            (TokType::Frac, "\\frac{1}{2}"),
+            (TokType::Minus, "-x"),
+            (TokType::Plus, "+x"),
+        ];
+        assert_eq!(operands.len(), PREFIX_TOKENS.len());
+        for (token, expr) in operands {
+            assert!(PREFIX_TOKENS.contains(&token), "{:?}", token);
+            let mut parser = ParserCQ::parser_init_with(String::from(expr), Syntax::Latex).unwrap();
+            assert_eq!(parser.lexer.curr_tok.token_type, token);
+            assert!(parser.parse_expr().is_ok(), "{}", expr);
+        }
+    }
+
+    #[rstest]
+    #[case(
+        Syntax::Standard,
+        "x * )",
+        "number, variable, imaginary unit, '(', '-', '+'"
+    )]
+    #[case(
+        Syntax::Latex,
+        "x * )",
+        "number, variable, imaginary unit, '(', '\\frac', '-', '+'"
+    )]
+    #[case(
+        Syntax::Standard,
+        "x = 1",
+        "'+', '-', '*', '/', '^', superscript, '(', variable, imaginary unit, end of input"
+    )]
+    fn test_expected_tokens(#[case] syntax: Syntax, #[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserQ::parser_init_with(String::from(expr), syntax).unwrap();
+        let e = parser.start_parser().unwrap_err();
+        assert_eq!(e.diagnostic().expected.join(", "), expected);
+    }
+
     #[rstest]
     fn test_invalid_syntax_b() {
-        let mut parser = Parser::parser_init(String::from("(x + 5")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("(x + 5")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
-            Ok(v) => assert!(false, "{:?}", v),
+            Ok(v) => panic!("{:?}", v),
             Err(e) => assert_eq!(
-                ParserErr::ExpectedToken(String::from(
-                    "Expected closing parenthesis at end of expression"
-                )),
+                ParserErr::ExpectedToken(
+                    Diagnostic::new(
+                        String::from("Expected closing parenthesis at end of expression"),
+                        Span::new(6, 6)
+                    )
+                    .with_expected(vec![String::from("')'")])
+                    .with_note(String::from("to match the '(' at column 1"))
+                ),
                 e
             ),
         }
@@ -855,41 +1350,41 @@ mod tests {
 
     #[rstest]
     fn test_parser_empty_input() {
-        let mut parser = Parser::parser_init(String::from("")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
-            Ok(v) => assert!(true, "{:?}", v),
-            Err(e) => assert!(false, "{:?}", e),
+            Ok(_) => {}
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn test_parser_input_space() {
-        let mut parser = Parser::parser_init(String::from(" ")).unwrap();
+        let mut parser = Parser64::parser_init(String::from(" ")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
-            Ok(v) => assert!(true, "{:?}", v),
-            Err(e) => assert!(false, "{:?}", e),
+            Ok(_) => {}
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn test_parser_input_new_line() {
-        let mut parser = Parser::parser_init(String::from("\n")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("\n")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
-            Ok(v) => assert!(true, "{:?}", v),
-            Err(e) => assert!(false, "{:?}", e),
+            Ok(_) => {}
+            Err(e) => panic!("{:?}", e),
         }
     }
 
     #[rstest]
     fn test_parser_input_space_and_new_line() {
-        let mut parser = Parser::parser_init(String::from("   \n")).unwrap();
+        let mut parser = Parser64::parser_init(String::from("   \n")).unwrap();
         let polynomial = parser.start_parser();
         match polynomial {
-            Ok(v) => assert!(true, "{:?}", v),
-            Err(e) => assert!(false, "{:?}", e),
+            Ok(_) => {}
+            Err(e) => panic!("{:?}", e),
         }
     }
     // Valid expressions
@@ -898,4 +1393,457 @@ mod tests {
     // ((x^4 + 1))
     // x^3 * x + x^4 + x^2
     // x * (x - 8)^2 * (x - 9)
+
+    #[rstest]
+    fn parse_big_integer_coefficients() {
+        let mut parser =
+            ParserZ::parser_init(String::from("123456789012345678901234567890x - 1\n")).unwrap();
+        let polynomial = parser.start_parser().unwrap();
+        assert_eq!(
+            polynomial.monomials[0].coefficient,
+            "123456789012345678901234567890".parse::<BigInt>().unwrap()
+        );
+        assert_eq!(
+            format!("{}", polynomial),
+            "123456789012345678901234567890x - 1"
+        );
+    }
+
+    #[rstest]
+    fn parse_rational_coefficients() {
+        let mut parser = ParserQ::parser_init(String::from("0.5x + 1.25 - (x - 0.75)\n")).unwrap();
+        let polynomial = parser.start_parser().unwrap();
+        assert_eq!(format!("{}", polynomial), "-(1/2)x + 2");
+    }
+
+    #[rstest]
+    fn parse_fraction_for_integer_type() {
+        let mut parser = ParserZ::parser_init(String::from("2.5x\n")).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert!(matches!(e, ParserErr::InvalidLiteral(_)), "{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("0.1", Ratio::new(1, 10))]
+    #[case("12", Ratio::new(12, 1))]
+    #[case("2.250", Ratio::new(9, 4))]
+    #[case("3.", Ratio::new(3, 1))]
+    fn rational_from_literal(#[case] literal: &str, #[case] expected: Ratio<i64>) {
+        assert_eq!(Ratio::<i64>::from_literal(literal), Some(expected));
+    }
+
+    #[rstest]
+    #[case("x^99999999999")]
+    #[case("(x + 1)^99999999999")]
+    #[case("x^2.5")]
+    fn parse_invalid_exponent(#[case] expr: &str) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert!(matches!(e, ParserErr::InvalidLiteral(_)), "{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("(x + y)^1000", Limits { max_degree: 100, max_terms: usize::MAX })]
+    #[case("(x + y + z)^30", Limits { max_degree: i32::MAX, max_terms: 100 })]
+    #[case("(x + 1) * (y + 1) * (z + 1)", Limits { max_degree: i32::MAX, max_terms: 4 })]
+    #[case("x^2000000000 * x^2000000000", Limits::unbounded())]
+    fn parse_exceeds_limits(#[case] expr: &str, #[case] limits: Limits) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        parser.limits = limits;
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert!(matches!(e, ParserErr::EvaluationErr(_)), "{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("x + 2.5y", "error[E0005]: Invalid literal 2.5 for the coefficient type\n  |\n1 | x + 2.5y\n  |     ^^^\n  = note: the literal is not a value of the coefficient type\n")]
+    #[case("x^", "error[E0004]: Expected a term, found end of input\n  |\n1 | x^\n  |   ^ expected one of number, variable, imaginary unit, '(', '-', '+'\n")]
+    #[case(
+        "x + $",
+        "error[E0001]: Unknown character '$'\n  |\n1 | x + $\n  |     ^\n"
+    )]
+    #[case("x + * y", "error[E0004]: Expected a term, found '*'\n  |\n1 | x + * y\n  |     ^ expected one of number, variable, imaginary unit, '(', '-', '+'\n")]
+    fn render_parser_errors(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserZ::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert_eq!(e.render(expr), expected),
+        }
+    }
+
+    #[rstest]
+    fn evaluation_error_spans_subexpression() {
+        let expr = "y + (x + 1)^20 * x";
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        parser.limits = Limits {
+            max_degree: 10,
+            max_terms: usize::MAX,
+        };
+        let e = parser.start_parser().unwrap_err();
+        assert_eq!(e.code(), "E0006");
+        assert_eq!(e.diagnostic().span, Span::new(4, 14));
+        assert_eq!(
+            e.diagnostic().notes,
+            vec![String::from("degrees are limited to 10")]
+        );
+    }
+
+    #[rstest]
+    #[case("x + * y - ^ 2", vec![Span::new(4, 5), Span::new(10, 11)])]
+    #[case("x^ * y^", vec![Span::new(3, 4), Span::new(7, 7)])]
+    #[case("(x + 2)5 + (y $ 1) + z #", vec![Span::new(7, 8), Span::new(14, 15), Span::new(23, 24)])]
+    #[case("(x + * y) * (z + )) + 1", vec![Span::new(5, 6), Span::new(17, 18), Span::new(18, 19)])]
+    #[case("x + (y * 2", vec![Span::new(10, 10)])]
+    #[case("(x + 2)) * 3) + y $", vec![Span::new(7, 8), Span::new(12, 13), Span::new(18, 19)])]
+    fn parse_all_reports_every_error(#[case] expr: &str, #[case] spans: Vec<Span>) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        match parser.parse_all() {
+            Ok(v) => panic!("{}", v),
+            Err(errors) => assert_eq!(
+                errors
+                    .iter()
+                    .map(|e| e.diagnostic().span)
+                    .collect::<Vec<Span>>(),
+                spans
+            ),
+        }
+    }
+
+    #[rstest]
+    fn parse_all_valid_input() {
+        let mut parser = Parser64::parser_init(String::from("(x + 1)^2 - 1\n")).unwrap();
+        assert_eq!(format!("{}", parser.parse_all().unwrap()), "x^2 + 2x");
+    }
+
+    #[rstest]
+    #[case("2(x+1)", "2x + 2")]
+    #[case("(x+1)(x-1)", "x^2 - 1")]
+    #[case("x(y+z)", "xy + xz")]
+    #[case("3 x y", "3xy")]
+    #[case("x + 2(x + 1)", "3x + 2")]
+    #[case("x + 2*x", "3x")]
+    #[case("2(x + 1)^2", "2x^2 + 4x + 2")]
+    #[case("-(x + 1)(x - 1)", "-x^2 + 1")]
+    #[case("(x + 1)y^2 - y(x)", "xy^2 - xy + y^2")]
+    fn parse_implicit_multiplication(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("+ y + z", "y + z")]
+    #[case("- y + z", "-y + z")]
+    #[case("-x*y", "-xy")]
+    #[case("x * -y - -(-x)", "-xy - x")]
+    #[case("-x^2", "-x^2")]
+    #[case("x^2^3", "x^8")]
+    #[case("2^10", "1024")]
+    #[case("(x+1)^(2+1)", "x^3 + 3x^2 + 3x + 1")]
+    #[case("x^(6 / 2 - 1)y^2^2^0", "x^2y^2")]
+    #[case("2^--1^2 * 4", "8")]
+    #[case("2^3^2", "512")]
+    #[case("(6x^2 + 3x) / 3 / 0.5", "4x^2 + 2x")]
+    #[case("x - y / 2 * 4", "x - 2y")]
+    fn parse_operator_precedence(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("x^y", Span::new(2, 3))]
+    #[case("x^-1", Span::new(0, 4))]
+    #[case("x / y", Span::new(4, 5))]
+    #[case("x / (2 - 2)", Span::new(4, 11))]
+    #[case("x^(1 / 2)", Span::new(2, 9))]
+    #[case("x^(2^31)", Span::new(2, 8))]
+    fn parse_invalid_operands(#[case] expr: &str, #[case] span: Span) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => {
+                assert!(matches!(e, ParserErr::EvaluationErr(_)), "{:?}", e);
+                assert_eq!(e.diagnostic().span, span);
+            }
+        }
+    }
+
+    #[rstest]
+    fn parse_inexact_integer_division() {
+        let mut parser = ParserZ::parser_init(String::from("(4x + 2) / 2")).unwrap();
+        assert_eq!(format!("{}", parser.start_parser().unwrap()), "2x + 1");
+        let mut parser = ParserZ::parser_init(String::from("(4x + 3) / 2")).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert_eq!(e.diagnostic().message, "Inexact division"),
+        }
+    }
+
+    #[rstest]
+    #[case("1/3x + 2/3", "(1/3)x + 2/3")]
+    #[case("1e-3x^2 - .5", "(1/1000)x^2 - 1/2")]
+    #[case("2.5E+2 + 1_000y", "1000y + 250")]
+    #[case("x^2/4", "(1/4)x^2")]
+    #[case("3/4 / 3/8", "1/32")]
+    #[case("(3/4) / (3/8)", "2")]
+    fn parse_rational_literals(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserQ::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("1e-6x + .5", "0.000001x + 0.5")]
+    #[case("3/4x", "0.75x")]
+    #[case("1_000.000_5", "1000.0005")]
+    fn parse_float_literals(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("x + 3/4", "Inexact division", Span::new(4, 7))]
+    #[case("x + 1/0", "Division by zero", Span::new(6, 7))]
+    fn parse_fraction_not_integer(#[case] expr: &str, #[case] message: &str, #[case] span: Span) {
+        let mut parser = ParserZ::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => {
+                assert_eq!(e.diagnostic().message, message);
+                assert_eq!(e.diagnostic().span, span);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case("3/2^2", "3/4")]
+    #[case("1/2^3x", "(1/8)x")]
+    #[case("1/2x", "(1/2)x")]
+    #[case("3/2²", "3/4")]
+    fn parse_fraction_precedence(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserQ::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("(".repeat(100_000) + "x" + &")".repeat(100_000))]
+    #[case("-".repeat(100_000) + "x")]
+    #[case("2^".repeat(100_000) + "2")]
+    #[case("x".to_string() + &"*(x".repeat(100_000))]
+    fn parse_deep_nesting(#[case] expr: String) {
+        let mut parser = Parser64::parser_init(expr).unwrap();
+        let e = parser.start_parser().unwrap_err();
+        assert_eq!(e.code(), "E0004");
+        assert_eq!(e.diagnostic().message, "Expression nested too deeply");
+    }
+
+    #[rstest]
+    fn parse_nesting_limit() {
+        let nested = |levels: usize| "(".repeat(levels) + "x" + &")".repeat(levels);
+        let mut parser = Parser64::parser_init(nested(MAX_NESTING - 1)).unwrap();
+        assert_eq!(format!("{}", parser.start_parser().unwrap()), "x");
+        let mut parser = Parser64::parser_init(nested(MAX_NESTING)).unwrap();
+        let e = parser.start_parser().unwrap_err();
+        assert_eq!(e.diagnostic().span, Span::new(MAX_NESTING, MAX_NESTING + 1));
+    }
+
+    #[rstest]
+    fn parse_deep_frac() {
+        let expr = "\\frac{".repeat(100_000) + "x";
+        let mut parser = Parser64::parser_init_with(expr, Syntax::Latex).unwrap();
+        assert!(matches!(
+            parser.start_parser(),
+            Err(ParserErr::InvalidSyntax(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("x + 1e-3", Span::new(4, 8))]
+    fn parse_literal_not_integer(#[case] expr: &str, #[case] span: Span) {
+        let mut parser = ParserZ::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => {
+                assert!(matches!(e, ParserErr::InvalidLiteral(_)), "{:?}", e);
+                assert_eq!(e.diagnostic().span, span);
+            }
+        }
+    }
+
+    #[rstest]
+    fn render_misplaced_separator() {
+        let expr = "x + 1_000_";
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        assert_eq!(
+            parser.start_parser().unwrap_err().render(expr),
+            "error[E0001]: Unexpected character '_' in number\n  |\n1 | x + 1_000_\n  |          ^\n  = note: '_' may only separate two digits\n"
+        );
+    }
+
+    #[rstest]
+    #[case("1e3", Ratio::new(1000, 1))]
+    #[case("2.5e-2", Ratio::new(1, 40))]
+    #[case(".25", Ratio::new(1, 4))]
+    #[case("6/8", Ratio::new(3, 4))]
+    fn rational_from_scientific_literal(#[case] literal: &str, #[case] expected: Ratio<i64>) {
+        assert_eq!(Ratio::<i64>::from_literal(literal), Some(expected));
+    }
+
+    #[rstest]
+    #[case("1e100")]
+    #[case("1e99999")]
+    #[case("1/0")]
+    fn rational_from_literal_out_of_range(#[case] literal: &str) {
+        assert_eq!(Ratio::<i64>::from_literal(literal), None);
+    }
+
+    #[rstest]
+    #[case("(1+2i)x^2 + 3i y", "(1+2i)x^2 + 3iy")]
+    #[case("i^2", "-1")]
+    #[case("(x + i)(x - i)", "x^2 + 1")]
+    #[case("(1 + i)^2 x - 2", "2ix - 2")]
+    #[case("x / i", "-ix")]
+    #[case("(2 + 4i) / (1 + i) + y", "y + (3+i)")]
+    fn parse_complex(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserC::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("(1/2 + i/3)x", "(1/2+1/3i)x")]
+    #[case("x / (1 + i)", "(1/2-1/2i)x")]
+    #[case("(3 - 4i)(3 + 4i)", "25")]
+    fn parse_complex_rational(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserCQ::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    fn parse_imaginary_unit_in_real_ring() {
+        let mut parser = Parser64::parser_init(String::from("x + 2i")).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => {
+                assert!(matches!(e, ParserErr::InvalidLiteral(_)), "{:?}", e);
+                assert_eq!(e.diagnostic().span, Span::new(5, 6));
+            }
+        }
+    }
+
+    #[rstest]
+    fn parse_imaginary_exponent() {
+        let mut parser = ParserC::parser_init(String::from("x^i")).unwrap();
+        match parser.start_parser() {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert_eq!(e.diagnostic().message, "Exponents must be real"),
+        }
+    }
+
+    #[rstest]
+    #[case("x²y³", "x^2y^3")]
+    #[case("2·x × y", "2xy")]
+    #[case("x − y", "x - y")]
+    #[case("(x + 1)² − x¹⁰", "-x^10 + x^2 + 2x + 1")]
+    #[case("(x − 1)³", "x^3 - 3x^2 + 3x - 1")]
+    #[case("α²β + ω", "α^2β + ω")]
+    #[case("x²³", "x^23")]
+    fn parse_unicode(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = Parser64::parser_init(String::from(expr)).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case("x^{10} + 1", "x^10 + 1")]
+    #[case("\\frac{3}{4}x \\cdot y", "(3/4)xy")]
+    #[case("2\\frac{x}{3}", "(2/3)x")]
+    #[case("\\left(\\alpha + \\beta\\right)^{2}", "α^2 + 2αβ + β^2")]
+    #[case("\\frac{1}{2} \\times x^{2} - \\frac{\\theta}{6}", "(1/2)x^2 - (1/6)θ")]
+    #[case("3\\,x\\;y", "3xy")]
+    fn parse_latex(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserQ::parser_init_with(String::from(expr), Syntax::Latex).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    #[case(
+        "x + \\sqrt{2}",
+        "error[E0001]: Unknown command \\sqrt\n  |\n1 | x + \\sqrt{2}\n  |     ^^^^^\n  = note: commands are \\cdot, \\times, \\frac and the Greek letters\n"
+    )]
+    #[case(
+        "x^{2 + 1",
+        "error[E0002]: Expected closing parenthesis at end of expression\n  |\n1 | x^{2 + 1\n  |         ^ expected '}'\n  = note: to match the '{' at column 3\n"
+    )]
+    #[case(
+        "\\frac 1 2",
+        "error[E0002]: Expected '{' after \\frac, found number\n  |\n1 | \\frac 1 2\n  |       ^ expected '{'\n"
+    )]
+    fn render_latex_errors(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserQ::parser_init_with(String::from(expr), Syntax::Latex).unwrap();
+        assert_eq!(parser.start_parser().unwrap_err().render(expr), expected);
+    }
+
+    #[rstest]
+    fn latex_is_not_standard_syntax() {
+        let mut parser = ParserQ::parser_init(String::from("x^{2}")).unwrap();
+        assert!(matches!(parser.start_parser(), Err(ParserErr::LexerErr(_))));
+    }
+
+    #[rstest]
+    #[case("x**2/2 + 3*x*y - I*z", "(1/2)x^2 + 3xy - iz")]
+    #[case("2 x^2 y + (1 + 2 I) x", "2x^2y + (1+2i)x")]
+    #[case("alpha**2 - 2*alpha*beta", "α^2 - 2αβ")]
+    #[case("x^2*y**3", "x^2y^3")]
+    fn parse_cas(#[case] expr: &str, #[case] expected: &str) {
+        let mut parser = ParserCQ::parser_init_with(String::from(expr), Syntax::Cas).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(format!("{}", v), expected),
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+
+    #[rstest]
+    fn render_unknown_name() {
+        let expr = "x + sqrt(2)";
+        let mut parser = ParserCQ::parser_init_with(String::from(expr), Syntax::Cas).unwrap();
+        assert_eq!(
+            parser.start_parser().unwrap_err().render(expr),
+            "error[E0001]: Unknown name sqrt\n  |\n1 | x + sqrt(2)\n  |     ^^^^\n  = note: names are the variables, I, and the Greek letters spelled out\n"
+        );
+    }
+
+    #[rstest]
+    fn double_star_is_not_standard_syntax() {
+        let mut parser = Parser64::parser_init(String::from("x**2")).unwrap();
+        assert!(parser.start_parser().is_err());
+    }
 }
@@ -1,13 +1,163 @@
-use crate::parser::{Parser, ParserErr};
+use crate::parser::{FromLiteral, Parser, ParserErr};
+use crate::render::{FormatCoefficient, FormatOptions, Notation, Rendered};
+use log::error;
 use std::clone::Clone;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::{Product, Sum};
 use std::ops;
 
+use crate::exponents::Exponents;
 use crate::monomial::Monomial;
-use crate::CRing;
+use crate::{CRing, IntegralDomain};
+use num::bigint::BigInt;
+use num::complex::Complex;
+use num::rational::BigRational;
+use num::{Float, One, Zero};
 
 pub type Polynomial32 = Polynomial<f32>;
 pub type Polynomial64 = Polynomial<f64>;
+pub type PolynomialZ = Polynomial<BigInt>;
+pub type PolynomialQ = Polynomial<BigRational>;
+pub type PolynomialC = Polynomial<Complex<f64>>;
+pub type PolynomialCQ = Polynomial<Complex<BigRational>>;
+
+// Polynomials with at most this many terms are raised to a power by direct
+// multinomial expansion instead of repeated squaring.
+const MULTINOMIAL_MAX_TERMS: usize = 4;
+
+#[derive(Debug, PartialEq)]
+pub enum PolynomialErr {
+    NegativeExponent(String),
+    ExponentOverflow(String),
+    DegreeLimit(String),
+    TermLimit(String),
+}
+
+// Bounds on the size of results, checked by checked_mul and pow_with_limits
+// before and while computing them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub max_degree: i32,
+    pub max_terms: usize,
+}
+
+impl Limits {
+    // Only exponent overflow is an error.
+    pub fn unbounded() -> Limits {
+        Limits {
+            max_degree: i32::MAX,
+            max_terms: usize::MAX,
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self::unbounded()
+    }
+}
+
+// The range of the total degree and of every exponent over the terms of a
+// product, widened to i64 so bounds of large products can be computed
+// without overflowing themselves.
+struct DegreeBounds {
+    degree: (i64, i64),
+    powers: Vec<(i64, i64)>,
+}
+
+impl DegreeBounds {
+    fn of<T: CRing>(monomials: &[Monomial<T>]) -> DegreeBounds {
+        let mut degree = (i64::MAX, i64::MIN);
+        let mut powers: Vec<(i64, i64)> = Vec::new();
+        for monomial in monomials.iter() {
+            let monomial_degree = i64::from(monomial.degree());
+            degree = (
+                std::cmp::min(degree.0, monomial_degree),
+                std::cmp::max(degree.1, monomial_degree),
+            );
+            // a variable missing from a term has exponent zero there, so
+            // starting from (0, 0) is exact for those and safe otherwise
+            if powers.len() < monomial.power_list.len() {
+                powers.resize(monomial.power_list.len(), (0, 0));
+            }
+            for (ind, power) in monomial.power_list.iter().enumerate() {
+                let power = i64::from(power);
+                powers[ind] = (
+                    std::cmp::min(powers[ind].0, power),
+                    std::cmp::max(powers[ind].1, power),
+                );
+            }
+        }
+        if monomials.is_empty() {
+            degree = (0, 0);
+        }
+        DegreeBounds { degree, powers }
+    }
+
+    fn add(&self, other: &DegreeBounds) -> DegreeBounds {
+        let len = std::cmp::max(self.powers.len(), other.powers.len());
+        let powers = (0..len)
+            .map(|ind| {
+                let a = self.powers.get(ind).copied().unwrap_or((0, 0));
+                let b = other.powers.get(ind).copied().unwrap_or((0, 0));
+                (a.0 + b.0, a.1 + b.1)
+            })
+            .collect();
+        DegreeBounds {
+            degree: (
+                self.degree.0 + other.degree.0,
+                self.degree.1 + other.degree.1,
+            ),
+            powers,
+        }
+    }
+
+    fn scale(&self, factor: i64) -> DegreeBounds {
+        DegreeBounds {
+            degree: (
+                self.degree.0.saturating_mul(factor),
+                self.degree.1.saturating_mul(factor),
+            ),
+            powers: self
+                .powers
+                .iter()
+                .map(|(min, max)| (min.saturating_mul(factor), max.saturating_mul(factor)))
+                .collect(),
+        }
+    }
+
+    fn check(&self, limits: &Limits) -> Result<(), PolynomialErr> {
+        let fits =
+            |(min, max): (i64, i64)| min >= i64::from(i32::MIN) && max <= i64::from(i32::MAX);
+        if !fits(self.degree) || !self.powers.iter().all(|&bounds| fits(bounds)) {
+            let msg =
+                String::from("Exponent overflow, the result has exponents beyond the range of i32");
+            error!("{}", msg);
+            return Err(PolynomialErr::ExponentOverflow(msg));
+        }
+        if self.degree.1 > i64::from(limits.max_degree) {
+            let msg = format!(
+                "Result of degree {} exceeds the maximum degree {}",
+                self.degree.1, limits.max_degree
+            );
+            error!("{}", msg);
+            return Err(PolynomialErr::DegreeLimit(msg));
+        }
+        Ok(())
+    }
+}
+
+fn check_terms(num_terms: usize, max_terms: usize) -> Result<(), PolynomialErr> {
+    if num_terms > max_terms {
+        let msg = format!("Result has more than the maximum of {} terms", max_terms);
+        error!("{}", msg);
+        return Err(PolynomialErr::TermLimit(msg));
+    }
+    Ok(())
+}
 
 #[derive(Debug)]
 pub struct Polynomial<T: CRing> {
@@ -23,90 +173,428 @@ where
             monomials: Vec::new(),
         }
     }
+}
+
+impl<T> Polynomial<T>
+where
+    T: CRing + Clone + PartialEq + FromLiteral,
+{
+    pub fn from(expr: &str) -> Result<Polynomial<T>, ParserErr> {
+        let mut parser = Parser::<T>::parser_init(String::from(expr))?;
+        parser.parse_poly_expr()?.evaluate(&Limits::unbounded())
+    }
+}
 
-    pub fn from(expr: &str) -> Result<Polynomial64, ParserErr> {
-        let mut parser = Parser::parser_init(String::from(expr))?;
-        let polynomial = parser.parse_poly_expr()?;
-        Ok(polynomial)
+impl<T> Default for Polynomial<T>
+where
+    T: CRing,
+{
+    fn default() -> Self {
+        Self::new()
     }
 }
 
+// Every operation keeps a polynomial in canonical form: the terms are sorted,
+// no two terms are alike, and no term has a zero coefficient. Two equal
+// polynomials therefore have identical term lists.
 impl<T> Polynomial<T>
 where
     T: CRing + PartialEq,
 {
     pub fn insert_monomial(&mut self, monomial: Monomial<T>) {
         match self.monomials.binary_search(&monomial) {
-            Ok(_) => {}
+            Ok(pos) if monomial.coefficient.is_zero() => {
+                self.monomials.remove(pos);
+            }
+            Ok(pos) => self.monomials[pos] = monomial,
+            Err(_) if monomial.coefficient.is_zero() => {}
             Err(pos) => self.monomials.insert(pos, monomial),
         }
     }
+
+    // Restores the canonical form after the term list was modified directly.
+    pub fn normalize(&mut self) {
+        self.monomials.sort_unstable();
+        self.monomials.dedup_by(|next, kept| {
+            if next.cmp_terms(kept) != Ordering::Equal {
+                return false;
+            }
+            let coefficient = std::mem::replace(&mut kept.coefficient, T::zero());
+            let other = std::mem::replace(&mut next.coefficient, T::zero());
+            kept.coefficient = coefficient + other;
+            true
+        });
+        self.prune();
+    }
+
+    fn prune(&mut self) {
+        self.monomials
+            .retain(|monomial| !monomial.coefficient.is_zero());
+    }
+}
+
+impl<T> Polynomial<T>
+where
+    T: CRing + Float,
+{
+    // Compares coefficients of like terms up to an absolute tolerance. A term
+    // missing from one side counts as a zero coefficient there.
+    pub fn approx_eq(&self, other: &Polynomial<T>, tolerance: T) -> bool {
+        let mut lhs = self.monomials.iter().peekable();
+        let mut rhs = other.monomials.iter().peekable();
+        loop {
+            let difference = match (lhs.peek(), rhs.peek()) {
+                (Some(a), Some(b)) => match a.cmp_terms(b) {
+                    Ordering::Less => lhs.next().unwrap().coefficient,
+                    Ordering::Greater => rhs.next().unwrap().coefficient,
+                    Ordering::Equal => {
+                        lhs.next().unwrap().coefficient - rhs.next().unwrap().coefficient
+                    }
+                },
+                (Some(_), None) => lhs.next().unwrap().coefficient,
+                (None, Some(_)) => rhs.next().unwrap().coefficient,
+                (None, None) => return true,
+            };
+            if difference.abs() > tolerance {
+                return false;
+            }
+        }
+    }
 }
 
 impl<T> Polynomial<T>
 where
     T: CRing + PartialEq + Clone,
 {
-    pub fn pow(&self, exponent: i32) -> Polynomial<T> {
-        let mut ind = exponent - 1;
-        let mut res = self.clone();
+    pub fn pow(&self, exponent: i32) -> Result<Polynomial<T>, PolynomialErr> {
+        self.pow_with_limits(exponent, &Limits::default())
+    }
+
+    pub fn pow_with_limits(
+        &self,
+        exponent: i32,
+        limits: &Limits,
+    ) -> Result<Polynomial<T>, PolynomialErr> {
+        if exponent < 0 {
+            let msg = format!("Cannot raise polynomial to negative power {}", exponent);
+            error!("{}", msg);
+            return Err(PolynomialErr::NegativeExponent(msg));
+        }
+        if exponent == 0 {
+            return Ok(Polynomial::one());
+        }
+
+        let terms: Vec<&Monomial<T>> = self.monomials.iter().collect();
+        if terms.is_empty() {
+            return Ok(Polynomial::new());
+        }
+        // every intermediate result divides the final one, so checking the
+        // final exponents up front rules out overflow along the way
+        DegreeBounds::of(&self.monomials)
+            .scale(i64::from(exponent))
+            .check(limits)?;
+        if let [monomial] = terms.as_slice() {
+            let mut polynomial = Polynomial::new();
+            polynomial += monomial.pow(exponent as u32);
+            return Ok(polynomial);
+        }
+        // the expansion makes every term before like terms are summed, so it
+        // is only used when all of them fit within the limits
+        if terms.len() <= MULTINOMIAL_MAX_TERMS
+            && multinomial_terms(terms.len(), exponent as usize) <= limits.max_terms as u128
+        {
+            return Ok(Self::multinomial_pow(&terms, exponent as usize));
+        }
+        self.binary_pow(exponent as u32, limits)
+    }
+
+    // Exponentiation by squaring, O(log n) polynomial multiplications.
+    fn binary_pow(&self, exponent: u32, limits: &Limits) -> Result<Polynomial<T>, PolynomialErr> {
+        let mut res = Polynomial::one();
+        let mut base = self.clone();
+        let mut ind = exponent;
         while ind > 0 {
-            res = res * self;
-            ind -= 1;
+            if ind & 1 == 1 {
+                res = heap_mul(&res.monomials, &base.monomials, limits.max_terms)?;
+            }
+            ind >>= 1;
+            if ind > 0 {
+                base = heap_mul(&base.monomials, &base.monomials, limits.max_terms)?;
+            }
+        }
+        Ok(res)
+    }
+
+    // Expands (m_1 + ... + m_k)^n as the sum over a_1 + ... + a_k = n of
+    // (n choose a_1, ..., a_k) m_1^a_1 ... m_k^a_k, collecting the terms and
+    // sorting them once.
+    fn multinomial_pow(terms: &[&Monomial<T>], exponent: usize) -> Polynomial<T> {
+        // a binomial only needs the last row
+        let binomials = binomial_table::<T>(exponent, terms.len() > 2);
+        let powers: Vec<Vec<Monomial<T>>> = terms
+            .iter()
+            .map(|&monomial| {
+                let mut row = vec![Monomial::new()];
+                for ind in 1..=exponent {
+                    row.push(&row[ind - 1] * monomial);
+                }
+                row
+            })
+            .collect();
+
+        let mut polynomial = Polynomial::new();
+        Self::expand_multinomial(
+            &powers,
+            &binomials,
+            0,
+            exponent,
+            Monomial::new(),
+            &mut polynomial.monomials,
+        );
+        polynomial.normalize();
+        polynomial
+    }
+
+    fn expand_multinomial(
+        powers: &[Vec<Monomial<T>>],
+        binomials: &[Vec<T>],
+        term_ind: usize,
+        remaining: usize,
+        partial: Monomial<T>,
+        expansion: &mut Vec<Monomial<T>>,
+    ) {
+        // the last term takes whatever power is left over
+        if term_ind == powers.len() - 1 {
+            expansion.push(partial * powers[term_ind][remaining].clone());
+            return;
+        }
+        for power in 0..=remaining {
+            let monomial = partial.clone()
+                * powers[term_ind][power].clone()
+                * binomials[remaining][power].clone();
+            Self::expand_multinomial(
+                powers,
+                binomials,
+                term_ind + 1,
+                remaining - power,
+                monomial,
+                expansion,
+            );
         }
-        res
     }
 
-    pub fn scale(&mut self, scale: T) -> () {
+    // Multiplication that fails instead of exceeding the limits or
+    // overflowing an exponent.
+    pub fn checked_mul(
+        &self,
+        other: &Polynomial<T>,
+        limits: &Limits,
+    ) -> Result<Polynomial<T>, PolynomialErr> {
+        DegreeBounds::of(&self.monomials)
+            .add(&DegreeBounds::of(&other.monomials))
+            .check(limits)?;
+        heap_mul(&self.monomials, &other.monomials, limits.max_terms)
+    }
+
+    pub fn scale(&mut self, scale: T) {
         for monomial in self.monomials.iter_mut() {
             monomial.coefficient = monomial.coefficient.clone() * scale.clone();
         }
+        self.prune();
     }
 }
 
-impl<T> std::fmt::Display for Polynomial<T>
+impl<T> Polynomial<T>
 where
-    T: std::fmt::Display + CRing + Clone + PartialEq,
+    T: CRing + PartialEq + Clone,
 {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut output = format!("");
-        let mut first_term_printed: bool = false;
-
-        for ind in 0..self.monomials.len() {
-            let monomial = &self.monomials[ind];
-            // skip nonzero terms
-            if monomial.coefficient.is_zero() {
-                continue;
+    // Views the polynomial as univariate in the variable var, with
+    // coefficients that are polynomials in the remaining variables.
+    pub fn to_recursive(&self, var: usize) -> Polynomial<Polynomial<T>> {
+        let mut recursive = Polynomial::new();
+        for monomial in self.monomials.iter() {
+            let mut power_list = Exponents::new();
+            power_list.set(var, monomial.power(var));
+            let mut coefficient = monomial.clone();
+            coefficient.power_list.set(var, 0);
+            recursive += Monomial {
+                coefficient: Polynomial {
+                    monomials: vec![coefficient],
+                },
+                power_list,
+            };
+        }
+        recursive
+    }
+
+    // Multiplies out a polynomial with polynomial coefficients. Any variable
+    // may appear both outside and inside the coefficients.
+    pub fn from_recursive(recursive: &Polynomial<Polynomial<T>>) -> Polynomial<T> {
+        let mut polynomial = Polynomial::new();
+        for monomial in recursive.monomials.iter() {
+            for term in monomial.coefficient.monomials.iter() {
+                polynomial += Monomial {
+                    coefficient: term.coefficient.clone(),
+                    power_list: &monomial.power_list + &term.power_list,
+                };
             }
-            // terms after first monomial term are printed differently
-            if first_term_printed {
-                let sign: &str;
-                let mut monomial_expr = format!("{}", monomial);
-                let is_negative: bool;
-
-                let mut chars = monomial_expr.chars();
-                let first_char = chars.next().unwrap();
-                if first_char == '-' {
-                    is_negative = true;
-                    monomial_expr = chars.as_str().to_string();
-                } else {
-                    is_negative = false;
-                }
+        }
+        polynomial
+    }
+}
 
-                // sign
-                if is_negative {
-                    sign = " - ";
-                } else {
-                    sign = " + ";
-                }
+// The operations shared by the sparse representations (the sorted term list
+// Polynomial and the hash map backed HashPolynomial). Algorithms written
+// against this trait work on either, and convert() moves between them.
+pub trait SparsePolynomial<T>: Sized
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn empty() -> Self;
+
+    fn num_terms(&self) -> usize;
+
+    fn terms<'a>(&'a self) -> Box<dyn Iterator<Item = &'a Monomial<T>> + 'a>;
+
+    fn add_term(&mut self, monomial: Monomial<T>);
+
+    fn sub_term(&mut self, mut monomial: Monomial<T>) {
+        monomial.coefficient = T::zero() - monomial.coefficient;
+        self.add_term(monomial);
+    }
+
+    fn from_terms<I>(terms: I) -> Self
+    where
+        I: IntoIterator<Item = Monomial<T>>,
+    {
+        let mut polynomial = Self::empty();
+        for monomial in terms {
+            polynomial.add_term(monomial);
+        }
+        polynomial
+    }
 
-                output.push_str(&format!("{}{}", sign, monomial_expr));
-            } else {
-                output.push_str(&format!("{}", monomial));
-                first_term_printed = true;
+    fn convert<R>(&self) -> R
+    where
+        R: SparsePolynomial<T>,
+    {
+        R::from_terms(self.terms().cloned())
+    }
+
+    fn add_poly(&mut self, other: &Self) {
+        for monomial in other.terms() {
+            self.add_term(monomial.clone());
+        }
+    }
+
+    fn sub_poly(&mut self, other: &Self) {
+        for monomial in other.terms() {
+            self.sub_term(monomial.clone());
+        }
+    }
+
+    fn mul_poly(&self, other: &Self) -> Self {
+        let mut polynomial = Self::empty();
+        for monomial in self.terms() {
+            for other_monomial in other.terms() {
+                polynomial.add_term(monomial * other_monomial);
             }
         }
-        write!(f, "{}", output)
+        polynomial
+    }
+}
+
+impl<T> SparsePolynomial<T> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn empty() -> Self {
+        Polynomial::new()
+    }
+
+    fn num_terms(&self) -> usize {
+        self.monomials.len()
+    }
+
+    fn terms<'a>(&'a self) -> Box<dyn Iterator<Item = &'a Monomial<T>> + 'a> {
+        Box::new(self.monomials.iter())
+    }
+
+    fn add_term(&mut self, monomial: Monomial<T>) {
+        *self += monomial;
+    }
+
+    fn add_poly(&mut self, other: &Self) {
+        self.merge_monomials(other.monomials.clone(), false);
+    }
+
+    fn sub_poly(&mut self, other: &Self) {
+        self.merge_monomials(other.monomials.clone(), true);
+    }
+
+    fn mul_poly(&self, other: &Self) -> Self {
+        mul_or_panic(self, other)
+    }
+}
+
+// Rows 0..=n of Pascal's triangle, built by addition alone so that the
+// coefficients never leave T, which need not have division. Without all_rows
+// only row n is kept, the others left empty, so that memory stays linear in
+// n. The whole triangle is no larger than the expansion of a trinomial that
+// needs it.
+fn binomial_table<T>(n: usize, all_rows: bool) -> Vec<Vec<T>>
+where
+    T: CRing + Clone,
+{
+    let mut table: Vec<Vec<T>> = Vec::with_capacity(n + 1);
+    let mut prev = vec![T::one()];
+    for row_ind in 1..=n {
+        let mut row = Vec::with_capacity(row_ind + 1);
+        row.push(T::one());
+        for ind in 1..row_ind {
+            row.push(prev[ind - 1].clone() + prev[ind].clone());
+        }
+        row.push(T::one());
+        let prev_row = std::mem::replace(&mut prev, row);
+        table.push(if all_rows { prev_row } else { Vec::new() });
+    }
+    table.push(prev);
+    table
+}
+
+// The number of terms in the expansion of a k-term polynomial to the power
+// n, n + k - 1 choose k - 1, or u128::MAX when that overflows.
+fn multinomial_terms(k: usize, n: usize) -> u128 {
+    let mut count: u128 = 1;
+    for ind in 1..k as u128 {
+        // count is n + ind - 1 choose ind - 1, so the division is exact
+        count = match count.checked_mul(n as u128 + ind) {
+            Some(v) => v / ind,
+            None => return u128::MAX,
+        };
+    }
+    count
+}
+
+impl<T> Polynomial<T>
+where
+    T: FormatCoefficient + CRing + Clone + PartialEq,
+{
+    pub fn render(&self, notation: Notation) -> Rendered<'_, T> {
+        Rendered::new(&self.monomials, notation)
+    }
+
+    pub fn render_with(&self, options: FormatOptions) -> Rendered<'_, T> {
+        Rendered::with_options(&self.monomials, options)
+    }
+}
+
+impl<T> std::fmt::Display for Polynomial<T>
+where
+    T: FormatCoefficient + CRing + Clone + PartialEq,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(Notation::Ascii))
     }
 }
 
@@ -121,200 +609,597 @@ where
     }
 }
 
+// With Zero and One, Polynomial<T> is itself a CRing, so polynomials can
+// have polynomial coefficients.
+impl<T> Zero for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn zero() -> Self {
+        Polynomial::new()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.monomials.is_empty()
+    }
+}
+
+impl<T> One for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn one() -> Self {
+        Polynomial {
+            monomials: vec![Monomial::new()],
+        }
+    }
+}
+
+// Polynomials over an integral domain have no zero divisors either: the
+// product of the leading terms cannot vanish.
+impl<T> IntegralDomain for Polynomial<T> where T: IntegralDomain + Clone + PartialEq {}
+
+// Compares coefficients as well as terms, unlike Monomial equality which
+// only identifies like terms.
+impl<T> PartialEq for Polynomial<T>
+where
+    T: CRing + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.monomials.len() == other.monomials.len()
+            && self
+                .monomials
+                .iter()
+                .zip(other.monomials.iter())
+                .all(|(a, b)| a.power_list == b.power_list && a.coefficient == b.coefficient)
+    }
+}
+
+impl<T> Eq for Polynomial<T> where T: CRing + Eq {}
+
+impl<T> Hash for Polynomial<T>
+where
+    T: CRing + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.monomials.len().hash(state);
+        for monomial in self.monomials.iter() {
+            monomial.power_list.hash(state);
+            monomial.coefficient.hash(state);
+        }
+    }
+}
+
 // Polynomial += Monomial
 impl<T> ops::AddAssign<Monomial<T>> for Polynomial<T>
 where
     T: CRing + Clone + PartialEq,
 {
-    fn add_assign(&mut self, other: Monomial<T>) {
-        match self
-            .monomials
-            .binary_search_by(|monomial| monomial.cmp_terms(&other))
-        {
-            Ok(pos) => {
-                self.monomials[pos].coefficient =
-                    self.monomials[pos].coefficient.clone() + other.coefficient;
-            }
-            Err(pos) => {
-                self.monomials.insert(pos, other);
-            }
-        }
+    fn add_assign(&mut self, other: Monomial<T>) {
+        if other.coefficient.is_zero() {
+            return;
+        }
+        match self
+            .monomials
+            .binary_search_by(|monomial| monomial.cmp_terms(&other))
+        {
+            Ok(pos) => {
+                self.monomials[pos].coefficient =
+                    self.monomials[pos].coefficient.clone() + other.coefficient;
+                if self.monomials[pos].coefficient.is_zero() {
+                    self.monomials.remove(pos);
+                }
+            }
+            Err(pos) => {
+                self.monomials.insert(pos, other);
+            }
+        }
+    }
+}
+
+// Polynomial -= Monomial
+impl<T> ops::SubAssign<Monomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn sub_assign(&mut self, other: Monomial<T>) {
+        *self += -other;
+    }
+}
+
+impl<T> Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    // Both term lists are sorted, so like terms are combined in a single
+    // linear pass instead of a binary search and insert per term.
+    fn merge_monomials<I>(&mut self, other: I, subtract: bool)
+    where
+        I: IntoIterator<Item = Monomial<T>>,
+    {
+        let monomials = std::mem::take(&mut self.monomials);
+        let other = other.into_iter();
+        let mut merged = Vec::with_capacity(monomials.len() + other.size_hint().0);
+        let mut lhs = monomials.into_iter().peekable();
+        let mut rhs = other.peekable();
+        loop {
+            let order = match (lhs.peek(), rhs.peek()) {
+                (Some(a), Some(b)) => a.cmp_terms(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let monomial = match order {
+                Ordering::Less => lhs.next().unwrap(),
+                Ordering::Greater => {
+                    let mut monomial = rhs.next().unwrap();
+                    if subtract {
+                        monomial.coefficient = T::zero() - monomial.coefficient;
+                    }
+                    monomial
+                }
+                Ordering::Equal => {
+                    let mut monomial = lhs.next().unwrap();
+                    let other_monomial = rhs.next().unwrap();
+                    if subtract {
+                        monomial.coefficient = monomial.coefficient - other_monomial.coefficient;
+                    } else {
+                        monomial.coefficient = monomial.coefficient + other_monomial.coefficient;
+                    }
+                    monomial
+                }
+            };
+            // like terms may cancel
+            if !monomial.coefficient.is_zero() {
+                merged.push(monomial);
+            }
+        }
+        self.monomials = merged;
+    }
+}
+
+// Polynomial += Polynomial
+impl<T> ops::AddAssign for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn add_assign(&mut self, other: Self) {
+        self.merge_monomials(other.monomials, false);
+    }
+}
+
+// Polynomial += &Polynomial
+impl<T> ops::AddAssign<&Polynomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn add_assign(&mut self, other: &Self) {
+        self.merge_monomials(other.monomials.iter().cloned(), false);
+    }
+}
+
+// Polynomial -= Polynomial
+impl<T> ops::SubAssign for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn sub_assign(&mut self, other: Self) {
+        self.merge_monomials(other.monomials, true);
+    }
+}
+
+// Polynomial -= &Polynomial
+impl<T> ops::SubAssign<&Polynomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn sub_assign(&mut self, other: &Self) {
+        self.merge_monomials(other.monomials.iter().cloned(), true);
+    }
+}
+
+// Polynomial + Polynomial
+impl<T> ops::Add for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+// Polynomial + &Polynomial
+impl<T> ops::Add<&Polynomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn add(mut self, other: &Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+// &Polynomial + Polynomial
+impl<T> ops::Add<Polynomial<T>> for &Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Polynomial<T>;
+    fn add(self, mut other: Polynomial<T>) -> Polynomial<T> {
+        // addition commutes, so reuse the owned operand
+        other += self;
+        other
+    }
+}
+
+// &Polynomial + &Polynomial
+impl<T> ops::Add for &Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Polynomial<T>;
+    fn add(self, other: Self) -> Polynomial<T> {
+        self.clone() + other
+    }
+}
+
+// Polynomial - Polynomial
+impl<T> ops::Sub for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn sub(mut self, other: Self) -> Self {
+        self -= other;
+        self
+    }
+}
+
+// Polynomial - &Polynomial
+impl<T> ops::Sub<&Polynomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn sub(mut self, other: &Self) -> Self {
+        self -= other;
+        self
+    }
+}
+
+// &Polynomial - Polynomial
+impl<T> ops::Sub<Polynomial<T>> for &Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Polynomial<T>;
+    fn sub(self, other: Polynomial<T>) -> Polynomial<T> {
+        // a - b = -b + a, negating the owned operand in place
+        -other + self
+    }
+}
+
+// &Polynomial - &Polynomial
+impl<T> ops::Sub for &Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Polynomial<T>;
+    fn sub(self, other: Self) -> Polynomial<T> {
+        self.clone() - other
+    }
+}
+
+// -Polynomial
+impl<T> ops::Neg for Polynomial<T>
+where
+    T: CRing + Clone,
+{
+    type Output = Self;
+    fn neg(self) -> Self {
+        let monomials = self
+            .monomials
+            .into_iter()
+            .map(|monomial| -monomial)
+            .collect();
+        Polynomial { monomials }
+    }
+}
+
+// -&Polynomial
+impl<T> ops::Neg for &Polynomial<T>
+where
+    T: CRing + Clone,
+{
+    type Output = Polynomial<T>;
+    fn neg(self) -> Polynomial<T> {
+        let monomials = self.monomials.iter().map(|monomial| -monomial).collect();
+        Polynomial { monomials }
+    }
+}
+
+// A pending product lhs[lhs_ind] * rhs[rhs_ind]. The ordering is reversed so
+// that the max-heap yields the leading term first.
+struct HeapTerm<T: CRing> {
+    monomial: Monomial<T>,
+    lhs_ind: usize,
+    rhs_ind: usize,
+}
+
+impl<T> Ord for HeapTerm<T>
+where
+    T: CRing + PartialEq,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.monomial.cmp_terms(&self.monomial)
+    }
+}
+
+impl<T> PartialOrd for HeapTerm<T>
+where
+    T: CRing + PartialEq,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for HeapTerm<T>
+where
+    T: CRing + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for HeapTerm<T> where T: CRing + PartialEq {}
+
+// Johnson's sparse multiplication, in the form given by Monagan and Pearce.
+// The heap holds at most one product per term of the shorter operand, and
+// products come off it in monomial order, so like terms are summed as they
+// are popped and every result term is appended to the end of the output.
+// Fails once the result has more than max_terms terms. Exponents are
+// assumed to have been checked by the caller.
+fn heap_mul<T>(
+    lhs: &[Monomial<T>],
+    rhs: &[Monomial<T>],
+    max_terms: usize,
+) -> Result<Polynomial<T>, PolynomialErr>
+where
+    T: CRing + Clone + PartialEq,
+{
+    let mut lhs: Vec<&Monomial<T>> = lhs.iter().filter(|m| !m.coefficient.is_zero()).collect();
+    let mut rhs: Vec<&Monomial<T>> = rhs.iter().filter(|m| !m.coefficient.is_zero()).collect();
+    if lhs.is_empty() || rhs.is_empty() {
+        return Ok(Polynomial::new());
+    }
+    if lhs.len() > rhs.len() {
+        std::mem::swap(&mut lhs, &mut rhs);
+    }
+
+    let mut heap = BinaryHeap::with_capacity(lhs.len());
+    heap.push(HeapTerm {
+        monomial: lhs[0].product(rhs[0]),
+        lhs_ind: 0,
+        rhs_ind: 0,
+    });
+    // Replaces a popped product with its successors. lhs[i + 1] * rhs[0] is
+    // only needed once lhs[i] * rhs[0] is gone, since it is smaller.
+    let advance = |heap: &mut BinaryHeap<HeapTerm<T>>, lhs_ind: usize, rhs_ind: usize| {
+        if rhs_ind == 0 && lhs_ind + 1 < lhs.len() {
+            heap.push(HeapTerm {
+                monomial: lhs[lhs_ind + 1].product(rhs[0]),
+                lhs_ind: lhs_ind + 1,
+                rhs_ind: 0,
+            });
+        }
+        if rhs_ind + 1 < rhs.len() {
+            heap.push(HeapTerm {
+                monomial: lhs[lhs_ind].product(rhs[rhs_ind + 1]),
+                lhs_ind,
+                rhs_ind: rhs_ind + 1,
+            });
+        }
+    };
+
+    let mut monomials = Vec::new();
+    while let Some(top) = heap.pop() {
+        let mut monomial = top.monomial;
+        advance(&mut heap, top.lhs_ind, top.rhs_ind);
+        while let Some(next) = heap.peek() {
+            if next.monomial.cmp_terms(&monomial) != Ordering::Equal {
+                break;
+            }
+            let next = heap.pop().unwrap();
+            monomial.coefficient = monomial.coefficient + next.monomial.coefficient;
+            advance(&mut heap, next.lhs_ind, next.rhs_ind);
+        }
+        if !monomial.coefficient.is_zero() {
+            monomials.push(monomial);
+            check_terms(monomials.len(), max_terms)?;
+        }
+    }
+    Ok(Polynomial { monomials })
+}
+
+// The arithmetic operators panic on exponent overflow, like the primitive
+// integer types do in debug builds. Use checked_mul to get an error instead.
+fn mul_or_panic<T>(lhs: &Polynomial<T>, rhs: &Polynomial<T>) -> Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    match lhs.checked_mul(rhs, &Limits::unbounded()) {
+        Ok(v) => v,
+        Err(e) => panic!("{:?}", e),
+    }
+}
+
+// Polynomial * Polynomial
+impl<T> ops::Mul for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        mul_or_panic(&self, &other)
+    }
+}
+
+// Polynomial * &Polynomial
+impl<T> ops::Mul<&Polynomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    type Output = Self;
+    fn mul(self, other: &Self) -> Self {
+        mul_or_panic(&self, other)
     }
 }
 
-// Polynomial -= Monomial
-impl<T> ops::SubAssign<Monomial<T>> for Polynomial<T>
+// &Polynomial * Polynomial
+impl<T> ops::Mul<Polynomial<T>> for &Polynomial<T>
 where
     T: CRing + Clone + PartialEq,
 {
-    fn sub_assign(&mut self, mut other: Monomial<T>) {
-        match self
-            .monomials
-            .binary_search_by(|monomial| monomial.cmp_terms(&other))
-        {
-            Ok(pos) => {
-                self.monomials[pos].coefficient =
-                    self.monomials[pos].coefficient.clone() - other.coefficient;
-            }
-            Err(pos) => {
-                let mut zero = other.coefficient.clone();
-                zero.set_zero();
-                other.coefficient = zero - other.coefficient.clone();
-                self.monomials.insert(pos, other);
-            }
-        }
+    type Output = Polynomial<T>;
+    fn mul(self, other: Polynomial<T>) -> Polynomial<T> {
+        mul_or_panic(self, &other)
     }
 }
 
-// Polynomial += Polynomial
-impl<T> ops::AddAssign for Polynomial<T>
+// &Polynomial * &Polynomial
+impl<T> ops::Mul for &Polynomial<T>
 where
     T: CRing + Clone + PartialEq,
 {
-    fn add_assign(&mut self, other: Self) {
-        for monomial in other.monomials {
-            *self += monomial;
-        }
+    type Output = Polynomial<T>;
+    fn mul(self, other: Self) -> Polynomial<T> {
+        mul_or_panic(self, other)
     }
 }
 
-// Polynomial -= Polynomial
-impl<T> ops::SubAssign for Polynomial<T>
+// Polynomial *= Polynomial
+impl<T> ops::MulAssign for Polynomial<T>
 where
     T: CRing + Clone + PartialEq,
 {
-    fn sub_assign(&mut self, other: Self) {
-        for monomial in other.monomials {
-            *self -= monomial;
-        }
+    fn mul_assign(&mut self, other: Self) {
+        *self = mul_or_panic(self, &other);
     }
 }
 
-// Polynomial + Polynomial
-impl<T> ops::Add for Polynomial<T>
+// Polynomial *= &Polynomial
+impl<T> ops::MulAssign<&Polynomial<T>> for Polynomial<T>
 where
     T: CRing + Clone + PartialEq,
 {
-    type Output = Self;
-    fn add(self, other: Self) -> Self {
-        let mut new_poly = self.clone();
-        new_poly += other;
-        new_poly
+    fn mul_assign(&mut self, other: &Self) {
+        *self = mul_or_panic(self, other);
     }
 }
 
-//Polynomial - Polynomial
-impl<T> ops::Sub for Polynomial<T>
+// Polynomial * T
+impl<T> ops::Mul<T> for Polynomial<T>
 where
-    T: CRing + Clone + PartialEq,
+    T: CRing + Clone,
 {
     type Output = Self;
-    fn sub(self, other: Self) -> Self {
-        let mut new_poly = self.clone();
-        new_poly -= other;
-        new_poly
+
+    fn mul(mut self, other: T) -> Self {
+        self *= other;
+        self
     }
 }
 
-// Polynomial + &Polynomial
-// impl<T> ops::Add<&Polynomial<T>> for Polynomial<T>
-// where T:CRing {
-//     type Output = Self;
-//     fn add(self, other: &Self) -> Self {
-//         let mut new_poly = self.clone();
-//         new_poly += other.clone();
-//         new_poly
-//     }
-// }
+// &Polynomial * T
+impl<T> ops::Mul<T> for &Polynomial<T>
+where
+    T: CRing + Clone,
+{
+    type Output = Polynomial<T>;
 
-// &Polynomial + Polynomial
-// impl<T> ops::Add<Polynomial<T>> for &Polynomial<T>
-// where T: CRing
-// {
-//     type Output = Polynomial<T>;
-//     fn add(self, other: Polynomial<T>) -> Polynomial<T> {
-//         let mut new_poly = self.clone();
-//         new_poly += other.clone();
-//         new_poly
-//     }
-// }
+    fn mul(self, other: T) -> Polynomial<T> {
+        let monomials = self
+            .monomials
+            .iter()
+            .map(|monomial| monomial * other.clone())
+            .filter(|monomial| !monomial.coefficient.is_zero())
+            .collect();
+        Polynomial { monomials }
+    }
+}
 
-// &Polynomial + &Polynomial
-// impl<'a> ops::Add<&'a Polynomial> for &'a Polynomial<T> {
-//     type Output = Polynomial<T>;
-//     fn add(self, other: Self) -> Polynomial<T> {
-//         let mut new_poly = self.clone();
-//         new_poly += other.clone();
-//         new_poly
-//     }
-// }
+// Polynomial *= T
+impl<T> ops::MulAssign<T> for Polynomial<T>
+where
+    T: CRing + Clone,
+{
+    fn mul_assign(&mut self, other: T) {
+        for monomial in self.monomials.iter_mut() {
+            let coefficient = std::mem::replace(&mut monomial.coefficient, T::zero());
+            monomial.coefficient = other.clone() * coefficient;
+        }
+        self.monomials
+            .retain(|monomial| !monomial.coefficient.is_zero());
+    }
+}
 
-// Polynomial * Polynomial
-impl<T> ops::Mul for Polynomial<T>
+impl<T> Sum for Polynomial<T>
 where
     T: CRing + Clone + PartialEq,
 {
-    type Output = Self;
-    fn mul(self, other: Self) -> Self {
-        let mut polynomial = Polynomial::new();
-        for monomial in self.monomials.iter() {
-            for other_monomial in other.monomials.iter() {
-                polynomial += monomial.clone() * other_monomial.clone();
-            }
-        }
-        polynomial
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Polynomial::new(), |acc, polynomial| acc + polynomial)
     }
 }
 
-// Polynomial * &Polynomial
-impl<T> ops::Mul<&Polynomial<T>> for Polynomial<T>
+impl<'a, T> Sum<&'a Polynomial<T>> for Polynomial<T>
 where
     T: CRing + Clone + PartialEq,
 {
-    type Output = Self;
-    fn mul(self, other: &Self) -> Self {
-        let mut polynomial = Polynomial::new();
-        for monomial in self.monomials.iter() {
-            for other_monomial in other.monomials.iter() {
-                polynomial += monomial.clone() * other_monomial.clone();
-            }
-        }
-        polynomial
+    fn sum<I: Iterator<Item = &'a Polynomial<T>>>(iter: I) -> Self {
+        iter.fold(Polynomial::new(), |acc, polynomial| acc + polynomial)
     }
 }
 
-// Polynomial * T
-impl<T> ops::Mul<T> for Polynomial<T>
+impl<T> Product for Polynomial<T>
 where
-    T: CRing + Clone,
+    T: CRing + Clone + PartialEq,
 {
-    type Output = Self;
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Polynomial::one(), |acc, polynomial| acc * polynomial)
+    }
+}
 
-    fn mul(self, other: T) -> Self {
-        let mut polynomial = self.clone();
-        for monomial in polynomial.monomials.iter_mut() {
-            monomial.coefficient = other.clone() * monomial.coefficient.clone();
-        }
-        polynomial
+impl<'a, T> Product<&'a Polynomial<T>> for Polynomial<T>
+where
+    T: CRing + Clone + PartialEq,
+{
+    fn product<I: Iterator<Item = &'a Polynomial<T>>>(iter: I) -> Self {
+        iter.fold(Polynomial::one(), |acc, polynomial| acc * polynomial)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::exponents;
     use crate::monomial::Monomial64;
     use rstest::*;
-    use smallvec::smallvec;
 
     #[fixture]
     fn polynomial_a() -> Polynomial64 {
         let monomial_a = Monomial {
             coefficient: 5.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 6.0,
-            power_list: smallvec![0, 2, 0],
+            power_list: exponents![0, 2, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
@@ -326,7 +1211,7 @@ mod tests {
     fn polynomial_b() -> Polynomial64 {
         let monomial_a = Monomial {
             coefficient: 7.0,
-            power_list: smallvec![0, 2, 0],
+            power_list: exponents![0, 2, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
@@ -337,11 +1222,11 @@ mod tests {
     fn linear_polynomial() -> Polynomial64 {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 0, 0],
+            power_list: exponents![1, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 2.0,
-            power_list: smallvec![0, 0, 0],
+            power_list: exponents![0, 0, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
@@ -353,15 +1238,15 @@ mod tests {
     fn polynomial_c() -> Polynomial64 {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![4, 0, 0],
+            power_list: exponents![4, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![3, 0, 0],
+            power_list: exponents![3, 0, 0],
         };
         let monomial_c = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
@@ -374,15 +1259,15 @@ mod tests {
     fn polynomial_d() -> Polynomial64 {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![4, 0, 0],
+            power_list: exponents![4, 0, 0],
         };
         let monomial_c = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![1, 0, 0],
+            power_list: exponents![1, 0, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
@@ -395,7 +1280,7 @@ mod tests {
     fn polynomial_e() -> Polynomial64 {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
@@ -406,50 +1291,50 @@ mod tests {
     fn test_insert_monomial() {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
         assert_eq!(polynomial.monomials[0].coefficient, 1.0);
-        assert_eq!(polynomial.monomials[0].power_list[0], 2);
+        assert_eq!(polynomial.monomials[0].power(0), 2);
     }
 
     #[rstest]
     fn test_insert_monomial_exists_with_diff_power_list() {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 3.0,
-            power_list: smallvec![1, 1, 1],
+            power_list: exponents![1, 1, 1],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
         polynomial.insert_monomial(monomial_b);
         assert_eq!(polynomial.monomials[0].coefficient, 3.0);
-        assert_eq!(polynomial.monomials[0].power_list[0], 1);
-        assert_eq!(polynomial.monomials[0].power_list[1], 1);
-        assert_eq!(polynomial.monomials[0].power_list[2], 1);
+        assert_eq!(polynomial.monomials[0].power(0), 1);
+        assert_eq!(polynomial.monomials[0].power(1), 1);
+        assert_eq!(polynomial.monomials[0].power(2), 1);
     }
 
     #[rstest]
     fn test_insert_monomial_exists_with_same_power_list() {
         let monomial_a = Monomial {
             coefficient: 1.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 3.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let mut polynomial: Polynomial<f64> = Polynomial::new();
         polynomial.insert_monomial(monomial_a);
         polynomial.insert_monomial(monomial_b);
         assert_eq!(polynomial.monomials[0].coefficient, 3.0);
-        assert_eq!(polynomial.monomials[0].power_list[0], 2);
-        assert_eq!(polynomial.monomials[0].power_list[1], 0);
-        assert_eq!(polynomial.monomials[0].power_list[2], 0);
+        assert_eq!(polynomial.monomials[0].power(0), 2);
+        assert_eq!(polynomial.monomials[0].power(1), 0);
+        assert_eq!(polynomial.monomials[0].power(2), 0);
     }
 
     #[rstest]
@@ -457,11 +1342,11 @@ mod tests {
         // 5x^2 + 6y^2
         let monomial_a = Monomial {
             coefficient: 5.0,
-            power_list: smallvec![2, 0, 0],
+            power_list: exponents![2, 0, 0],
         };
         let monomial_b = Monomial {
             coefficient: 6.0,
-            power_list: smallvec![0, 2, 0],
+            power_list: exponents![0, 2, 0],
         };
         assert_eq!(polynomial_a.monomials[0], monomial_a);
         assert_eq!(polynomial_a.monomials[1], monomial_b);
@@ -472,7 +1357,7 @@ mod tests {
         // 7y^2
         let monomial = Monomial {
             coefficient: 7.0,
-            power_list: smallvec![0, 2, 0],
+            power_list: exponents![0, 2, 0],
         };
         assert_eq!(polynomial_b.monomials[0], monomial);
     }
@@ -555,6 +1440,17 @@ mod tests {
         assert_eq!(format!("{}", polynomial.monomials[3]), "x");
     }
 
+    #[rstest]
+    fn test_addition_interleaved_terms() {
+        let mut polynomial = Polynomial64::from("x^5 + x^3 + x").unwrap();
+        polynomial += Polynomial64::from("x^4 + x^3 + x^2 + 1").unwrap();
+        assert_eq!(polynomial.monomials.len(), 6);
+        assert_eq!(format!("{}", polynomial), "x^5 + x^4 + 2x^3 + x^2 + x + 1");
+
+        polynomial -= Polynomial64::from("x^6 + x^4 + 1").unwrap();
+        assert_eq!(format!("{}", polynomial), "-x^6 + x^5 + 2x^3 + x^2 + x");
+    }
+
     #[rstest]
     fn test_subtraction_1(polynomial_a: Polynomial64, polynomial_b: Polynomial64) {
         let polynomial = polynomial_a - polynomial_b;
@@ -576,7 +1472,8 @@ mod tests {
         let other = polynomial_e;
         polynomial -= other;
 
-        assert_eq!(polynomial.monomials.len(), 3);
+        // the cancelled x^2 term is dropped
+        assert_eq!(polynomial.monomials.len(), 2);
         assert_eq!(format!("{}", polynomial), "x^4 + x^3");
         assert_eq!(polynomial.monomials[0].coefficient, 1.0);
         assert_eq!(polynomial.monomials[1].coefficient, 1.0);
@@ -593,12 +1490,12 @@ mod tests {
         let other = polynomial_d.clone();
         polynomial -= other;
 
-        assert_eq!(polynomial.monomials.len(), 4);
+        assert_eq!(polynomial.monomials.len(), 2);
         assert_eq!(format!("{}", polynomial), "x^3 - x");
-        assert_eq!(polynomial.monomials[1].coefficient, 1.0);
-        assert_eq!(polynomial.monomials[3].coefficient, -1.0);
-        assert_eq!(format!("{}", polynomial.monomials[1]), "x^3");
-        assert_eq!(format!("{}", polynomial.monomials[3]), "-x");
+        assert_eq!(polynomial.monomials[0].coefficient, 1.0);
+        assert_eq!(polynomial.monomials[1].coefficient, -1.0);
+        assert_eq!(format!("{}", polynomial.monomials[0]), "x^3");
+        assert_eq!(format!("{}", polynomial.monomials[1]), "-x");
     }
 
     #[rstest]
@@ -660,14 +1557,14 @@ mod tests {
             polynomial.monomials[0],
             Monomial {
                 coefficient: 35.0,
-                power_list: smallvec![2, 2, 0],
+                power_list: exponents![2, 2, 0],
             }
         );
         assert_eq!(
             polynomial.monomials[1],
             Monomial {
                 coefficient: 42.0,
-                power_list: smallvec![0, 4, 0],
+                power_list: exponents![0, 4, 0],
             }
         );
         assert_eq!(format!("{}", polynomial), "35x^2y^2 + 42y^4");
@@ -675,6 +1572,60 @@ mod tests {
         assert_eq!(format!("{}", polynomial.monomials[1]), "42y^4");
     }
 
+    #[rstest]
+    fn test_mul_cancellation() {
+        let lhs = Polynomial64::from("x - y").unwrap();
+        let rhs = Polynomial64::from("x + y").unwrap();
+        let polynomial = lhs * rhs;
+        assert_eq!(polynomial.monomials.len(), 2);
+        assert_eq!(format!("{}", polynomial), "x^2 - y^2");
+    }
+
+    #[rstest]
+    fn test_mul_agrees_with_schoolbook() {
+        let lhs = Polynomial64::from("x + y + z + u + 1")
+            .unwrap()
+            .pow(6)
+            .unwrap();
+        let rhs = Polynomial64::from("x - y + 2z - u + 3")
+            .unwrap()
+            .pow(5)
+            .unwrap();
+        let mut expected = Polynomial64::new();
+        for monomial in lhs.monomials.iter() {
+            for other_monomial in rhs.monomials.iter() {
+                expected += monomial * other_monomial;
+            }
+        }
+        let polynomial = lhs * &rhs;
+        assert_eq!(format!("{}", polynomial), format!("{}", expected));
+        // the output must already be in order
+        for ind in 1..polynomial.monomials.len() {
+            assert!(polynomial.monomials[ind - 1] < polynomial.monomials[ind]);
+        }
+    }
+
+    #[rstest]
+    fn test_mul_many_terms() {
+        let polynomial = Polynomial64::from("x + y + z + u + v + 1")
+            .unwrap()
+            .pow(5)
+            .unwrap();
+        let square = polynomial.clone() * &polynomial;
+        assert_eq!(polynomial.monomials.len(), 252);
+        assert_eq!(square.monomials.len(), 3003);
+        assert_eq!(
+            format!("{}", square),
+            format!(
+                "{}",
+                Polynomial64::from("x + y + z + u + v + 1")
+                    .unwrap()
+                    .pow(10)
+                    .unwrap()
+            )
+        );
+    }
+
     #[rstest]
     fn test_mul_polynomial_by_scalar(polynomial_a: Polynomial64) {
         let polynomial = polynomial_a * 4.0;
@@ -683,21 +1634,472 @@ mod tests {
         assert_eq!(format!("{}", polynomial.monomials[1]), "24y^2");
     }
 
+    #[rstest]
+    fn test_cancellation_is_pruned(polynomial_a: Polynomial64) {
+        let difference = &polynomial_a - &polynomial_a;
+        assert!(difference.is_zero());
+        assert!(difference.monomials.is_empty());
+        assert_eq!(format!("{}", difference), "0");
+
+        let mut polynomial = Polynomial64::from("x + y").unwrap();
+        polynomial -= Monomial64::from("y").unwrap();
+        assert_eq!(polynomial.monomials.len(), 1);
+        polynomial += Monomial {
+            coefficient: 0.0,
+            power_list: exponents![0, 0, 3],
+        };
+        assert_eq!(polynomial.monomials.len(), 1);
+        assert!((polynomial * 0.0).is_zero());
+    }
+
+    #[rstest]
+    fn test_normalize() {
+        let mut polynomial = Polynomial {
+            monomials: vec![
+                Monomial {
+                    coefficient: 2,
+                    power_list: exponents![0, 1],
+                },
+                Monomial {
+                    coefficient: 3,
+                    power_list: exponents![2],
+                },
+                Monomial {
+                    coefficient: -2,
+                    power_list: exponents![0, 1],
+                },
+                Monomial {
+                    coefficient: 1,
+                    power_list: exponents![2],
+                },
+                Monomial {
+                    coefficient: 0,
+                    power_list: exponents![1],
+                },
+            ],
+        };
+        polynomial.normalize();
+        assert_eq!(polynomial.monomials.len(), 1);
+        assert_eq!(format!("{}", polynomial), "4x^2");
+    }
+
+    #[rstest]
+    fn test_equality(polynomial_a: Polynomial64, polynomial_b: Polynomial64) {
+        let sum = &polynomial_a + &polynomial_b;
+        assert_eq!(sum, &polynomial_b + &polynomial_a);
+        assert_ne!(sum, polynomial_a);
+        // same terms, different coefficients
+        assert_ne!(polynomial_a, &polynomial_a * 2.0);
+        assert_eq!(&sum - &polynomial_b, polynomial_a);
+    }
+
+    #[rstest]
+    fn test_hash() {
+        use std::collections::HashSet;
+        let mut set = HashSet::new();
+        let lhs = Polynomial {
+            monomials: vec![Monomial {
+                coefficient: 1,
+                power_list: exponents![1],
+            }],
+        };
+        let rhs = Polynomial {
+            monomials: vec![Monomial {
+                coefficient: 1,
+                power_list: exponents![0, 1],
+            }],
+        };
+        set.insert(&lhs * &rhs);
+        set.insert(&rhs * &lhs);
+        set.insert(&lhs + &rhs);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[rstest]
+    fn test_approx_eq() {
+        let lhs = Polynomial64::from("0.1x + 0.2y").unwrap();
+        let rhs = Polynomial64::from("x + y").unwrap() * 0.1 + Polynomial64::from("0.1y").unwrap();
+        assert!(lhs.approx_eq(&rhs, 1e-12));
+        assert!(!lhs.approx_eq(&rhs, -1.0));
+        let extra = &rhs + &Polynomial64::from("0.000001z").unwrap();
+        assert!(!lhs.approx_eq(&extra, 1e-9));
+        assert!(lhs.approx_eq(&extra, 1e-3));
+    }
+
+    #[rstest]
+    fn test_polynomial_is_cring() {
+        fn square<R: CRing + Clone>(value: R) -> R {
+            value.clone() * value
+        }
+        let polynomial = Polynomial64::from("x + 1").unwrap();
+        assert_eq!(
+            square(polynomial),
+            Polynomial64::from("x^2 + 2x + 1").unwrap()
+        );
+        assert!(Polynomial64::zero().is_zero());
+        assert_eq!(format!("{}", Polynomial64::one()), "1");
+        assert_eq!(format!("{}", -Polynomial64::one()), "-1");
+    }
+
+    #[rstest]
+    fn test_polynomial_is_integral_domain() {
+        fn cancel<R: IntegralDomain + PartialEq>(a: R, b: R) -> bool {
+            (a * b).is_zero()
+        }
+        let polynomial = Polynomial {
+            monomials: vec![Monomial {
+                coefficient: 3i64,
+                power_list: exponents![1],
+            }],
+        };
+        assert!(!cancel(polynomial.clone(), polynomial.clone()));
+        assert!(cancel(polynomial, Polynomial::zero()));
+    }
+
+    #[rstest]
+    fn test_to_recursive() {
+        let polynomial = Polynomial64::from("x^2y + x^2z + 3x + y^2").unwrap();
+        let recursive = polynomial.to_recursive(0);
+        assert_eq!(recursive.monomials.len(), 3);
+        assert_eq!(format!("{}", recursive), "(y + z)x^2 + 3x + y^2");
+
+        let recursive = polynomial.to_recursive(1);
+        assert_eq!(format!("{}", recursive), "y^2 + x^2y + x^2z + 3x");
+        assert_eq!(Polynomial::from_recursive(&recursive), polynomial);
+
+        // a coefficient takes its sign from its leading term
+        let recursive = (-polynomial).to_recursive(0);
+        assert_eq!(format!("{}", recursive), "-(y + z)x^2 - 3x - y^2");
+    }
+
+    #[rstest]
+    fn test_recursive_arithmetic() {
+        let lhs = Polynomial64::from("xy + x + z").unwrap();
+        let rhs = Polynomial64::from("x - y^2").unwrap();
+        let product = lhs.to_recursive(0) * rhs.to_recursive(0);
+        assert_eq!(product, (&lhs * &rhs).to_recursive(0));
+        assert_eq!(Polynomial::from_recursive(&product), &lhs * &rhs);
+
+        let power = lhs.to_recursive(0).pow(3).unwrap();
+        assert_eq!(Polynomial::from_recursive(&power), lhs.pow(3).unwrap());
+
+        let difference = lhs.to_recursive(0) - lhs.to_recursive(0);
+        assert!(difference.is_zero());
+    }
+
+    #[rstest]
+    fn test_pow_exponent_overflow() {
+        let polynomial = Polynomial64::from("x^100000 + y").unwrap();
+        match polynomial.pow(100000) {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert!(matches!(e, PolynomialErr::ExponentOverflow(_))),
+        }
+        // too many terms for the multinomial expansion
+        let polynomial = Polynomial64::from("x^100000 + y + z + u + v + w").unwrap();
+        match polynomial.pow(100000) {
+            Ok(v) => panic!("{}", v),
+            Err(e) => assert!(matches!(e, PolynomialErr::ExponentOverflow(_))),
+        }
+    }
+
+    #[rstest]
+    fn test_pow_with_limits() {
+        let polynomial = Polynomial64::from("x + y + z").unwrap();
+        let limits = Limits {
+            max_degree: 10,
+            max_terms: usize::MAX,
+        };
+        assert!(polynomial.pow_with_limits(10, &limits).is_ok());
+        assert!(matches!(
+            polynomial.pow_with_limits(11, &limits),
+            Err(PolynomialErr::DegreeLimit(_))
+        ));
+
+        // (x + y + z)^4 has 15 terms
+        let limits = Limits {
+            max_degree: i32::MAX,
+            max_terms: 14,
+        };
+        assert!(matches!(
+            polynomial.pow_with_limits(4, &limits),
+            Err(PolynomialErr::TermLimit(_))
+        ));
+        let polynomial = Polynomial64::from("x + y + z + u + v").unwrap();
+        assert!(matches!(
+            polynomial.pow_with_limits(2, &limits),
+            Err(PolynomialErr::TermLimit(_))
+        ));
+    }
+
+    #[rstest]
+    fn test_checked_mul() {
+        let lhs = Polynomial64::from("x^2000000000 + y").unwrap();
+        let rhs = Polynomial64::from("x^100000000 + 1").unwrap();
+        assert!(lhs.checked_mul(&rhs, &Limits::unbounded()).is_ok());
+        assert!(matches!(
+            lhs.checked_mul(&lhs, &Limits::unbounded()),
+            Err(PolynomialErr::ExponentOverflow(_))
+        ));
+
+        let lhs = Polynomial64::from("x + y").unwrap();
+        let rhs = Polynomial64::from("x - y").unwrap();
+        // cancellation keeps the result within the limit
+        let limits = Limits {
+            max_degree: 2,
+            max_terms: 2,
+        };
+        assert_eq!(
+            lhs.checked_mul(&rhs, &limits).unwrap(),
+            Polynomial64::from("x^2 - y^2").unwrap()
+        );
+        assert!(matches!(
+            lhs.checked_mul(&lhs, &limits),
+            Err(PolynomialErr::TermLimit(_))
+        ));
+    }
+
+    #[rstest]
+    #[should_panic(expected = "ExponentOverflow")]
+    fn test_mul_overflow_panics() {
+        let polynomial = Polynomial64::from("x^2000000000").unwrap();
+        let _ = &polynomial * &polynomial;
+    }
+
+    #[rstest]
+    fn test_pow_big_integer() {
+        // C(100, 50) overflows i64
+        let polynomial = PolynomialZ::from("x + y").unwrap().pow(100).unwrap();
+        assert_eq!(polynomial.monomials.len(), 101);
+        assert_eq!(
+            polynomial.monomials[50].coefficient,
+            "100891344545564193334812497256".parse::<BigInt>().unwrap()
+        );
+        let mut product = PolynomialZ::one();
+        for _ in 0..100 {
+            product *= PolynomialZ::from("x + y").unwrap();
+        }
+        assert_eq!(product, polynomial);
+    }
+
+    #[rstest]
+    fn test_rational_arithmetic() {
+        let lhs = PolynomialQ::from("0.5x + 0.25").unwrap();
+        let rhs = PolynomialQ::from("2x - 0.5").unwrap();
+        assert_eq!(format!("{}", &lhs * &rhs), "x^2 + (1/4)x - 1/8");
+        assert_eq!(
+            format!("{}", lhs.pow(3).unwrap()),
+            "(1/8)x^3 + (3/16)x^2 + (3/32)x + 1/64"
+        );
+    }
+
+    #[rstest]
+    fn test_borrowed_operands(polynomial_a: Polynomial64, polynomial_c: Polynomial64) {
+        let sum = format!("{}", polynomial_a.clone() + polynomial_c.clone());
+        assert_eq!(format!("{}", &polynomial_a + &polynomial_c), sum);
+        assert_eq!(format!("{}", polynomial_a.clone() + &polynomial_c), sum);
+        assert_eq!(format!("{}", &polynomial_a + polynomial_c.clone()), sum);
+
+        let difference = format!("{}", polynomial_a.clone() - polynomial_c.clone());
+        assert_eq!(format!("{}", &polynomial_a - &polynomial_c), difference);
+        assert_eq!(
+            format!("{}", polynomial_a.clone() - &polynomial_c),
+            difference
+        );
+        assert_eq!(
+            format!("{}", &polynomial_a - polynomial_c.clone()),
+            difference
+        );
+
+        let product = format!("{}", polynomial_a.clone() * polynomial_c.clone());
+        assert_eq!(format!("{}", &polynomial_a * &polynomial_c), product);
+        assert_eq!(format!("{}", polynomial_a.clone() * &polynomial_c), product);
+        assert_eq!(format!("{}", &polynomial_a * polynomial_c.clone()), product);
+        assert_eq!(format!("{}", &polynomial_a * 4.0), "20x^2 + 24y^2");
+    }
+
+    #[rstest]
+    fn test_assign_borrowed(polynomial_a: Polynomial64, polynomial_b: Polynomial64) {
+        let mut polynomial = polynomial_a.clone();
+        polynomial += &polynomial_b;
+        assert_eq!(format!("{}", polynomial), "5x^2 + 13y^2");
+        polynomial -= &polynomial_b;
+        assert_eq!(format!("{}", polynomial), "5x^2 + 6y^2");
+        polynomial *= &polynomial_b;
+        assert_eq!(format!("{}", polynomial), "35x^2y^2 + 42y^4");
+        polynomial *= Polynomial64::from("2").unwrap();
+        polynomial *= 0.5;
+        assert_eq!(format!("{}", polynomial), "35x^2y^2 + 42y^4");
+    }
+
+    #[rstest]
+    fn test_neg(polynomial_a: Polynomial64) {
+        assert_eq!(format!("{}", -&polynomial_a), "-5x^2 - 6y^2");
+        assert_eq!(format!("{}", -polynomial_a), "-5x^2 - 6y^2");
+    }
+
+    #[rstest]
+    fn test_sum_and_product() {
+        let factors = vec![
+            Polynomial64::from("x + 1").unwrap(),
+            Polynomial64::from("x - 1").unwrap(),
+            Polynomial64::from("y").unwrap(),
+        ];
+        assert_eq!(
+            format!("{}", factors.iter().sum::<Polynomial64>()),
+            "2x + y"
+        );
+        assert_eq!(
+            format!("{}", factors.iter().product::<Polynomial64>()),
+            "x^2y - y"
+        );
+        assert_eq!(
+            format!("{}", factors.into_iter().product::<Polynomial64>()),
+            "x^2y - y"
+        );
+        let empty: Vec<Polynomial64> = Vec::new();
+        assert_eq!(format!("{}", empty.iter().product::<Polynomial64>()), "1");
+        assert!(empty.into_iter().sum::<Polynomial64>().monomials.is_empty());
+    }
+
     #[rstest]
     fn test_pow_1(linear_polynomial: Polynomial64) {
-        assert_eq!(format!("{}", linear_polynomial.pow(1)), "x + 2");
+        assert_eq!(format!("{}", linear_polynomial.pow(1).unwrap()), "x + 2");
     }
 
     #[rstest]
     fn test_pow_2(linear_polynomial: Polynomial64) {
-        assert_eq!(format!("{}", linear_polynomial.pow(2)), "x^2 + 4x + 4");
+        assert_eq!(
+            format!("{}", linear_polynomial.pow(2).unwrap()),
+            "x^2 + 4x + 4"
+        );
     }
 
     #[rstest]
     fn test_pow_3(linear_polynomial: Polynomial64) {
         assert_eq!(
-            format!("{}", linear_polynomial.pow(3)),
+            format!("{}", linear_polynomial.pow(3).unwrap()),
             "x^3 + 6x^2 + 12x + 8"
         );
     }
+
+    #[rstest]
+    fn test_pow_0(linear_polynomial: Polynomial64) {
+        assert_eq!(format!("{}", linear_polynomial.pow(0).unwrap()), "1");
+        assert_eq!(format!("{}", Polynomial64::new().pow(0).unwrap()), "1");
+    }
+
+    #[rstest]
+    fn test_pow_zero_polynomial() {
+        let polynomial = Polynomial64::new().pow(3).unwrap();
+        assert_eq!(polynomial.monomials.len(), 0);
+    }
+
+    #[rstest]
+    fn test_pow_negative(linear_polynomial: Polynomial64) {
+        match linear_polynomial.pow(-2) {
+            Ok(v) => panic!("{:?}", v),
+            Err(e) => assert_eq!(
+                e,
+                PolynomialErr::NegativeExponent(String::from(
+                    "Cannot raise polynomial to negative power -2"
+                ))
+            ),
+        }
+    }
+
+    #[rstest]
+    fn test_pow_monomial() {
+        let polynomial = Polynomial64::from("2xy^2").unwrap();
+        assert_eq!(format!("{}", polynomial.pow(5).unwrap()), "32x^5y^10");
+    }
+
+    #[rstest]
+    fn test_pow_like_products() {
+        // x^2 arises from both x*x and 1*x^2
+        let polynomial = Polynomial64::from("1 + x + x^2").unwrap();
+        assert_eq!(
+            format!("{}", polynomial.pow(2).unwrap()),
+            "x^4 + 2x^3 + 3x^2 + 2x + 1"
+        );
+    }
+
+    #[rstest]
+    fn test_pow_binary_agrees_with_multinomial() {
+        // five terms takes the repeated squaring path
+        let polynomial = Polynomial64::from("x + y + z + u + 1").unwrap();
+        let mut expected = polynomial.clone();
+        for _ in 1..6 {
+            expected *= &polynomial;
+        }
+        assert_eq!(
+            format!("{}", polynomial.pow(6).unwrap()),
+            format!("{}", expected)
+        );
+
+        let polynomial = Polynomial64::from("x + 2y - 3").unwrap();
+        assert_eq!(
+            format!("{}", polynomial.pow(7).unwrap()),
+            format!(
+                "{}",
+                polynomial.binary_pow(7, &Limits::unbounded()).unwrap()
+            )
+        );
+    }
+
+    #[rstest]
+    fn test_pow_many_terms() {
+        let polynomial = Polynomial64::from("x + y + z + 1")
+            .unwrap()
+            .pow(100)
+            .unwrap();
+        assert_eq!(polynomial.monomials.len(), 176851);
+        assert_eq!(polynomial.monomials[0], Monomial64::from("x^100").unwrap());
+    }
+
+    #[rstest]
+    fn test_pow_falls_back_beyond_term_limit() {
+        // (1 + x + x^2)^2 expands to 6 terms that sum to 5, too many for the
+        // expansion but not for repeated squaring, while (x + x^2)^10 has
+        // 11 distinct terms
+        let limits = Limits {
+            max_degree: i32::MAX,
+            max_terms: 5,
+        };
+        let polynomial = Polynomial64::from("1 + x + x^2").unwrap();
+        assert_eq!(
+            format!("{}", polynomial.pow_with_limits(2, &limits).unwrap()),
+            "x^4 + 2x^3 + 3x^2 + 2x + 1"
+        );
+        let polynomial = Polynomial64::from("x + x^2").unwrap();
+        assert!(matches!(
+            polynomial.pow_with_limits(10, &limits),
+            Err(PolynomialErr::TermLimit(_))
+        ));
+    }
+
+    #[rstest]
+    fn test_binomial_table() {
+        let table = binomial_table::<i64>(4, true);
+        assert_eq!(table[3], vec![1, 3, 3, 1]);
+        assert_eq!(table[4], vec![1, 4, 6, 4, 1]);
+        let table = binomial_table::<i64>(4, false);
+        assert!(table[..4].iter().all(|row| row.is_empty()));
+        assert_eq!(table[4], vec![1, 4, 6, 4, 1]);
+    }
+
+    #[rstest]
+    #[case(2, 10, 11)]
+    #[case(4, 100, 176851)]
+    #[case(1, 7, 1)]
+    #[case(4, usize::MAX, u128::MAX)]
+    fn test_multinomial_terms(#[case] k: usize, #[case] n: usize, #[case] expected: u128) {
+        assert_eq!(multinomial_terms(k, n), expected);
+    }
+
+    #[rstest]
+    fn test_pow_large_exponent() {
+        let polynomial = Polynomial64::from("x + 1").unwrap().pow(20).unwrap();
+        assert_eq!(polynomial.monomials.len(), 21);
+        assert_eq!(polynomial.monomials[10].coefficient, 184756.0);
+    }
 }
@@ -0,0 +1,1002 @@
+use crate::exponents::Exponents;
+use crate::lexer::greek_letter_name;
+use crate::modint::ModInt;
+use crate::monomial::{variable_symbol, Monomial};
+use crate::polynomial::Polynomial;
+use crate::CRing;
+use log::error;
+use num::rational::Ratio;
+use num::{BigInt, Complex, Integer, Num, Zero};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+// The notations polynomials can be written out in, Ascii being Display.
+//
+//   Ascii     2x^2y^3 - (1/2)z
+//   Unicode   2x²y³ − (1/2)z
+//   Latex     2x^{2}y^{3} - \frac{1}{2}z
+//   MathMl    <math ...><mrow><mn>2</mn><msup><mi>x</mi><mn>2</mn></msup>...
+//
+// and the input syntax of computer algebra systems, which Syntax::Cas reads
+// back. Fractions stay exact, as SymPy's sympify, Maple and Sage read 1/2 as
+// a rational.
+//
+//   SymPy        2*x**2*y**3 - 1/2*z
+//   Mathematica  2*x^2*y^3 - 1/2*z
+//   Maple        2*x^2*y^3 - 1/2*z
+//   Sage         2*x^2*y^3 - 1/2*z
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Notation {
+    #[default]
+    Ascii,
+    Unicode,
+    Latex,
+    MathMl,
+    SymPy,
+    Mathematica,
+    Maple,
+    Sage,
+}
+
+impl Notation {
+    // The notations of computer algebra systems, with explicit products.
+    fn is_cas(&self) -> bool {
+        matches!(
+            self,
+            Notation::SymPy | Notation::Mathematica | Notation::Maple | Notation::Sage
+        )
+    }
+
+    // The variable names a computer algebra system reads as something else:
+    // keywords such as Python's lambda, and its constants and functions.
+    pub fn reserved_names(&self) -> &'static [&'static str] {
+        match self {
+            Notation::SymPy => &["lambda", "pi", "gamma", "beta", "zeta"],
+            Notation::Mathematica => &["π"],
+            Notation::Maple => &["pi", "gamma"],
+            Notation::Sage => &[
+                "lambda", "pi", "gamma", "beta", "zeta", "eta", "psi", "sigma",
+            ],
+            _ => &[],
+        }
+    }
+}
+
+impl FromStr for Notation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ascii" => Ok(Notation::Ascii),
+            "unicode" => Ok(Notation::Unicode),
+            "latex" => Ok(Notation::Latex),
+            "mathml" => Ok(Notation::MathMl),
+            "sympy" => Ok(Notation::SymPy),
+            "mathematica" => Ok(Notation::Mathematica),
+            "maple" => Ok(Notation::Maple),
+            "sage" => Ok(Notation::Sage),
+            _ => {
+                let msg = format!(
+                    "Unknown notation {:?}, expected ascii, unicode, latex, mathml, sympy, mathematica, maple or sage",
+                    s
+                );
+                error!("{}", msg);
+                Err(msg)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Notation::Ascii => "ascii",
+            Notation::Unicode => "unicode",
+            Notation::Latex => "latex",
+            Notation::MathMl => "mathml",
+            Notation::SymPy => "sympy",
+            Notation::Mathematica => "mathematica",
+            Notation::Maple => "maple",
+            Notation::Sage => "sage",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// The orders terms can be listed in, each comparing exponent vectors.
+//
+//   Grlex    higher total degree first, ties broken by the power of x, then
+//            y, and so on. Polynomials keep their terms in this order.
+//   Lex      the higher power of x first, then of y, and so on, whatever
+//            the total degree
+//   Grevlex  higher total degree first, ties broken by the lower power of
+//            the last variable that differs
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TermOrder {
+    #[default]
+    Grlex,
+    Lex,
+    Grevlex,
+}
+
+impl TermOrder {
+    pub fn cmp(&self, a: &Exponents, b: &Exponents) -> Ordering {
+        let len = std::cmp::max(a.len(), b.len());
+        let first_difference = |inds: &mut dyn Iterator<Item = usize>| {
+            inds.map(|ind| a.get(ind).cmp(&b.get(ind)))
+                .find(|&order| order != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        };
+        match self {
+            TermOrder::Grlex => a.cmp(b),
+            TermOrder::Lex => first_difference(&mut (0..len)),
+            TermOrder::Grevlex => a
+                .degree()
+                .cmp(&b.degree())
+                .then_with(|| first_difference(&mut (0..len).rev()).reverse()),
+        }
+    }
+}
+
+impl FromStr for TermOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "grlex" => Ok(TermOrder::Grlex),
+            "lex" => Ok(TermOrder::Lex),
+            "grevlex" => Ok(TermOrder::Grevlex),
+            _ => {
+                let msg = format!("Unknown order {:?}, expected grlex, lex or grevlex", s);
+                error!("{}", msg);
+                Err(msg)
+            }
+        }
+    }
+}
+
+impl fmt::Display for TermOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TermOrder::Grlex => "grlex",
+            TermOrder::Lex => "lex",
+            TermOrder::Grevlex => "grevlex",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// How render_with writes polynomials. The default writes them as Display
+// does: in Ascii, from the highest term to the lowest in Grlex, with the
+// coefficients as they print.
+//
+//   ascending   from the lowest term to the highest instead
+//   precision   digits after the point of floating point coefficients, so
+//               0.1 + 0.2 writes as 0.30 rather than 0.30000000000000004
+//   scientific  floating point coefficients with an exponent, as 1.5e-3
+//   group_by    the index of a main variable, whose powers collect the
+//               other variables into coefficients, as in (y + 1)x^2 - x + y
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FormatOptions {
+    pub notation: Notation,
+    pub order: TermOrder,
+    pub ascending: bool,
+    pub precision: Option<usize>,
+    pub scientific: bool,
+    pub group_by: Option<usize>,
+}
+
+// Coefficients written out with the precision and scientific options. Only
+// floating point numbers have digits to round, the others write as they
+// Display.
+pub trait FormatCoefficient: fmt::Display {
+    fn format_coefficient(&self, _options: &FormatOptions) -> String {
+        format!("{}", self)
+    }
+
+    // Whether a term with the coefficient is written with a minus sign and
+    // the negation of the coefficient after it, as -2x is.
+    fn is_negative(&self) -> bool;
+}
+
+macro_rules! impl_float_format_coefficient {
+    ($($t:ty),*) => {$(
+        impl FormatCoefficient for $t {
+            fn format_coefficient(&self, options: &FormatOptions) -> String {
+                match (options.precision, options.scientific) {
+                    (Some(precision), true) => format!("{:.*e}", precision, self),
+                    (None, true) => format!("{:e}", self),
+                    (Some(precision), false) => format!("{:.*}", precision, self),
+                    (None, false) => format!("{}", self),
+                }
+            }
+
+            fn is_negative(&self) -> bool {
+                *self < 0.0
+            }
+        }
+    )*};
+}
+
+impl_float_format_coefficient!(f32, f64);
+
+macro_rules! impl_exact_format_coefficient {
+    ($($t:ty),*) => {$(
+        impl FormatCoefficient for $t {
+            fn is_negative(&self) -> bool {
+                *self < <$t>::zero()
+            }
+        }
+    )*};
+}
+
+impl_exact_format_coefficient!(i8, i16, i32, i64, i128, isize, BigInt);
+
+impl<T> FormatCoefficient for Ratio<T>
+where
+    T: Clone + Integer + fmt::Display,
+{
+    fn is_negative(&self) -> bool {
+        *self < Ratio::zero()
+    }
+}
+
+// Residues have no sign, and write as they are.
+impl<const P: u64> FormatCoefficient for ModInt<P> {
+    fn is_negative(&self) -> bool {
+        false
+    }
+}
+
+// Both parts are written with the options, leaving out a zero part and a
+// unit imaginary coefficient, as 3i, 0.50 or 1.00-i.
+impl<T> FormatCoefficient for Complex<T>
+where
+    T: FormatCoefficient + Clone + Num + PartialOrd,
+{
+    fn format_coefficient(&self, options: &FormatOptions) -> String {
+        let (sign, im) = if self.im < T::zero() {
+            ("-", T::zero() - self.im.clone())
+        } else {
+            ("+", self.im.clone())
+        };
+        let im = if im.is_one() {
+            String::new()
+        } else {
+            im.format_coefficient(options)
+        };
+        if self.im.is_zero() {
+            self.re.format_coefficient(options)
+        } else if self.re.is_zero() {
+            format!("{}{}i", if sign == "-" { sign } else { "" }, im)
+        } else {
+            format!("{}{}{}i", self.re.format_coefficient(options), sign, im)
+        }
+    }
+
+    // Negative only with a single part, as -2 and -3i are. Both parts are
+    // written out together, so neither -1+2i nor -1-2i takes its sign out.
+    fn is_negative(&self) -> bool {
+        let zero = T::zero();
+        (self.re < zero && self.im.is_zero()) || (self.re.is_zero() && self.im < zero)
+    }
+}
+
+// A polynomial coefficient, as of a polynomial in one variable over the
+// others, takes its sign from its leading term, as -(y + z)x^2.
+impl<T> FormatCoefficient for Polynomial<T>
+where
+    T: FormatCoefficient + CRing + Clone + PartialEq,
+{
+    fn format_coefficient(&self, options: &FormatOptions) -> String {
+        self.render_with(*options).to_string()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.monomials
+            .first()
+            .is_some_and(|monomial| monomial.coefficient.is_negative())
+    }
+}
+
+// A sum of terms written out, as returned by the render methods of
+// Polynomial and Monomial.
+pub struct Rendered<'a, T: CRing> {
+    terms: &'a [Monomial<T>],
+    options: FormatOptions,
+    format: fn(&T, &FormatOptions) -> String,
+}
+
+impl<'a, T> Rendered<'a, T>
+where
+    T: FormatCoefficient + CRing,
+{
+    pub fn new(terms: &'a [Monomial<T>], notation: Notation) -> Self {
+        Rendered {
+            terms,
+            options: FormatOptions {
+                notation,
+                ..FormatOptions::default()
+            },
+            format: |coefficient, _| format!("{}", coefficient),
+        }
+    }
+}
+
+impl<'a, T> Rendered<'a, T>
+where
+    T: FormatCoefficient + CRing,
+{
+    pub fn with_options(terms: &'a [Monomial<T>], options: FormatOptions) -> Self {
+        Rendered {
+            terms,
+            options,
+            format: T::format_coefficient,
+        }
+    }
+}
+
+impl<'a, T> fmt::Display for Rendered<'a, T>
+where
+    T: FormatCoefficient + CRing + Clone + PartialEq,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // zero terms only appear in term lists built by hand
+        let terms: Vec<&Monomial<T>> = self
+            .terms
+            .iter()
+            .filter(|monomial| !monomial.coefficient.is_zero())
+            .collect();
+        let output = match self.options.group_by {
+            Some(var) => self.grouped(terms, var),
+            None => self.sum(&self.sorted(terms)),
+        };
+        match self.options.notation {
+            Notation::MathMl => write!(
+                f,
+                "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow>{}</mrow></math>",
+                output
+            ),
+            _ => write!(f, "{}", output),
+        }
+    }
+}
+
+impl<'a, T> Rendered<'a, T>
+where
+    T: FormatCoefficient + CRing + Clone + PartialEq,
+{
+    // The terms in the order of the options.
+    fn sorted<'b>(&self, mut terms: Vec<&'b Monomial<T>>) -> Vec<&'b Monomial<T>> {
+        terms.sort_by(|a, b| {
+            let order = self.options.order.cmp(&b.power_list, &a.power_list);
+            if self.options.ascending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        terms
+    }
+
+    // The terms as a sum, 0 when there are none.
+    fn sum(&self, terms: &[&Monomial<T>]) -> String {
+        let mut output = String::new();
+        for monomial in terms {
+            self.push_term(&mut output, monomial, None);
+        }
+        if output.is_empty() {
+            output = self.coefficient("0");
+        }
+        output
+    }
+
+    // The terms collected by the powers of the main variable, as in
+    // (y + 1)x^2 - x + y. A coefficient of several terms is parenthesized,
+    // with its sign taken out when its leading term is negative.
+    fn grouped(&self, terms: Vec<&Monomial<T>>, var: usize) -> String {
+        let polynomial = Polynomial {
+            monomials: terms.into_iter().cloned().collect(),
+        };
+        let recursive = polynomial.to_recursive(var);
+        let mut groups: Vec<&Monomial<Polynomial<T>>> = recursive.monomials.iter().collect();
+        groups.sort_by_key(|group| group.power(var));
+        if !self.options.ascending {
+            groups.reverse();
+        }
+        let mut output = String::new();
+        for group in groups {
+            let power = group.power(var);
+            let inner = self.sorted(group.coefficient.monomials.iter().collect());
+            if power == 0 || inner.len() == 1 {
+                for monomial in inner {
+                    let mut term = monomial.clone();
+                    term.power_list.set(var, power);
+                    self.push_term(&mut output, &term, Some(var));
+                }
+                continue;
+            }
+            let is_negative = inner[0].coefficient.is_negative();
+            let inner: Vec<Monomial<T>> = inner
+                .into_iter()
+                .map(|monomial| Monomial {
+                    coefficient: if is_negative {
+                        T::zero() - monomial.coefficient.clone()
+                    } else {
+                        monomial.coefficient.clone()
+                    },
+                    power_list: monomial.power_list.clone(),
+                })
+                .collect();
+            output.push_str(&self.sign(is_negative, !output.is_empty()));
+            let sum = self.sum(&inner.iter().collect::<Vec<_>>());
+            output.push_str(&self.product(vec![
+                self.parenthesized(&sum),
+                self.power(variable_symbol(var), power),
+            ]));
+        }
+        if output.is_empty() {
+            output = self.coefficient("0");
+        }
+        output
+    }
+
+    // A term after those in output, its sign and then the product of its
+    // factors, with the main variable last.
+    fn push_term(&self, output: &mut String, monomial: &Monomial<T>, main: Option<usize>) {
+        let (is_negative, magnitude) =
+            monomial.signed_coefficient(|c| (self.format)(c, &self.options));
+        output.push_str(&self.sign(is_negative, !output.is_empty()));
+        let main_symbol = main.map(variable_symbol);
+        let mut factors = vec![self.coefficient(&magnitude)];
+        for (symbol, power) in monomial.variable_powers() {
+            if Some(symbol) != main_symbol {
+                factors.push(self.power(symbol, power));
+            }
+        }
+        if let Some(var) = main.filter(|&var| monomial.power(var) != 0) {
+            factors.push(self.power(variable_symbol(var), monomial.power(var)));
+        }
+        output.push_str(&self.product(factors));
+    }
+}
+
+impl<'a, T: CRing> Rendered<'a, T> {
+    // The factors of a term side by side, spelled out as a product for the
+    // computer algebra systems.
+    fn product(&self, mut factors: Vec<String>) -> String {
+        factors.retain(|factor| !factor.is_empty());
+        factors.join(if self.options.notation.is_cas() {
+            "*"
+        } else {
+            ""
+        })
+    }
+
+    fn parenthesized(&self, text: &str) -> String {
+        match self.options.notation {
+            Notation::MathMl => format!("<mo>(</mo>{}<mo>)</mo>", text),
+            _ => format!("({})", text),
+        }
+    }
+
+    // The sign before a term, spaced as a binary operator after the first.
+    fn sign(&self, is_negative: bool, after_first: bool) -> String {
+        let sign = match (self.options.notation, is_negative) {
+            (_, false) if !after_first => return String::new(),
+            (Notation::MathMl, true) => "<mo>-</mo>",
+            (Notation::MathMl, false) => "<mo>+</mo>",
+            (Notation::Unicode, true) => "−",
+            (_, true) => "-",
+            (_, false) => "+",
+        };
+        match (self.options.notation, after_first) {
+            (Notation::MathMl, _) | (_, false) => String::from(sign),
+            _ => format!(" {} ", sign),
+        }
+    }
+
+    // A coefficient as written before the variables, without its sign.
+    fn coefficient(&self, coeff: &str) -> String {
+        match self.options.notation {
+            Notation::Ascii => String::from(coeff),
+            Notation::Unicode => coeff.replace('-', "−"),
+            Notation::Latex => numeric_runs(unwrap_fraction(coeff))
+                .map(|(run, is_number)| match run.split_once('/') {
+                    Some((numerator, denominator)) if is_number => {
+                        format!("\\frac{{{}}}{{{}}}", numerator, denominator)
+                    }
+                    _ => String::from(run),
+                })
+                .collect(),
+            Notation::MathMl => numeric_runs(unwrap_fraction(coeff))
+                .map(|(run, is_number)| {
+                    if !is_number {
+                        return run
+                            .chars()
+                            .filter(|c| !c.is_whitespace())
+                            .map(|c| {
+                                if c.is_alphabetic() {
+                                    format!("<mi>{}</mi>", c)
+                                } else {
+                                    format!("<mo>{}</mo>", c)
+                                }
+                            })
+                            .collect();
+                    }
+                    match run.split_once('/') {
+                        Some((numerator, denominator)) => format!(
+                            "<mfrac><mn>{}</mn><mn>{}</mn></mfrac>",
+                            numerator, denominator
+                        ),
+                        None => format!("<mn>{}</mn>", run),
+                    }
+                })
+                .collect(),
+            // the imaginary unit is I, and multiplies explicitly, and
+            // Mathematica writes 1.5e3 as 1.5*^3
+            _ => {
+                let coeff = unwrap_fraction(coeff);
+                let mut text = String::new();
+                for c in coeff.chars() {
+                    if c == 'e' && self.options.notation == Notation::Mathematica {
+                        text.push_str("*^");
+                    } else if c == 'i' {
+                        if text.ends_with(|c: char| c.is_ascii_digit() || c == '.') {
+                            text.push('*');
+                        }
+                        text.push('I');
+                    } else {
+                        text.push(c);
+                    }
+                }
+                text
+            }
+        }
+    }
+
+    // A variable to a power, the power left out when it is one.
+    fn power(&self, symbol: &str, power: i32) -> String {
+        match self.options.notation {
+            Notation::Ascii if power == 1 => String::from(symbol),
+            Notation::Ascii => format!("{}^{}", symbol, power),
+            Notation::Unicode if power == 1 => String::from(symbol),
+            Notation::Unicode => format!("{}{}", symbol, superscript(power)),
+            Notation::Latex => {
+                let symbol = match symbol.chars().next().and_then(greek_letter_name) {
+                    Some(command) => format!("\\{}", command),
+                    None => String::from(symbol),
+                };
+                if power == 1 {
+                    symbol
+                } else {
+                    format!("{}^{{{}}}", symbol, power)
+                }
+            }
+            Notation::MathMl if power == 1 => format!("<mi>{}</mi>", symbol),
+            Notation::MathMl => format!("<msup><mi>{}</mi><mn>{}</mn></msup>", symbol, power),
+            _ => {
+                let symbol = self.cas_name(symbol);
+                match (self.options.notation, power) {
+                    (_, 1) => symbol,
+                    (Notation::SymPy, _) => format!("{}**{}", symbol, power),
+                    _ => format!("{}^{}", symbol, power),
+                }
+            }
+        }
+    }
+
+    // A variable as a computer algebra system reads it. Mathematica reads the
+    // Greek letters themselves, the others their names. A name the system
+    // already gives a meaning to is spelled out for Mathematica, where π is
+    // Pi, and followed by '_' for the others, as lambda_.
+    fn cas_name(&self, symbol: &str) -> String {
+        let notation = self.options.notation;
+        let name = symbol
+            .chars()
+            .next()
+            .and_then(greek_letter_name)
+            .unwrap_or(symbol);
+        let reserved = |name: &str| notation.reserved_names().contains(&name);
+        match notation {
+            Notation::Mathematica if reserved(symbol) => String::from(name),
+            Notation::Mathematica => String::from(symbol),
+            _ if reserved(name) => format!("{}_", name),
+            _ => String::from(name),
+        }
+    }
+}
+
+// A fraction coefficient is parenthesized to keep it apart from the
+// variables, which notations with proper fractions do not need.
+fn unwrap_fraction(coeff: &str) -> &str {
+    match coeff.strip_prefix('(').and_then(|c| c.strip_suffix(')')) {
+        Some(inner) if inner.contains('/') && !inner.contains(['+', '-', '(']) => inner,
+        _ => coeff,
+    }
+}
+
+// Splits text into its numbers, such as 2.5 or 1/2, and the runs between
+// them, marking which are numbers.
+fn numeric_runs(text: &str) -> impl Iterator<Item = (&str, bool)> {
+    let is_numeric = |c: char| c.is_ascii_digit() || c == '.' || c == '/';
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let is_number = is_numeric(first);
+        let end = rest
+            .find(|c: char| is_numeric(c) != is_number)
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        rest = tail;
+        Some((run, is_number))
+    })
+}
+
+// The number in superscript digits, as in x².
+fn superscript(power: i32) -> String {
+    power
+        .to_string()
+        .chars()
+        .map(|c| match c {
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '-' => '⁻',
+            _ => char::from_u32('⁴' as u32 + c as u32 - '4' as u32).unwrap_or(c),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monomial::{variable_index, Monomial64, MonomialQ};
+    use crate::parser::{Parser64, ParserCQ, ParserQ, Syntax};
+    use crate::polynomial::{Polynomial64, PolynomialC, PolynomialCQ, PolynomialQ};
+    use rstest::*;
+
+    #[rstest]
+    #[case(Notation::Ascii, "2x^2y^3 - x + 1")]
+    #[case(Notation::Unicode, "2x²y³ − x + 1")]
+    #[case(Notation::Latex, "2x^{2}y^{3} - x + 1")]
+    #[case(
+        Notation::MathMl,
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mn>2</mn><msup><mi>x</mi><mn>2</mn></msup><msup><mi>y</mi><mn>3</mn></msup><mo>-</mo><mi>x</mi><mo>+</mo><mn>1</mn></mrow></math>"
+    )]
+    fn test_render_polynomial(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = Polynomial64::from("2x^2y^3 - x + 1").unwrap();
+        assert_eq!(format!("{}", polynomial.render(notation)), expected);
+    }
+
+    #[rstest]
+    #[case(Notation::Ascii, "-(1/2)x^10 + (9/4)α^2 - 1/16")]
+    #[case(Notation::Unicode, "−(1/2)x¹⁰ + (9/4)α² − 1/16")]
+    #[case(
+        Notation::Latex,
+        "-\\frac{1}{2}x^{10} + \\frac{9}{4}\\alpha^{2} - \\frac{1}{16}"
+    )]
+    #[case(
+        Notation::MathMl,
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mo>-</mo><mfrac><mn>1</mn><mn>2</mn></mfrac><msup><mi>x</mi><mn>10</mn></msup><mo>+</mo><mfrac><mn>9</mn><mn>4</mn></mfrac><msup><mi>α</mi><mn>2</mn></msup><mo>-</mo><mfrac><mn>1</mn><mn>16</mn></mfrac></mrow></math>"
+    )]
+    fn test_render_rational(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = PolynomialQ::from("-0.5x^10 + 2.25α^2 - 0.0625").unwrap();
+        assert_eq!(format!("{}", polynomial.render(notation)), expected);
+    }
+
+    #[rstest]
+    #[case(Notation::Unicode, "(1−2i)x² + 3iy")]
+    #[case(Notation::Latex, "(1-2i)x^{2} + 3iy")]
+    #[case(
+        Notation::MathMl,
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mo>(</mo><mn>1</mn><mo>-</mo><mn>2</mn><mi>i</mi><mo>)</mo><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>3</mn><mi>i</mi><mi>y</mi></mrow></math>"
+    )]
+    fn test_render_complex(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = PolynomialC::from("(1 - 2i)x^2 + 3iy").unwrap();
+        assert_eq!(format!("{}", polynomial.render(notation)), expected);
+    }
+
+    #[rstest]
+    fn test_render_complex_rational_latex() {
+        let polynomial = PolynomialCQ::from("x / (1 + i)").unwrap();
+        assert_eq!(
+            format!("{}", polynomial.render(Notation::Latex)),
+            "(\\frac{1}{2}-\\frac{1}{2}i)x"
+        );
+    }
+
+    #[rstest]
+    #[case(Notation::Ascii, "0")]
+    #[case(Notation::Latex, "0")]
+    #[case(
+        Notation::MathMl,
+        "<math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mn>0</mn></mrow></math>"
+    )]
+    fn test_render_zero(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = Polynomial64::new();
+        assert_eq!(format!("{}", polynomial.render(notation)), expected);
+    }
+
+    #[rstest]
+    fn test_render_monomial() {
+        let monomial = Monomial64::from("-3x^2z").unwrap();
+        assert_eq!(format!("{}", monomial.render(Notation::Unicode)), "−3x²z");
+        let monomial = MonomialQ::from("1.5xy^12").unwrap();
+        assert_eq!(
+            format!("{}", monomial.render(Notation::Latex)),
+            "\\frac{3}{2}xy^{12}"
+        );
+    }
+
+    #[rstest]
+    #[case(Notation::SymPy, "2*x**2*y**3 - 1/2*alpha + 1")]
+    #[case(Notation::Mathematica, "2*x^2*y^3 - 1/2*α + 1")]
+    #[case(Notation::Maple, "2*x^2*y^3 - 1/2*alpha + 1")]
+    #[case(Notation::Sage, "2*x^2*y^3 - 1/2*alpha + 1")]
+    fn test_render_cas(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = PolynomialQ::from("2x^2y^3 - α/2 + 1").unwrap();
+        assert_eq!(format!("{}", polynomial.render(notation)), expected);
+    }
+
+    #[rstest]
+    #[case(Notation::SymPy, "(1-2*I)*x**2 - I*y + 3*I")]
+    #[case(Notation::Maple, "(1-2*I)*x^2 - I*y + 3*I")]
+    fn test_render_cas_complex(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = PolynomialC::from("(1 - 2i)x^2 - iy + 3i").unwrap();
+        assert_eq!(format!("{}", polynomial.render(notation)), expected);
+    }
+
+    #[rstest]
+    fn test_render_cas_round_trip(
+        #[values(
+            Notation::SymPy,
+            Notation::Mathematica,
+            Notation::Maple,
+            Notation::Sage
+        )]
+        notation: Notation,
+        #[values(
+            "(x + y)^5",
+            "-x^2/3 + (1/2 - i/3)yz^10 - 7",
+            "(α - 2β)^2 + ω + 1/1000",
+            "-i x - 1"
+        )]
+        expr: &str,
+    ) {
+        let polynomial = PolynomialCQ::from(expr).unwrap();
+        let rendered = format!("{}", polynomial.render(notation));
+        let mut parser = ParserCQ::parser_init_with(rendered.clone(), Syntax::Cas).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(v, polynomial, "{}", rendered),
+            Err(e) => panic!("{}: {:?}", rendered, e),
+        }
+    }
+
+    #[rstest]
+    #[case(Notation::SymPy, "alpha*pi_ + lambda_**2 + gamma_ + eta")]
+    #[case(Notation::Mathematica, "α*pi + λ^2 + γ + η")]
+    #[case(Notation::Maple, "alpha*pi_ + lambda^2 + gamma_ + eta")]
+    #[case(Notation::Sage, "alpha*pi_ + lambda_^2 + gamma_ + eta_")]
+    fn test_render_cas_reserved(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = PolynomialQ::from("λ^2 + απ + γ + η").unwrap();
+        assert_eq!(format!("{}", polynomial.render(notation)), expected);
+    }
+
+    #[rstest]
+    fn test_render_cas_every_variable(
+        #[values(
+            Notation::SymPy,
+            Notation::Mathematica,
+            Notation::Maple,
+            Notation::Sage
+        )]
+        notation: Notation,
+    ) {
+        let expr: Vec<&str> = (0..)
+            .map(variable_symbol)
+            .take_while(|&v| v != "?")
+            .collect();
+        let polynomial = PolynomialQ::from(&expr.join(" + ")).unwrap();
+        let rendered = format!("{}", polynomial.render(notation));
+        for name in rendered.split(|c: char| !c.is_alphanumeric() && c != '_') {
+            assert!(!notation.reserved_names().contains(&name), "{}", rendered);
+        }
+        let mut parser = ParserQ::parser_init_with(rendered.clone(), Syntax::Cas).unwrap();
+        match parser.start_parser() {
+            Ok(v) => assert_eq!(v, polynomial, "{}", rendered),
+            Err(e) => panic!("{}: {:?}", rendered, e),
+        }
+    }
+
+    #[rstest]
+    #[case(Notation::SymPy, "2.5e3*x**2 - 1.5e-4*lambda_")]
+    #[case(Notation::Mathematica, "2.5*^3*x^2 - 1.5*^-4*λ")]
+    fn test_render_cas_scientific(#[case] notation: Notation, #[case] expected: &str) {
+        let polynomial = Polynomial64::from("2500x^2 - 0.00015λ").unwrap();
+        let options = FormatOptions {
+            notation,
+            scientific: true,
+            ..FormatOptions::default()
+        };
+        let rendered = format!("{}", polynomial.render_with(options));
+        assert_eq!(rendered, expected);
+        let mut parser = Parser64::parser_init_with(rendered, Syntax::Cas).unwrap();
+        assert_eq!(parser.start_parser().unwrap(), polynomial);
+    }
+
+    #[rstest]
+    #[case(TermOrder::Grlex, false, "y^4 + x^2z + xy^2 + z")]
+    #[case(TermOrder::Lex, false, "x^2z + xy^2 + y^4 + z")]
+    #[case(TermOrder::Grevlex, false, "y^4 + xy^2 + x^2z + z")]
+    #[case(TermOrder::Grlex, true, "z + xy^2 + x^2z + y^4")]
+    #[case(TermOrder::Lex, true, "z + y^4 + xy^2 + x^2z")]
+    fn test_render_order(
+        #[case] order: TermOrder,
+        #[case] ascending: bool,
+        #[case] expected: &str,
+    ) {
+        let polynomial = Polynomial64::from("x^2z + xy^2 + y^4 + z").unwrap();
+        let options = FormatOptions {
+            order,
+            ascending,
+            ..FormatOptions::default()
+        };
+        assert_eq!(format!("{}", polynomial.render_with(options)), expected);
+    }
+
+    #[rstest]
+    #[case(Some(2), false, "0.30x^2 - x + 2500.00y - 0.00")]
+    #[case(None, true, "3.0000000000000004e-1x^2 - x + 2.5e3y - 1.5e-4")]
+    #[case(Some(2), true, "3.00e-1x^2 - x + 2.50e3y - 1.50e-4")]
+    fn test_render_precision(
+        #[case] precision: Option<usize>,
+        #[case] scientific: bool,
+        #[case] expected: &str,
+    ) {
+        let polynomial = Polynomial64::from("(0.1 + 0.2)x^2 - x + 2500y - 0.00015").unwrap();
+        let options = FormatOptions {
+            precision,
+            scientific,
+            ..FormatOptions::default()
+        };
+        assert_eq!(format!("{}", polynomial.render_with(options)), expected);
+    }
+
+    #[rstest]
+    fn test_render_precision_complex() {
+        let polynomial = PolynomialC::from("(1/3 + 2i)x - i/3 + iy").unwrap();
+        let options = FormatOptions {
+            precision: Some(2),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format!("{}", polynomial.render_with(options)),
+            "(0.33+2.00i)x + iy - 0.33i"
+        );
+    }
+
+    #[rstest]
+    fn test_render_precision_exact() {
+        let polynomial = PolynomialQ::from("x/3 - 2").unwrap();
+        let options = FormatOptions {
+            precision: Some(2),
+            scientific: true,
+            ..FormatOptions::default()
+        };
+        assert_eq!(format!("{}", polynomial.render_with(options)), "(1/3)x - 2");
+    }
+
+    #[rstest]
+    #[case(0, false, Notation::Ascii, "(y + 1)x^2 - (y + 1)x + y^2 - 3")]
+    #[case(1, false, Notation::Ascii, "y^2 + (x^2 - x)y + x^2 - x - 3")]
+    #[case(0, true, Notation::Ascii, "-3 + y^2 - (1 + y)x + (1 + y)x^2")]
+    #[case(0, false, Notation::SymPy, "(y + 1)*x**2 - (y + 1)*x + y**2 - 3")]
+    #[case(0, false, Notation::Unicode, "(y + 1)x² − (y + 1)x + y² − 3")]
+    fn test_render_grouped(
+        #[case] var: usize,
+        #[case] ascending: bool,
+        #[case] notation: Notation,
+        #[case] expected: &str,
+    ) {
+        let polynomial = Polynomial64::from("x^2y + x^2 - xy - x + y^2 - 3").unwrap();
+        let options = FormatOptions {
+            notation,
+            ascending,
+            group_by: Some(var),
+            ..FormatOptions::default()
+        };
+        assert_eq!(format!("{}", polynomial.render_with(options)), expected);
+    }
+
+    #[rstest]
+    #[case("3x^2y + x", "3yx^2 + x")]
+    #[case("-x^2y - x^2 + 1", "-(y + 1)x^2 + 1")]
+    #[case("y - 2", "y - 2")]
+    #[case("0", "0")]
+    fn test_render_grouped_single_terms(#[case] expr: &str, #[case] expected: &str) {
+        let polynomial = Polynomial64::from(expr).unwrap();
+        let options = FormatOptions {
+            group_by: Some(0),
+            ..FormatOptions::default()
+        };
+        assert_eq!(format!("{}", polynomial.render_with(options)), expected);
+    }
+
+    #[rstest]
+    fn test_render_grouped_precision() {
+        let polynomial = Polynomial64::from("(0.1 + 0.2)x^2y + 0.3x^2 - xy - x + y^2").unwrap();
+        let options = FormatOptions {
+            precision: Some(2),
+            group_by: variable_index("x"),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format!("{}", polynomial.render_with(options)),
+            "(0.30y + 0.30)x^2 - (y + 1.00)x + y^2"
+        );
+    }
+
+    #[rstest]
+    fn test_render_negative_coefficients() {
+        let polynomial = Polynomial64::from("-x^2 - 2.5x - 1").unwrap();
+        let options = FormatOptions {
+            precision: Some(1),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format!("{}", polynomial.render_with(options)),
+            "-x^2 - 2.5x - 1.0"
+        );
+        let polynomial = PolynomialQ::from("-x/2 - 3y").unwrap();
+        assert_eq!(format!("{}", polynomial), "-(1/2)x - 3y");
+        let polynomial = PolynomialC::from("(-1 + 2i)x - 2y").unwrap();
+        assert_eq!(format!("{}", polynomial), "(-1+2i)x - 2y");
+    }
+
+    #[rstest]
+    fn test_is_negative() {
+        assert!(FormatCoefficient::is_negative(&-2i64));
+        assert!(!FormatCoefficient::is_negative(&0i64));
+        assert!(FormatCoefficient::is_negative(&-0.5f64));
+        assert!(FormatCoefficient::is_negative(&Ratio::new(-1, 2)));
+        assert!(FormatCoefficient::is_negative(&Complex::new(-2.0, 0.0)));
+        assert!(FormatCoefficient::is_negative(&Complex::new(0.0, -3.0)));
+        assert!(!FormatCoefficient::is_negative(&Complex::new(-1.0, 2.0)));
+        assert!(!FormatCoefficient::is_negative(&Complex::new(-1.0, -2.0)));
+        assert!(!FormatCoefficient::is_negative(&ModInt::<7>::new(6)));
+        let polynomial = Polynomial64::from("-xy + x^2").unwrap();
+        assert!(!FormatCoefficient::is_negative(&polynomial));
+        assert!(FormatCoefficient::is_negative(&-polynomial));
+    }
+
+    #[rstest]
+    #[case("grlex", TermOrder::Grlex)]
+    #[case("Lex", TermOrder::Lex)]
+    #[case("GREVLEX", TermOrder::Grevlex)]
+    fn test_term_order_from_str(#[case] name: &str, #[case] order: TermOrder) {
+        assert_eq!(name.parse::<TermOrder>(), Ok(order));
+        assert_eq!(format!("{}", order).parse::<TermOrder>(), Ok(order));
+    }
+
+    #[rstest]
+    #[case("ascii", Notation::Ascii)]
+    #[case("Unicode", Notation::Unicode)]
+    #[case("LATEX", Notation::Latex)]
+    #[case("mathml", Notation::MathMl)]
+    #[case("SymPy", Notation::SymPy)]
+    #[case("mathematica", Notation::Mathematica)]
+    #[case("maple", Notation::Maple)]
+    #[case("sage", Notation::Sage)]
+    fn test_notation_from_str(#[case] name: &str, #[case] notation: Notation) {
+        assert_eq!(name.parse::<Notation>(), Ok(notation));
+        assert_eq!(format!("{}", notation).parse::<Notation>(), Ok(notation));
+    }
+
+    #[rstest]
+    fn test_unknown_notation() {
+        assert!("html".parse::<Notation>().is_err());
+    }
+}
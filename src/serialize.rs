@@ -0,0 +1,328 @@
+use crate::exponents::Exponents;
+use crate::monomial::{variable_index, variable_symbol, Monomial};
+use crate::polynomial::Polynomial;
+use crate::CRing;
+use log::error;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{self, Serialize, Serializer};
+
+// Polynomials serialize as their variables, the order of their terms, and
+// the terms, each an exponent vector over the variables and a coefficient as
+// T serializes it. In JSON, 3x^2y - z is
+//
+//   {
+//     "variables": ["x", "y", "z"],
+//     "ordering": "grlex",
+//     "terms": [
+//       { "exponents": [2, 1, 0], "coefficient": 3.0 },
+//       { "exponents": [0, 0, 1], "coefficient": -1.0 }
+//     ]
+//   }
+//
+// The terms are listed from highest to lowest in the graded lexicographic
+// order with x > y > z > u > v > w > s > t > α > ... > ω. Deserializing
+// accepts the variables in any order and the terms in any order, and sums
+// like terms. Exponents may be negative, as in a monomial built by hand,
+// and read back as they were written.
+const ORDERING: &str = "grlex";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PolynomialRepr<C> {
+    variables: Vec<String>,
+    ordering: String,
+    terms: Vec<TermRepr<C>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TermRepr<C> {
+    exponents: Vec<i32>,
+    coefficient: C,
+}
+
+// Monomials serialize as a single term with its variables.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MonomialRepr<C> {
+    variables: Vec<String>,
+    exponents: Vec<i32>,
+    coefficient: C,
+}
+
+// The names of the first count variables.
+fn variable_names<E: ser::Error>(count: usize) -> Result<Vec<String>, E> {
+    (0..count)
+        .map(|ind| match variable_symbol(ind) {
+            "?" => {
+                let msg = format!("Variable {} has no name", ind);
+                error!("{}", msg);
+                Err(E::custom(msg))
+            }
+            symbol => Ok(String::from(symbol)),
+        })
+        .collect()
+}
+
+// The exponent vector, the exponents in the order of the checked variables.
+fn exponents_from_names<E: de::Error>(
+    variables: &[String],
+    exponents: &[i32],
+) -> Result<Exponents, E> {
+    let fail = |msg: String| {
+        error!("{}", msg);
+        Err(E::custom(msg))
+    };
+    if exponents.len() != variables.len() {
+        return fail(format!(
+            "Expected {} exponents, one for each variable, found {}",
+            variables.len(),
+            exponents.len()
+        ));
+    }
+    let mut powers = vec![0; variables.len()];
+    for (name, &power) in variables.iter().zip(exponents) {
+        // known by check_variables
+        let ind = variable_index(name).unwrap_or_default();
+        if powers.len() <= ind {
+            powers.resize(ind + 1, 0);
+        }
+        powers[ind] = power;
+    }
+    match Exponents::checked_from_slice(&powers) {
+        Some(v) => Ok(v),
+        None => fail(format!("Degree overflow in {:?}", exponents)),
+    }
+}
+
+// Every variable must be known, and listed once.
+fn check_variables<E: de::Error>(variables: &[String]) -> Result<(), E> {
+    for (ind, name) in variables.iter().enumerate() {
+        let msg = if variable_index(name).is_none() {
+            format!("Unknown variable {:?}", name)
+        } else if variables[..ind].contains(name) {
+            format!("Variable {:?} is listed twice", name)
+        } else {
+            continue;
+        };
+        error!("{}", msg);
+        return Err(E::custom(msg));
+    }
+    Ok(())
+}
+
+fn padded_exponents(power_list: &Exponents, count: usize) -> Vec<i32> {
+    (0..count).map(|ind| power_list.get(ind)).collect()
+}
+
+impl<T> Serialize for Polynomial<T>
+where
+    T: CRing + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let count = self
+            .monomials
+            .iter()
+            .map(|monomial| monomial.power_list.len())
+            .max()
+            .unwrap_or(0);
+        PolynomialRepr {
+            variables: variable_names(count)?,
+            ordering: String::from(ORDERING),
+            terms: self
+                .monomials
+                .iter()
+                .map(|monomial| TermRepr {
+                    exponents: padded_exponents(&monomial.power_list, count),
+                    coefficient: &monomial.coefficient,
+                })
+                .collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Polynomial<T>
+where
+    T: CRing + PartialEq + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = PolynomialRepr::<T>::deserialize(deserializer)?;
+        if repr.ordering != ORDERING {
+            let msg = format!(
+                "Unknown ordering {:?}, expected {:?}",
+                repr.ordering, ORDERING
+            );
+            error!("{}", msg);
+            return Err(de::Error::custom(msg));
+        }
+        check_variables(&repr.variables)?;
+        let mut polynomial = Polynomial::new();
+        for term in repr.terms {
+            polynomial.monomials.push(Monomial {
+                coefficient: term.coefficient,
+                power_list: exponents_from_names(&repr.variables, &term.exponents)?,
+            });
+        }
+        polynomial.normalize();
+        Ok(polynomial)
+    }
+}
+
+impl<T> Serialize for Monomial<T>
+where
+    T: CRing + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let count = self.power_list.len();
+        MonomialRepr {
+            variables: variable_names(count)?,
+            exponents: padded_exponents(&self.power_list, count),
+            coefficient: &self.coefficient,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Monomial<T>
+where
+    T: CRing + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = MonomialRepr::<T>::deserialize(deserializer)?;
+        check_variables(&repr.variables)?;
+        Ok(Monomial {
+            power_list: exponents_from_names(&repr.variables, &repr.exponents)?,
+            coefficient: repr.coefficient,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monomial::MonomialQ;
+    use crate::polynomial::{Polynomial64, PolynomialC, PolynomialQ, PolynomialZ};
+    use rstest::*;
+
+    #[rstest]
+    fn test_json_format() {
+        let polynomial = Polynomial64::from("3x^2y - z").unwrap();
+        assert_eq!(
+            serde_json::to_string(&polynomial).unwrap(),
+            "{\"variables\":[\"x\",\"y\",\"z\"],\"ordering\":\"grlex\",\"terms\":[{\"exponents\":[2,1,0],\"coefficient\":3.0},{\"exponents\":[0,0,1],\"coefficient\":-1.0}]}"
+        );
+    }
+
+    #[rstest]
+    #[case("0")]
+    #[case("7")]
+    #[case("(x + y + z)^4 - 3x^10")]
+    #[case("α^2β - 2ω + x")]
+    fn test_json_round_trip(#[case] expr: &str) {
+        let polynomial = Polynomial64::from(expr).unwrap();
+        let json = serde_json::to_string(&polynomial).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Polynomial64>(&json).unwrap(),
+            polynomial
+        );
+    }
+
+    #[rstest]
+    fn test_json_round_trip_negative_exponents() {
+        let mut polynomial = Polynomial64::new();
+        polynomial += Monomial {
+            coefficient: 2.0,
+            power_list: Exponents::from_slice(&[-2, 1]),
+        };
+        let json = serde_json::to_string(&polynomial).unwrap();
+        assert_eq!(
+            json,
+            r#"{"variables":["x","y"],"ordering":"grlex","terms":[{"exponents":[-2,1],"coefficient":2.0}]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Polynomial64>(&json).unwrap(),
+            polynomial
+        );
+    }
+
+    #[rstest]
+    fn test_json_round_trip_exact() {
+        let polynomial = PolynomialZ::from("(x + y)^80").unwrap();
+        let json = serde_json::to_string(&polynomial).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PolynomialZ>(&json).unwrap(),
+            polynomial
+        );
+        let polynomial = PolynomialQ::from("x/3 - 2.5y^2").unwrap();
+        let json = serde_json::to_string(&polynomial).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PolynomialQ>(&json).unwrap(),
+            polynomial
+        );
+        let polynomial = PolynomialC::from("(1 + 2i)x - iy").unwrap();
+        let json = serde_json::to_string(&polynomial).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PolynomialC>(&json).unwrap(),
+            polynomial
+        );
+    }
+
+    #[rstest]
+    fn test_json_reordered_variables_and_like_terms() {
+        let json = r#"{
+            "variables": ["z", "x"],
+            "ordering": "grlex",
+            "terms": [
+                { "exponents": [1, 0], "coefficient": 2.0 },
+                { "exponents": [0, 2], "coefficient": 1.0 },
+                { "exponents": [1, 0], "coefficient": 3.0 },
+                { "exponents": [2, 0], "coefficient": 0.0 }
+            ]
+        }"#;
+        let polynomial: Polynomial64 = serde_json::from_str(json).unwrap();
+        assert_eq!(polynomial, Polynomial64::from("x^2 + 5z").unwrap());
+    }
+
+    #[rstest]
+    #[case(
+        r#"{"variables":["x","q"],"ordering":"grlex","terms":[]}"#,
+        "Unknown variable \"q\""
+    )]
+    #[case(
+        r#"{"variables":["x","x"],"ordering":"grlex","terms":[]}"#,
+        "listed twice"
+    )]
+    #[case(
+        r#"{"variables":["x"],"ordering":"lex","terms":[]}"#,
+        "Unknown ordering"
+    )]
+    #[case(
+        r#"{"variables":["x"],"ordering":"grlex","terms":[{"exponents":[1,2],"coefficient":1.0}]}"#,
+        "Expected 1 exponents"
+    )]
+    fn test_json_invalid(#[case] json: &str, #[case] message: &str) {
+        let e = serde_json::from_str::<Polynomial64>(json).unwrap_err();
+        assert!(e.to_string().contains(message), "{}", e);
+    }
+
+    #[rstest]
+    fn test_monomial_json() {
+        let monomial = MonomialQ::from("1.5xz^2").unwrap();
+        let json = serde_json::to_string(&monomial).unwrap();
+        assert_eq!(
+            json,
+            "{\"variables\":[\"x\",\"y\",\"z\"],\"exponents\":[1,0,2],\"coefficient\":[[1,[3]],[1,[2]]]}"
+        );
+        assert_eq!(serde_json::from_str::<MonomialQ>(&json).unwrap(), monomial);
+    }
+
+    #[rstest]
+    fn test_unnamed_variable() {
+        let mut power_list = Exponents::new();
+        power_list.set(40, 1);
+        let mut polynomial = Polynomial64::new();
+        polynomial += Monomial {
+            coefficient: 1.0,
+            power_list,
+        };
+        assert!(serde_json::to_string(&polynomial).is_err());
+    }
+}